@@ -46,6 +46,11 @@ bench() {
   errcheck $?
 }
 
+header() {
+  cbindgen --config cbindgen.toml --output include/linebreak.h
+  errcheck $?
+}
+
 doc() {
   cargo doc
   errcheck $?
@@ -80,6 +85,9 @@ else
     doc)
       doc
       ;;
+    header)
+      header
+      ;;
     cover)
       cover
       ;;
@@ -0,0 +1,70 @@
+/* Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+ * This program is free software under MIT License.
+ * See the file LICENSE in this distribution for more details. */
+
+#ifndef LINEBREAK_H
+#define LINEBREAK_H
+
+/* Generated with cbindgen. Do not edit this file manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Breaks the specified NUL-terminated UTF-8 text into lines within the
+ * specified width, and returns a newly allocated string which joins the lines
+ * with `\n`.
+ *
+ * This function returns a null pointer if the text is null or not valid
+ * UTF-8, or if the width is zero.
+ * The returned string must be released with `linebreak_free_string`.
+ *
+ * # Safety
+ *
+ * `text` must be null or a pointer to a NUL-terminated string.
+ */
+char *linebreak_wrap(const char *text, size_t width);
+
+/**
+ * Releases a string returned by `linebreak_wrap`.
+ * This function does nothing if the pointer is null.
+ *
+ * # Safety
+ *
+ * `text` must be null or a pointer returned by `linebreak_wrap` which is not
+ * released yet.
+ */
+void linebreak_free_string(char *text);
+
+/**
+ * Returns the display width of the specified NUL-terminated UTF-8 text.
+ *
+ * This function returns 0 if the text is null or not valid UTF-8.
+ *
+ * # Safety
+ *
+ * `text` must be null or a pointer to a NUL-terminated string.
+ */
+size_t linebreak_text_width(const char *text);
+
+#if defined(LINEBREAK_FEATURE_TERMINAL)
+/**
+ * Returns the column number of the current terminal, or 80 if failing to
+ * retrieve it.
+ * This function is available with the `terminal` feature.
+ */
+size_t linebreak_term_cols(void);
+#endif
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* LINEBREAK_H */
@@ -0,0 +1,435 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+
+use crate::unicode::{text_width_with, AmbiguousWidth};
+
+/// `AnsiPolicy` is the enum for specifying how `LineIter` treats the ANSI
+/// escape sequences in a text, such as the SGR sequences for colors.
+///
+/// ```rust
+///     use linebreak::{AnsiPolicy, LineIter};
+///
+///     let text = "\u{1b}[31mred apples\u{1b}[0m and pears";
+///
+///     let mut iter = LineIter::new(text, 10);
+///     iter.set_ansi_policy(AnsiPolicy::Keep);
+///     assert_eq!(iter.next().unwrap(), "\u{1b}[31mred apples\u{1b}[0m");
+///     assert_eq!(iter.next().unwrap(), "and pears");
+///     assert!(iter.next().is_none());
+///
+///     let mut iter = LineIter::new(text, 6);
+///     iter.set_ansi_policy(AnsiPolicy::Reapply);
+///     assert_eq!(iter.next().unwrap(), "\u{1b}[31mred\u{1b}[0m");
+///     assert_eq!(iter.next().unwrap(), "\u{1b}[31mapples\u{1b}[0m");
+///     assert_eq!(iter.next().unwrap(), "and");
+///     assert_eq!(iter.next().unwrap(), "pears");
+///     assert!(iter.next().is_none());
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AnsiPolicy {
+    /// Escape sequences are not recognized, and the escape character is
+    /// treated according to `NonPrintPolicy` (default).
+    #[default]
+    Ignore,
+
+    /// Escape sequences are output verbatim, have width 0, and are never
+    /// divided by a line break.
+    Keep,
+
+    /// Escape sequences are kept as `AnsiPolicy::Keep`, and moreover the SGR
+    /// attributes and the OSC 8 hyperlink which are active at the end of a
+    /// line are reset and closed there, and re-applied at the start of the
+    /// next line, so that a colored run or a link divided into lines keeps
+    /// its color and stays clickable in pagers which reset the attributes at
+    /// each line end.
+    Reapply,
+}
+
+// The SGR sequence which resets all attributes.
+const SGR_RESET: &str = "\u{1b}[0m";
+
+// The OSC 8 sequence which closes a hyperlink.
+const LINK_CLOSE: &str = "\u{1b}]8;;\u{1b}\\";
+
+// Holds the SGR attributes and the OSC 8 hyperlink which are active at a
+// position in a text, so that they can be reset at the end of a line and
+// re-applied at the start of the next line.
+pub(crate) struct AnsiState {
+    attrs: u16,
+    fg: String,
+    bg: String,
+    underline_color: String,
+    link: String,
+}
+
+impl AnsiState {
+    pub fn new() -> AnsiState {
+        AnsiState {
+            attrs: 0,
+            fg: String::new(),
+            bg: String::new(),
+            underline_color: String::new(),
+            link: String::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.clear_attrs();
+        self.link.clear();
+    }
+
+    fn clear_attrs(&mut self) {
+        self.attrs = 0;
+        self.fg.clear();
+        self.bg.clear();
+        self.underline_color.clear();
+    }
+
+    fn has_attrs(&self) -> bool {
+        self.attrs != 0
+            || !self.fg.is_empty()
+            || !self.bg.is_empty()
+            || !self.underline_color.is_empty()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.has_attrs() || !self.link.is_empty()
+    }
+
+    // Updates the state with the SGR sequences and the OSC 8 sequences in the
+    // specified text.
+    pub fn update(&mut self, text: &str) {
+        let mut rest = text;
+        while let Some(i) = find_escape(rest) {
+            let seq = &rest[i..(i + escape_len(&rest[i..]))];
+            rest = &rest[(i + seq.len())..];
+            if let Some(link) = seq
+                .strip_prefix("\u{1b}]8;")
+                .or_else(|| seq.strip_prefix("\u{9d}8;"))
+            {
+                // a hyperlink is closed by an OSC 8 sequence with an empty URI.
+                let link = link
+                    .strip_suffix('\u{07}')
+                    .or_else(|| link.strip_suffix("\u{1b}\\"))
+                    .or_else(|| link.strip_suffix('\u{9c}'))
+                    .unwrap_or(link);
+                let uri = link.split_once(';').map_or("", |(_, uri)| uri);
+                self.link.clear();
+                if !uri.is_empty() {
+                    self.link.push_str(seq);
+                }
+                continue;
+            }
+            let params = seq
+                .strip_prefix("\u{1b}[")
+                .or_else(|| seq.strip_prefix('\u{9b}'))
+                .and_then(|s| s.strip_suffix('m'));
+            match params {
+                Some(params)
+                    if params
+                        .bytes()
+                        .all(|b| b.is_ascii_digit() || b == b';' || b == b':') =>
+                {
+                    self.apply(params)
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn apply(&mut self, params: &str) {
+        let mut params = params.split(';');
+        while let Some(param) = params.next() {
+            // an extended color, such as `38;5;208` and `38;2;255;128;0`.
+            let mut color = String::from(param);
+            if matches!(param, "38" | "48" | "58") {
+                let n = match params.next() {
+                    Some("5") => 1,
+                    Some("2") => 3,
+                    _ => return,
+                };
+                color.push_str(if n == 1 { ";5" } else { ";2" });
+                for _ in 0..n {
+                    color.push(';');
+                    color.push_str(params.next().unwrap_or("0"));
+                }
+            }
+            let code = param.split(':').next().unwrap_or("");
+            match code.parse::<u16>().unwrap_or(0) {
+                0 => self.clear_attrs(),
+                n @ 1..=9 => self.attrs |= 1 << n,
+                22 => self.attrs &= !((1 << 1) | (1 << 2)),
+                25 => self.attrs &= !((1 << 5) | (1 << 6)),
+                n @ (23 | 24 | 27 | 28 | 29) => self.attrs &= !(1 << (n - 20)),
+                30..=38 | 90..=97 => self.fg = color,
+                39 => self.fg.clear(),
+                40..=48 | 100..=107 => self.bg = color,
+                49 => self.bg.clear(),
+                58 => self.underline_color = color,
+                59 => self.underline_color.clear(),
+                _ => (),
+            }
+        }
+    }
+
+    // Writes the sequences which apply the active attributes and open the
+    // active hyperlink into the specified buffer.
+    pub fn push_start(&self, buf: &mut String) {
+        if self.has_attrs() {
+            self.push_attrs(buf);
+        }
+        buf.push_str(&self.link);
+    }
+
+    // Writes the sequences which close the active hyperlink and reset the
+    // active attributes into the specified buffer.
+    pub fn push_end(&self, buf: &mut String) {
+        if !self.link.is_empty() {
+            buf.push_str(LINK_CLOSE);
+        }
+        if self.has_attrs() {
+            buf.push_str(SGR_RESET);
+        }
+    }
+
+    fn push_attrs(&self, buf: &mut String) {
+        buf.push_str("\u{1b}[");
+        let mut sep = "";
+        for n in 1..=9 {
+            if self.attrs & (1 << n) != 0 {
+                buf.push_str(sep);
+                buf.push(char::from(b'0' + n));
+                sep = ";";
+            }
+        }
+        for color in [&self.fg, &self.bg, &self.underline_color] {
+            if !color.is_empty() {
+                buf.push_str(sep);
+                buf.push_str(color);
+                sep = ";";
+            }
+        }
+        buf.push('m');
+    }
+}
+
+/// Removes the ANSI escape sequences from the specified text, such as the
+/// SGR sequences for colors (`ESC [ ... m`), the other CSI sequences and the
+/// OSC sequences for hyperlinks and window titles (`ESC ] ... BEL`).
+///
+/// An unterminated escape sequence is removed up to the end of the text.
+/// If the text has no escape sequence, this function does not allocate.
+///
+/// ```rust
+///     use linebreak::strip_ansi;
+///
+///     assert_eq!(strip_ansi("\u{1b}[1;31mError\u{1b}[0m: failed"), "Error: failed");
+///     assert_eq!(strip_ansi("\u{1b}]0;title\u{07}abc"), "abc");
+///     assert_eq!(strip_ansi("plain text"), "plain text");
+/// ```
+pub fn strip_ansi(text: &str) -> Cow<'_, str> {
+    let Some(mut i) = find_escape(text) else {
+        return Cow::Borrowed(text);
+    };
+    let mut stripped = String::with_capacity(text.len());
+    let mut rest = text;
+    loop {
+        stripped.push_str(&rest[..i]);
+        rest = &rest[i + escape_len(&rest[i..])..];
+        match find_escape(rest) {
+            Some(j) => i = j,
+            None => break,
+        }
+    }
+    stripped.push_str(rest);
+    Cow::Owned(stripped)
+}
+
+/// Returns the display width of the specified text, skipping the ANSI escape
+/// sequences which `strip_ansi` removes.
+///
+/// The width of each character is the same as `text_width`.
+///
+/// ```rust
+///     use linebreak::{text_width, visible_width};
+///
+///     let text = "\u{1b}[32mこんにちは\u{1b}[0m, world";
+///     assert_eq!(visible_width(text), 17);
+///     assert_eq!(text_width(text), 24);
+/// ```
+pub fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut rest = text;
+    while let Some(i) = find_escape(rest) {
+        width += text_width_with(&rest[..i], AmbiguousWidth::Wide);
+        rest = &rest[i + escape_len(&rest[i..])..];
+    }
+    width + text_width_with(rest, AmbiguousWidth::Wide)
+}
+
+// Returns the byte position of the first escape sequence in the specified
+// text, which starts with ESC, or a C1 control character of CSI or OSC.
+pub(crate) fn find_escape(text: &str) -> Option<usize> {
+    text.find(['\u{1b}', '\u{9b}', '\u{9d}'])
+}
+
+// Returns the byte length of the escape sequence at the start of the
+// specified text, which starts with ESC, CSI (U+009B) or OSC (U+009D).
+pub(crate) fn escape_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let (kind, start) = match text.chars().next() {
+        Some('\u{9b}') => (b'[', 2),
+        Some('\u{9d}') => (b']', 2),
+        _ => match bytes.get(1) {
+            Some(b) => (*b, 2),
+            None => return bytes.len(),
+        },
+    };
+    match kind {
+        // CSI: parameter and intermediate bytes, and a final byte.
+        b'[' => match bytes[start..]
+            .iter()
+            .position(|b| !(0x20..=0x3f).contains(b))
+        {
+            Some(i) if (0x40..=0x7e).contains(&bytes[start + i]) => start + i + 1,
+            Some(i) => start + i,
+            None => bytes.len(),
+        },
+        // OSC, DCS, SOS, PM and APC: a string terminated by BEL or ST.
+        b']' | b'P' | b'X' | b'^' | b'_' => {
+            let mut i = start;
+            while i < bytes.len() {
+                match bytes[i] {
+                    0x07 if kind == b']' => return i + 1,
+                    0x1b if bytes.get(i + 1) == Some(&b'\\') => return i + 2,
+                    0xc2 if bytes.get(i + 1) == Some(&0x9c) => return i + 2,
+                    _ => i += 1,
+                }
+            }
+            bytes.len()
+        }
+        // the other sequences: intermediate bytes and a final byte.
+        _ => match bytes[1..].iter().position(|b| !(0x20..=0x2f).contains(b)) {
+            Some(i) if (0x30..=0x7e).contains(&bytes[1 + i]) => i + 2,
+            Some(i) => i + 1,
+            None => bytes.len(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test_of_ansi {
+    use super::*;
+
+    #[test]
+    fn test_escape_len() {
+        assert_eq!(escape_len("\u{1b}[31mabc"), 5);
+        assert_eq!(escape_len("\u{1b}[38;5;208mabc"), 11);
+        assert_eq!(escape_len("\u{1b}[?25habc"), 6);
+        assert_eq!(escape_len("\u{9b}31mabc"), 5);
+        assert_eq!(escape_len("\u{1b}]8;;http://a\u{1b}\\abc"), 15);
+        assert_eq!(escape_len("\u{1b}]0;title\u{07}abc"), 10);
+        assert_eq!(escape_len("\u{9d}0;t\u{9c}abc"), 7);
+        assert_eq!(escape_len("\u{1b}Pq#0\u{1b}\\abc"), 7);
+        assert_eq!(escape_len("\u{1b}(Babc"), 3);
+        assert_eq!(escape_len("\u{1b}7abc"), 2);
+        assert_eq!(escape_len("\u{1b}"), 1);
+        assert_eq!(escape_len("\u{1b}[31"), 4);
+        assert_eq!(escape_len("\u{1b}]0;title"), 9);
+    }
+
+    #[test]
+    fn test_escape_len_of_broken_sequences() {
+        // a sequence interrupted by a non-final character ends before it.
+        assert_eq!(escape_len("\u{1b}[31\nabc"), 4);
+        assert_eq!(escape_len("\u{1b}\nabc"), 1);
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi(""), "");
+        assert!(matches!(strip_ansi("abc"), Cow::Borrowed("abc")));
+        assert_eq!(strip_ansi("\u{1b}[1m\u{1b}[31mab\u{1b}[0mc\u{1b}[K"), "abc");
+        assert_eq!(
+            strip_ansi("\u{1b}]8;;https://example.com\u{1b}\\link\u{1b}]8;;\u{1b}\\"),
+            "link"
+        );
+        assert_eq!(strip_ansi("abc\u{1b}[3"), "abc");
+    }
+
+    fn start_of(text: &str) -> String {
+        let mut state = AnsiState::new();
+        state.update(text);
+        let mut s = String::new();
+        state.push_start(&mut s);
+        s
+    }
+
+    fn end_of(text: &str) -> String {
+        let mut state = AnsiState::new();
+        state.update(text);
+        let mut s = String::new();
+        state.push_end(&mut s);
+        s
+    }
+
+    #[test]
+    fn test_sgr_state() {
+        assert_eq!(start_of(""), "");
+        assert_eq!(start_of("\u{1b}[31mabc"), "\u{1b}[31m");
+        assert_eq!(start_of("\u{1b}[1m\u{1b}[4;32mabc"), "\u{1b}[1;4;32m");
+        assert_eq!(start_of("\u{1b}[1;31mab\u{1b}[0mc"), "");
+        assert_eq!(start_of("\u{1b}[1;31mab\u{1b}[mc"), "");
+        assert_eq!(start_of("\u{1b}[1;31;44m\u{1b}[22;39m"), "\u{1b}[44m");
+        assert_eq!(start_of("\u{1b}[31m\u{1b}[92m"), "\u{1b}[92m");
+        assert_eq!(
+            start_of("\u{1b}[38;5;208;48;2;1;2;3m"),
+            "\u{1b}[38;5;208;48;2;1;2;3m"
+        );
+        assert_eq!(start_of("\u{1b}[4:3m\u{1b}[58:5:9m"), "\u{1b}[4;58:5:9m");
+        assert_eq!(start_of("\u{1b}[7m\u{1b}[27m\u{1b}[K"), "");
+        assert_eq!(start_of("\u{1b}[?1m\u{1b}]0;x\u{07}"), "");
+
+        assert_eq!(end_of(""), "");
+        assert_eq!(end_of("\u{1b}[31mabc"), "\u{1b}[0m");
+        assert_eq!(end_of("\u{1b}[1;31mab\u{1b}[0mc"), "");
+        assert_eq!(end_of("\u{1b}[7m\u{1b}[27m\u{1b}[K"), "");
+    }
+
+    #[test]
+    fn test_ansi_state_of_hyperlinks() {
+        let open = "\u{1b}]8;id=1;https://example.com\u{1b}\\";
+        let close = "\u{1b}]8;;\u{1b}\\";
+        assert_eq!(start_of(&format!("{}link", open)), open);
+        assert_eq!(end_of(&format!("{}link", open)), close);
+        assert_eq!(start_of(&format!("{}link{}", open, close)), "");
+        assert_eq!(end_of(&format!("{}link{}", open, close)), "");
+
+        let open = "\u{1b}]8;;https://example.com\u{07}";
+        let text = format!("\u{1b}[1m{}link\u{1b}[0m", open);
+        assert_eq!(start_of(&text), open);
+        assert_eq!(end_of(&text), close);
+        let text = format!("\u{1b}[1m{}link", open);
+        assert_eq!(start_of(&text), format!("\u{1b}[1m{}", open));
+        assert_eq!(end_of(&text), format!("{}\u{1b}[0m", close));
+        assert_eq!(end_of("\u{1b}]8;;\u{07}"), "");
+    }
+
+    #[test]
+    fn test_visible_width() {
+        assert_eq!(visible_width(""), 0);
+        assert_eq!(visible_width("abc"), 3);
+        assert_eq!(visible_width("\u{1b}[31mあいう\u{1b}[0m"), 6);
+        assert_eq!(
+            visible_width("\u{1b}]8;;https://example.com\u{07}link\u{1b}]8;;\u{07}"),
+            4
+        );
+        assert_eq!(visible_width("\u{9b}1mα"), 2);
+    }
+}
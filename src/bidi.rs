@@ -0,0 +1,204 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::unicode::is_zero_width_attached;
+use icu::properties::maps;
+use icu::properties::maps::CodePointMapDataBorrowed;
+use icu::properties::BidiClass;
+
+const BIDI_CLASS: CodePointMapDataBorrowed<'static, BidiClass> = maps::bidi_class();
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum BidiType {
+    L,
+    R,
+    EN,
+    AN,
+    N,
+}
+
+// Returns `Some(true)` if the specified character is a strong right-to-left
+// character, `Some(false)` if it is a strong left-to-right character, and
+// `None` otherwise.
+pub fn strong_rtl(ch: char) -> Option<bool> {
+    match BIDI_CLASS.get(ch) {
+        BidiClass::LeftToRight => Some(false),
+        BidiClass::RightToLeft | BidiClass::ArabicLetter => Some(true),
+        _ => None,
+    }
+}
+
+// Checks whether the paragraph which starts with the specified text is
+// right-to-left, by its first strong character.
+pub fn is_rtl_paragraph(text: &str) -> bool {
+    for ch in text.chars() {
+        if matches!(ch, '\n' | '\r' | '\u{2029}') {
+            break;
+        }
+        if let Some(rtl) = strong_rtl(ch) {
+            return rtl;
+        }
+    }
+    false
+}
+
+// Reorders the characters of the specified line from the logical order to the
+// display order, with a simplified version of the Unicode Bidirectional
+// Algorithm (UAX #9) which has no explicit embeddings.
+// Characters with no width which are attached to a character are kept after it.
+pub fn reorder_line(line: &str, rtl: bool) -> String {
+    let mut clusters: Vec<Range<usize>> = Vec::new();
+    let mut types: Vec<BidiType> = Vec::new();
+    let mut prev_strong = if rtl { BidiType::R } else { BidiType::L };
+
+    for (i, ch) in line.char_indices() {
+        if let Some(last) = clusters.last_mut() {
+            if is_zero_width_attached(ch) {
+                last.end = i + ch.len_utf8();
+                continue;
+            }
+        }
+        clusters.push(i..(i + ch.len_utf8()));
+        let t = match BIDI_CLASS.get(ch) {
+            BidiClass::LeftToRight => BidiType::L,
+            BidiClass::RightToLeft | BidiClass::ArabicLetter => BidiType::R,
+            BidiClass::EuropeanNumber if prev_strong == BidiType::L => BidiType::L,
+            BidiClass::EuropeanNumber => BidiType::EN,
+            BidiClass::ArabicNumber => BidiType::AN,
+            _ => BidiType::N,
+        };
+        if matches!(t, BidiType::L | BidiType::R) {
+            prev_strong = t;
+        }
+        types.push(t);
+    }
+
+    // resolves neutrals by the directions of the surrounding characters.
+    let base = if rtl { BidiType::R } else { BidiType::L };
+    let direction = |t: BidiType| match t {
+        BidiType::L => BidiType::L,
+        _ => BidiType::R,
+    };
+    let mut i = 0;
+    while i < types.len() {
+        if types[i] != BidiType::N {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < types.len() && types[i] == BidiType::N {
+            i += 1;
+        }
+        let before = if start > 0 {
+            direction(types[start - 1])
+        } else {
+            base
+        };
+        let after = if i < types.len() {
+            direction(types[i])
+        } else {
+            base
+        };
+        let resolved = if before == after { before } else { base };
+        for t in &mut types[start..i] {
+            *t = resolved;
+        }
+    }
+
+    let levels: Vec<u8> = types
+        .iter()
+        .map(|t| match (rtl, t) {
+            (false, BidiType::L) => 0,
+            (false, BidiType::R) => 1,
+            (true, BidiType::R) => 1,
+            _ => 2,
+        })
+        .collect();
+
+    // reverses the runs from the highest level to the lowest odd level.
+    let mut order: Vec<usize> = (0..clusters.len()).collect();
+    let max = levels.iter().copied().max().unwrap_or(0);
+    let mut level = max;
+    while level >= 1 {
+        let mut j = 0;
+        while j < order.len() {
+            if levels[order[j]] < level {
+                j += 1;
+                continue;
+            }
+            let start = j;
+            while j < order.len() && levels[order[j]] >= level {
+                j += 1;
+            }
+            order[start..j].reverse();
+        }
+        level -= 1;
+    }
+
+    let mut reordered = String::with_capacity(line.len());
+    for i in order {
+        reordered.push_str(&line[clusters[i].clone()]);
+    }
+    reordered
+}
+
+#[cfg(test)]
+mod test_of_bidi {
+    use super::*;
+
+    #[test]
+    fn test_strong_rtl() {
+        assert_eq!(strong_rtl('a'), Some(false));
+        assert_eq!(strong_rtl('\u{05D0}'), Some(true));
+        assert_eq!(strong_rtl('\u{0639}'), Some(true));
+        assert_eq!(strong_rtl('1'), None);
+        assert_eq!(strong_rtl(' '), None);
+    }
+
+    #[test]
+    fn test_is_rtl_paragraph() {
+        assert!(!is_rtl_paragraph(""));
+        assert!(!is_rtl_paragraph("abc \u{05D0}"));
+        assert!(is_rtl_paragraph("1. \u{05D0}bc"));
+        assert!(!is_rtl_paragraph("1.\n\u{05D0}"));
+    }
+
+    #[test]
+    fn test_reorder_ltr_line() {
+        assert_eq!(reorder_line("", false), "");
+        assert_eq!(reorder_line("abc def", false), "abc def");
+        assert_eq!(
+            reorder_line("ab \u{05D0}\u{05D1} \u{05D2} cd", false),
+            "ab \u{05D2} \u{05D1}\u{05D0} cd"
+        );
+    }
+
+    #[test]
+    fn test_reorder_rtl_line() {
+        assert_eq!(
+            reorder_line("\u{05D0}\u{05D1} \u{05D2}", true),
+            "\u{05D2} \u{05D1}\u{05D0}"
+        );
+        assert_eq!(
+            reorder_line("\u{05D0} abc def \u{05D1}", true),
+            "\u{05D1} abc def \u{05D0}"
+        );
+        assert_eq!(
+            reorder_line("\u{05D0} 123 \u{05D1}", true),
+            "\u{05D1} 123 \u{05D0}"
+        );
+    }
+
+    #[test]
+    fn test_reorder_keeps_marks_after_base() {
+        assert_eq!(
+            reorder_line("\u{05D0}\u{05B8}\u{05D1}", true),
+            "\u{05D1}\u{05D0}\u{05B8}"
+        );
+    }
+}
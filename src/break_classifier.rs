@@ -0,0 +1,180 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use crate::code::CodeState;
+use crate::linebreak::{line_break_opportunity, Kinsoku, LboState, LboType};
+use crate::options::BreakMode;
+use crate::uax14::Uax14State;
+
+/// `BreakClassifier` is the struct for determining the line break opportunity
+/// of each character in a text with the same rules as `LineIter`.
+///
+/// This struct is useful for other layout code, such as tables and widgets
+/// of text user interfaces, which breaks texts by itself.
+/// Since some rules depend on the preceding characters, the characters of a
+/// text must be passed to `classify` in order.
+///
+/// ```rust
+///     use linebreak::{BreakClassifier, BreakMode, LboType};
+///
+///     let mut classifier = BreakClassifier::new(BreakMode::Basic);
+///     let types: Vec<LboType> = "a (b)".chars().map(|c| classifier.classify(c)).collect();
+///     assert_eq!(
+///         types,
+///         [LboType::Never, LboType::Space, LboType::Before, LboType::Never, LboType::After]
+///     );
+/// ```
+pub struct BreakClassifier {
+    mode: BreakMode,
+    state: LboState,
+    uax14: Uax14State,
+    code: CodeState,
+    kinsoku: Kinsoku,
+}
+
+impl BreakClassifier {
+    /// Creates a `BreakClassifier` instance which uses the rules of the
+    /// specified `BreakMode`.
+    pub fn new(mode: BreakMode) -> BreakClassifier {
+        BreakClassifier {
+            mode,
+            state: LboState {
+                lbo_type: LboType::Never,
+                lbo_prev: LboType::Never,
+                open_apos: 0,
+                open_quot: 0,
+            },
+            uax14: Uax14State::new(),
+            code: CodeState::new(),
+            kinsoku: Kinsoku::new(),
+        }
+    }
+
+    /// Returns the line break opportunity of the specified character, which
+    /// follows the characters passed to this method before.
+    pub fn classify(&mut self, ch: char) -> LboType {
+        match self.mode {
+            BreakMode::Basic => line_break_opportunity(ch, &mut self.state),
+            BreakMode::Uax14 => self.uax14.line_break_opportunity(ch, &mut self.state),
+            BreakMode::Code => self.code.line_break_opportunity(ch, &mut self.state),
+        }
+        self.kinsoku.apply(ch, &mut self.state);
+        self.state.lbo_type
+    }
+
+    /// Adds the specified character to the set of the characters before which
+    /// a line must not be broken.
+    pub fn add_no_break_before(&mut self, ch: char) {
+        self.kinsoku.set_no_break_before(ch, true);
+    }
+
+    /// Removes the specified character from the set of the characters before
+    /// which a line must not be broken.
+    pub fn remove_no_break_before(&mut self, ch: char) {
+        self.kinsoku.set_no_break_before(ch, false);
+    }
+
+    /// Adds the specified character to the set of the characters after which
+    /// a line must not be broken.
+    pub fn add_no_break_after(&mut self, ch: char) {
+        self.kinsoku.set_no_break_after(ch, true);
+    }
+
+    /// Removes the specified character from the set of the characters after
+    /// which a line must not be broken.
+    pub fn remove_no_break_after(&mut self, ch: char) {
+        self.kinsoku.set_no_break_after(ch, false);
+    }
+
+    /// Clears the state which depends on the preceding characters, for
+    /// classifying the characters of another text.
+    pub fn reset(&mut self) {
+        self.state.lbo_type = LboType::Never;
+        self.state.lbo_prev = LboType::Never;
+        self.state.open_apos = 0;
+        self.state.open_quot = 0;
+        self.uax14.reset();
+        self.code.reset();
+    }
+}
+
+impl Default for BreakClassifier {
+    fn default() -> Self {
+        BreakClassifier::new(BreakMode::Basic)
+    }
+}
+
+#[cfg(test)]
+mod test_of_break_classifier {
+    use super::*;
+
+    fn classify_all(classifier: &mut BreakClassifier, text: &str) -> Vec<LboType> {
+        text.chars().map(|c| classifier.classify(c)).collect()
+    }
+
+    #[test]
+    fn test_classify_basic() {
+        let mut classifier = BreakClassifier::default();
+        assert_eq!(
+            classify_all(&mut classifier, "あ、\"b\"\n"),
+            [
+                LboType::Both,
+                LboType::After,
+                LboType::Before,
+                LboType::Never,
+                LboType::After,
+                LboType::Break,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_classify_uax14() {
+        let mut classifier = BreakClassifier::new(BreakMode::Uax14);
+        assert_eq!(
+            classify_all(&mut classifier, "a-b"),
+            [LboType::Never, LboType::Never, LboType::Both]
+        );
+    }
+
+    #[test]
+    fn test_classify_code() {
+        let mut classifier = BreakClassifier::new(BreakMode::Code);
+        assert_eq!(
+            classify_all(&mut classifier, "f(\"a,b\")"),
+            [
+                LboType::Never,
+                LboType::After,
+                LboType::Never,
+                LboType::Never,
+                LboType::Never,
+                LboType::Never,
+                LboType::Never,
+                LboType::Never,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_kinsoku() {
+        let mut classifier = BreakClassifier::default();
+        classifier.add_no_break_after('$');
+        classifier.add_no_break_before('%');
+        assert_eq!(classifier.classify('$'), LboType::Before);
+        assert_eq!(classifier.classify('%'), LboType::After);
+        classifier.remove_no_break_after('$');
+        classifier.remove_no_break_before('%');
+        assert_eq!(classifier.classify('$'), LboType::Never);
+        assert_eq!(classifier.classify('%'), LboType::Never);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut classifier = BreakClassifier::default();
+        assert_eq!(classifier.classify('"'), LboType::Before);
+        classifier.reset();
+        assert_eq!(classifier.classify('"'), LboType::Before);
+        assert_eq!(classifier.classify('"'), LboType::After);
+    }
+}
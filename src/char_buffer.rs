@@ -2,33 +2,54 @@
 // This program is free software under MIT License.
 // See the file LICENSE in this distribution for more details.
 
-use std::cmp::min;
+#![allow(clippy::needless_return, clippy::bool_assert_comparison)]
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::min;
+use core::ops::Range;
 
 pub struct CharBuffer {
     ch_vec: Vec<char>,
+    pos_vec: Vec<usize>,
+    keeps_ideographic_space: bool,
 }
 
 impl CharBuffer {
     pub fn new(capacity: usize) -> CharBuffer {
         CharBuffer {
             ch_vec: Vec::with_capacity(capacity),
+            pos_vec: Vec::with_capacity(capacity),
+            keeps_ideographic_space: false,
         }
     }
 
-    pub fn add(&mut self, ch: char) -> bool {
-        if self.ch_vec.len() >= self.ch_vec.capacity() {
-            return false;
-        }
+    // Sets whether to keep ideographic spaces (U+3000) at the end of a line
+    // instead of trimming them as the other whitespace.
+    pub fn set_keeps_ideographic_space(&mut self, enabled: bool) {
+        self.keeps_ideographic_space = enabled;
+    }
+
+    fn is_trimmed(&self, ch: char) -> bool {
+        ch.is_whitespace() && !(self.keeps_ideographic_space && ch == '\u{3000}')
+    }
+
+    // Adds a character and its byte position in the source text.
+    // The buffer is not bounded by the initial capacity, because zero-width
+    // characters attached to the previous one are kept in it too, and so the
+    // number of characters in a line is not limited by the line width.
+    pub fn add(&mut self, ch: char, pos: usize) {
         self.ch_vec.push(ch);
-        return true;
+        self.pos_vec.push(pos);
     }
 
     pub fn cr(&mut self, start: usize) {
         if start >= self.ch_vec.len() {
-            self.ch_vec.clear();
+            self.clear();
             return;
         }
         self.ch_vec.drain(..start);
+        self.pos_vec.drain(..start);
     }
 
     #[cfg(test)]
@@ -38,6 +59,7 @@ impl CharBuffer {
 
     pub fn clear(&mut self) {
         self.ch_vec.clear();
+        self.pos_vec.clear();
     }
 
     pub fn len(&self) -> usize {
@@ -48,6 +70,10 @@ impl CharBuffer {
         self.ch_vec.is_empty()
     }
 
+    pub fn pos(&self, i: usize) -> Option<usize> {
+        self.pos_vec.get(i).copied()
+    }
+
     pub fn get(&self, i: usize) -> Option<char> {
         if i >= self.ch_vec.len() {
             return None;
@@ -59,7 +85,7 @@ impl CharBuffer {
         let n = self.ch_vec.len();
         if n > 0 {
             for i in 1..=n {
-                if !self.ch_vec[n - i].is_whitespace() {
+                if !self.is_trimmed(self.ch_vec[n - i]) {
                     return String::from_iter(&self.ch_vec[0..(n - i + 1)]);
                 }
             }
@@ -73,13 +99,48 @@ impl CharBuffer {
             let n = end - start;
             for i in 1..=n {
                 let last = start + n - i;
-                if !self.ch_vec[last].is_whitespace() {
+                if !self.is_trimmed(self.ch_vec[last]) {
                     return String::from_iter(&self.ch_vec[start..(last + 1)]);
                 }
             }
         }
         return String::from("");
     }
+
+    pub fn slice_trimmed_end<'a>(
+        &self,
+        text: &'a str,
+        start: usize,
+        end: usize,
+    ) -> Option<&'a str> {
+        let range = self.span_trimmed_end(start, end)?;
+        let slice = text.get(range)?;
+        let mut chars = slice.chars();
+        for ch in &self.ch_vec[start..min(end, self.ch_vec.len())] {
+            match chars.next() {
+                Some(c) if c == *ch => (),
+                None if self.is_trimmed(*ch) => break,
+                _ => return None,
+            }
+        }
+        match chars.next() {
+            None => Some(slice),
+            Some(_) => None,
+        }
+    }
+
+    pub fn span_trimmed_end(&self, start: usize, end: usize) -> Option<Range<usize>> {
+        let end = min(end, self.ch_vec.len());
+        if start < end {
+            for last in (start..end).rev() {
+                let ch = self.ch_vec[last];
+                if !self.is_trimmed(ch) {
+                    return Some(self.pos_vec[start]..(self.pos_vec[last] + ch.len_utf8()));
+                }
+            }
+        }
+        None
+    }
 }
 
 #[cfg(test)]
@@ -101,25 +162,25 @@ mod test_of_char_buffer {
         assert_eq!(buf.len(), 0);
         assert_eq!(buf.full(), "");
 
-        assert_eq!(buf.add('1'), true);
+        buf.add('1', 0);
         assert_eq!(buf.is_empty(), false);
         assert_eq!(buf.len(), 1);
         assert_eq!(buf.full(), "1");
 
-        assert_eq!(buf.add('2'), true);
+        buf.add('2', 0);
         assert_eq!(buf.is_empty(), false);
         assert_eq!(buf.len(), 2);
         assert_eq!(buf.full(), "12");
 
-        assert_eq!(buf.add('3'), true);
+        buf.add('3', 0);
         assert_eq!(buf.is_empty(), false);
         assert_eq!(buf.len(), 3);
         assert_eq!(buf.full(), "123");
 
-        assert_eq!(buf.add('4'), false);
+        buf.add('4', 0);
         assert_eq!(buf.is_empty(), false);
-        assert_eq!(buf.len(), 3);
-        assert_eq!(buf.full(), "123");
+        assert_eq!(buf.len(), 4);
+        assert_eq!(buf.full(), "1234");
     }
 
     #[test]
@@ -129,11 +190,11 @@ mod test_of_char_buffer {
         assert_eq!(buf.is_empty(), true);
         assert_eq!(buf.len(), 0);
 
-        assert_eq!(buf.add('1'), true);
-        assert_eq!(buf.add('2'), true);
-        assert_eq!(buf.add('3'), true);
-        assert_eq!(buf.add('4'), true);
-        assert_eq!(buf.add('5'), true);
+        buf.add('1', 0);
+        buf.add('2', 0);
+        buf.add('3', 0);
+        buf.add('4', 0);
+        buf.add('5', 0);
         assert_eq!(buf.full(), "12345");
         assert_eq!(buf.is_empty(), false);
         assert_eq!(buf.len(), 5);
@@ -143,7 +204,7 @@ mod test_of_char_buffer {
         assert_eq!(buf.is_empty(), false);
         assert_eq!(buf.len(), 2);
 
-        assert_eq!(buf.add('6'), true);
+        buf.add('6', 0);
         assert_eq!(buf.full(), "456");
         assert_eq!(buf.is_empty(), false);
         assert_eq!(buf.len(), 3);
@@ -171,8 +232,8 @@ mod test_of_char_buffer {
         assert_eq!(buf.is_empty(), true);
         assert_eq!(buf.len(), 0);
 
-        buf.add('1');
-        buf.add('2');
+        buf.add('1', 0);
+        buf.add('2', 0);
         assert_eq!(buf.full(), "12");
         assert_eq!(buf.is_empty(), false);
         assert_eq!(buf.len(), 2);
@@ -192,35 +253,35 @@ mod test_of_char_buffer {
 
         assert_eq!(buf.to_string_trimmed_end(), "");
 
-        buf.add('1');
+        buf.add('1', 0);
         assert_eq!(buf.full(), "1");
         assert_eq!(buf.is_empty(), false);
         assert_eq!(buf.len(), 1);
 
         assert_eq!(buf.to_string_trimmed_end(), "1");
 
-        buf.add('2');
+        buf.add('2', 0);
         assert_eq!(buf.full(), "12");
         assert_eq!(buf.is_empty(), false);
         assert_eq!(buf.len(), 2);
 
         assert_eq!(buf.to_string_trimmed_end(), "12");
 
-        buf.add(' ');
+        buf.add(' ', 0);
         assert_eq!(buf.full(), "12 ");
         assert_eq!(buf.is_empty(), false);
         assert_eq!(buf.len(), 3);
 
         assert_eq!(buf.to_string_trimmed_end(), "12");
 
-        buf.add('4');
+        buf.add('4', 0);
         assert_eq!(buf.full(), "12 4");
         assert_eq!(buf.is_empty(), false);
         assert_eq!(buf.len(), 4);
 
         assert_eq!(buf.to_string_trimmed_end(), "12 4");
 
-        buf.add(' ');
+        buf.add(' ', 0);
         assert_eq!(buf.full(), "12 4 ");
         assert_eq!(buf.is_empty(), false);
         assert_eq!(buf.len(), 5);
@@ -237,11 +298,11 @@ mod test_of_char_buffer {
 
         assert_eq!(buf.substring_trimmed_end(0, 0), "");
 
-        buf.add('1');
-        buf.add('2');
-        buf.add(' ');
-        buf.add('4');
-        buf.add(' ');
+        buf.add('1', 0);
+        buf.add('2', 0);
+        buf.add(' ', 0);
+        buf.add('4', 0);
+        buf.add(' ', 0);
         assert_eq!(buf.full(), "12 4 ");
         assert_eq!(buf.is_empty(), false);
         assert_eq!(buf.len(), 5);
@@ -284,14 +345,77 @@ mod test_of_char_buffer {
         assert_eq!(buf.substring_trimmed_end(4, 0), "");
     }
 
+    #[test]
+    fn test_span_trimmed_end() {
+        let mut buf = CharBuffer::new(5);
+        assert_eq!(buf.span_trimmed_end(0, 5), None);
+
+        buf.add('a', 3);
+        buf.add('あ', 4);
+        buf.add(' ', 7);
+        buf.add('b', 8);
+        buf.add(' ', 9);
+        assert_eq!(buf.span_trimmed_end(0, 5), Some(3..9));
+        assert_eq!(buf.span_trimmed_end(0, 3), Some(3..7));
+        assert_eq!(buf.span_trimmed_end(1, 2), Some(4..7));
+        assert_eq!(buf.span_trimmed_end(2, 3), None);
+        assert_eq!(buf.span_trimmed_end(3, 2), None);
+
+        buf.cr(1);
+        assert_eq!(buf.span_trimmed_end(0, 5), Some(4..9));
+    }
+
+    #[test]
+    fn test_keeps_ideographic_space() {
+        let mut buf = CharBuffer::new(5);
+        buf.add('あ', 0);
+        buf.add('\u{3000}', 3);
+        buf.add(' ', 6);
+        assert_eq!(buf.span_trimmed_end(0, 3), Some(0..3));
+        assert_eq!(buf.to_string_trimmed_end(), "あ");
+
+        buf.set_keeps_ideographic_space(true);
+        assert_eq!(buf.span_trimmed_end(0, 3), Some(0..6));
+        assert_eq!(buf.to_string_trimmed_end(), "あ\u{3000}");
+        assert_eq!(buf.substring_trimmed_end(0, 3), "あ\u{3000}");
+        assert_eq!(
+            buf.slice_trimmed_end("あ\u{3000} ", 0, 3),
+            Some("あ\u{3000}")
+        );
+    }
+
+    #[test]
+    fn test_slice_trimmed_end() {
+        let text = "ab\tcd e ";
+        let mut buf = CharBuffer::new(8);
+        assert_eq!(buf.slice_trimmed_end(text, 0, 8), None);
+
+        buf.add('a', 0);
+        buf.add('b', 1);
+        buf.add(' ', 2);
+        buf.add('c', 3);
+        buf.add('d', 4);
+        buf.add(' ', 5);
+        buf.add('e', 6);
+        buf.add(' ', 7);
+        assert_eq!(buf.slice_trimmed_end(text, 0, 2), Some("ab"));
+        assert_eq!(buf.slice_trimmed_end(text, 3, 8), Some("cd e"));
+        assert_eq!(buf.slice_trimmed_end(text, 0, 8), None);
+
+        buf.cr(6);
+        buf.add('g', 9);
+        assert_eq!(buf.slice_trimmed_end(text, 0, 1), Some("e"));
+        assert_eq!(buf.slice_trimmed_end(text, 0, 3), None);
+    }
+
     #[test]
     fn test_get() {
         let mut buf = CharBuffer::new(5);
-        assert_eq!(buf.add('1'), true);
-        assert_eq!(buf.add('2'), true);
-        assert_eq!(buf.add('3'), true);
-        assert_eq!(buf.add('4'), true);
-        assert_eq!(buf.add('5'), true);
+        buf.add('1', 0);
+        buf.add('2', 0);
+        buf.add('3', 0);
+        buf.add('4', 0);
+        buf.add('5', 0);
         assert_eq!(buf.full(), "12345");
 
         assert_eq!(buf.get(0), Some('1'));
@@ -0,0 +1,268 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::borrow::Cow;
+use alloc::collections::VecDeque;
+use alloc::string::String;
+
+use crate::chunk::{ChunkLines, Chunker};
+use crate::options::{Options, OptionsError};
+
+/// `CharLineIter` is the struct that outputs the text read from an arbitrary
+/// source of characters line by line.
+///
+/// This struct is used to wrap decoded streams, rope data structures or
+/// generated texts without building a `&str` of the whole text first.
+/// The source is read by a source line, or by a paragraph which is separated
+/// by empty lines in the paragraph mode, and the output lines are the same as
+/// those of `LineIter`.
+///
+/// ```rust
+///     use linebreak::CharLineIter;
+///
+///     let source = "abc def ghi\njkl".chars();
+///     let mut iter = CharLineIter::new(source, 7);
+///     assert_eq!(iter.next().unwrap(), "abc def");
+///     assert_eq!(iter.next().unwrap(), "ghi");
+///     assert_eq!(iter.next().unwrap(), "jkl");
+///     assert!(iter.next().is_none());
+/// ```
+pub struct CharLineIter<I> {
+    source: I,
+    opts: Options,
+    chunker: Chunker,
+    state: ChunkLines,
+    lines: VecDeque<String>,
+}
+
+impl<I> CharLineIter<I>
+where
+    I: Iterator<Item = char>,
+{
+    /// Creates a `CharLineIter` instance which outputs the text read from the
+    /// given source line by line within the specified width.
+    pub fn new(source: I, line_width: usize) -> CharLineIter<I> {
+        let opts = Options::new(line_width);
+        CharLineIter {
+            source,
+            chunker: Chunker::new(&opts),
+            opts,
+            state: ChunkLines::new(),
+            lines: VecDeque::new(),
+        }
+    }
+
+    /// Creates a `CharLineIter` instance with the settings in the specified
+    /// `Options`.
+    /// If the settings are invalid, this function returns an `OptionsError`.
+    pub fn with_options(source: I, opts: Options) -> Result<CharLineIter<I>, OptionsError> {
+        opts.validate()?;
+        let mut iter = CharLineIter::new(source, opts.width);
+        iter.chunker = Chunker::new(&opts);
+        iter.opts = opts;
+        Ok(iter)
+    }
+
+    // Reads the source until a chunk is completed.
+    fn read_chunk(&mut self) -> (String, bool) {
+        loop {
+            if let Some(chunk) = self.chunker.pop() {
+                return chunk;
+            }
+            match self.source.next() {
+                Some(ch) => self.chunker.push(ch),
+                None => self.chunker.finish(),
+            }
+        }
+    }
+}
+
+impl<I> Iterator for CharLineIter<I>
+where
+    I: Iterator<Item = char>,
+{
+    type Item = String;
+
+    /// Returns an Option of a line string.
+    fn next(&mut self) -> Option<String> {
+        loop {
+            if let Some(line) = self.lines.pop_front() {
+                return Some(line);
+            }
+            if self.state.is_done() {
+                return None;
+            }
+            let (chunk, follows) = self.read_chunk();
+            let lines = self.state.wrap(&chunk, follows, &self.opts).ok()?;
+            self.lines.extend(lines.into_iter().map(Cow::into_owned));
+        }
+    }
+}
+
+/// `IoChars` is the struct which adapts a source of `io::Result<char>` to a
+/// source of `char` for `CharLineIter`.
+///
+/// The characters are read until an error occurs, and the error is kept so
+/// that it can be retrieved with `CharLineIter::io_error` after the iteration.
+///
+/// ```rust
+///     use std::io;
+///     use linebreak::CharLineIter;
+///
+///     let source = vec![Ok('a'), Ok(' '), Ok('b'), Err(io::Error::new(io::ErrorKind::Other, "broken"))];
+///     let mut iter = CharLineIter::from_io(source.into_iter(), 10);
+///     assert_eq!(iter.next().unwrap(), "a b");
+///     assert!(iter.next().is_none());
+///     assert_eq!(iter.io_error().unwrap().to_string(), "broken");
+/// ```
+#[cfg(feature = "std")]
+pub struct IoChars<I> {
+    inner: I,
+    error: Option<std::io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<I> Iterator for IoChars<I>
+where
+    I: Iterator<Item = std::io::Result<char>>,
+{
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        if self.error.is_some() {
+            return None;
+        }
+        match self.inner.next()? {
+            Ok(ch) => Some(ch),
+            Err(err) => {
+                self.error = Some(err);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<I> CharLineIter<IoChars<I>>
+where
+    I: Iterator<Item = std::io::Result<char>>,
+{
+    /// Creates a `CharLineIter` instance which outputs the text read from the
+    /// given source of `io::Result<char>` line by line within the specified
+    /// width.
+    /// When the source returns an error, the iteration stops after the lines
+    /// of the text read before it.
+    pub fn from_io(source: I, line_width: usize) -> CharLineIter<IoChars<I>> {
+        let chars = IoChars {
+            inner: source,
+            error: None,
+        };
+        CharLineIter::new(chars, line_width)
+    }
+
+    /// Returns the error which the source returned, if any.
+    pub fn io_error(&self) -> Option<&std::io::Error> {
+        self.source.error.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod test_of_char_line_iter {
+    use super::*;
+    use crate::ansi::AnsiPolicy;
+    use crate::line_iter::LineIter;
+
+    fn assert_same_as_line_iter(text: &str, opts: &Options) {
+        let expected: Vec<String> = LineIter::with_options(text, opts)
+            .unwrap()
+            .map(|s| s.into_owned())
+            .collect();
+        let iter = CharLineIter::with_options(text.chars(), opts.clone()).unwrap();
+        assert_eq!(iter.collect::<Vec<_>>(), expected, "text: {:?}", text);
+    }
+
+    #[test]
+    fn test_same_as_line_iter() {
+        let texts = [
+            "",
+            "abc",
+            "abc\n",
+            "abc def ghi jkl",
+            "abc\n\ndef ghi\r\njkl\n\n",
+            "\n\nabc",
+            "句読点は、行頭に置くことは禁止である。\n次の行",
+            "漢字、\n\nx",
+        ];
+        for text in texts {
+            assert_same_as_line_iter(text, &Options::new(4));
+            assert_same_as_line_iter(text, &Options::new(7));
+            assert_same_as_line_iter(text, &Options::new(9).first_indent("* ").indent("  "));
+            assert_same_as_line_iter(text, &Options::new(9).line_numbers(2));
+            assert_same_as_line_iter(text, &Options::new(9).max_lines(1));
+            assert_same_as_line_iter(text, &Options::new(9).strict_line_breaks(true));
+        }
+    }
+
+    #[test]
+    fn test_same_as_line_iter_with_line_breaks() {
+        let texts = ["a\r\nb", "a\r\rb", "a\rb", "abc\r\n", "\n"];
+        for text in texts {
+            assert_same_as_line_iter(text, &Options::new(7));
+            assert_same_as_line_iter(text, &Options::new(7).max_lines(1));
+            assert_same_as_line_iter(text, &Options::new(7).strict_line_breaks(true));
+        }
+    }
+
+    #[test]
+    fn test_same_as_line_iter_with_escape_sequences() {
+        let text = "\u{1b}[1mabc def\nghi\u{1b}[0m jkl";
+        assert_same_as_line_iter(text, &Options::new(5).ansi_policy(AnsiPolicy::Reapply));
+    }
+
+    #[test]
+    fn test_same_as_line_iter_in_paragraph_mode() {
+        let texts = [
+            "abc\ndef\nghi jkl",
+            "abc\ndef\n\nghi\r\njkl\n",
+            "abc\n\n\ndef",
+            "abc\n",
+        ];
+        for text in texts {
+            assert_same_as_line_iter(text, &Options::new(7).paragraph_mode(true));
+        }
+    }
+
+    #[test]
+    fn test_max_lines() {
+        let opts = Options::new(7).max_lines(3);
+        let iter = CharLineIter::with_options("abc\ndef ghi jkl\nmno".chars(), opts).unwrap();
+        assert_eq!(iter.collect::<Vec<_>>(), ["abc", "def ghi", "jkl…"]);
+
+        let opts = Options::new(10).max_lines(1);
+        let iter = CharLineIter::with_options("abc\ndef".chars(), opts).unwrap();
+        assert_eq!(iter.collect::<Vec<_>>(), ["abc…"]);
+
+        let opts = Options::new(7).max_lines(2);
+        let iter = CharLineIter::with_options("abc\ndef ghi jkl\nmno".chars(), opts).unwrap();
+        assert_eq!(iter.collect::<Vec<_>>(), ["abc", "def g…"]);
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        let opts = Options::new(2).prefix("> ");
+        assert!(CharLineIter::with_options("abc".chars(), opts).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_from_io() {
+        let source = "abc def\nghi".chars().map(Ok);
+        let mut iter = CharLineIter::from_io(source, 5);
+        assert_eq!(iter.next().unwrap(), "abc");
+        assert_eq!(iter.next().unwrap(), "def");
+        assert_eq!(iter.next().unwrap(), "ghi");
+        assert!(iter.next().is_none());
+        assert!(iter.io_error().is_none());
+    }
+}
@@ -0,0 +1,214 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::borrow::Cow;
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::ansi::AnsiState;
+use crate::line_iter::LineIter;
+use crate::options::{Options, OptionsError};
+
+// Divides the characters which are pushed one by one into chunks, which are
+// source lines, or paragraphs separated by empty lines in the paragraph mode,
+// without the last line breaks.
+// CRLF is converted to LF unless the line breaks are strict, in which case CR
+// is a line break by itself.
+// Each chunk is paired with whether a line break follows it.
+pub(crate) struct Chunker {
+    strict_line_breaks: bool,
+    paragraph_mode: bool,
+    chunk: String,
+    pending_cr: bool,
+    pending_end: Option<char>,
+    chunks: VecDeque<(String, bool)>,
+}
+
+impl Chunker {
+    pub fn new(opts: &Options) -> Chunker {
+        Chunker {
+            strict_line_breaks: opts.strict_line_breaks,
+            paragraph_mode: opts.paragraph_mode,
+            chunk: String::new(),
+            pending_cr: false,
+            pending_end: None,
+            chunks: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, ch: char) {
+        if self.pending_cr {
+            self.pending_cr = false;
+            if ch == '\n' {
+                self.push_folded(ch);
+                return;
+            }
+            self.push_folded('\r');
+        }
+        if ch == '\r' && !self.strict_line_breaks {
+            self.pending_cr = true;
+            return;
+        }
+        self.push_folded(ch);
+    }
+
+    // Ends the last chunk, which is not followed by a line break.
+    pub fn finish(&mut self) {
+        if self.pending_cr {
+            self.pending_cr = false;
+            self.push_folded('\r');
+        }
+        if self.pending_end.take().is_some() {
+            self.end_chunk(true);
+        }
+        self.end_chunk(false);
+    }
+
+    pub fn pop(&mut self) -> Option<(String, bool)> {
+        self.chunks.pop_front()
+    }
+
+    fn is_chunk_end(&self, ch: char) -> bool {
+        ch == '\n' || (ch == '\r' && self.strict_line_breaks)
+    }
+
+    fn push_folded(&mut self, ch: char) {
+        // in the paragraph mode, a line break ends a chunk only if another
+        // line break follows it.
+        if let Some(end) = self.pending_end.take() {
+            match self.is_chunk_end(ch) {
+                true => self.end_chunk(true),
+                false => self.chunk.push(end),
+            }
+        }
+        if !self.is_chunk_end(ch) {
+            self.chunk.push(ch);
+        } else if self.paragraph_mode && !self.chunk.is_empty() {
+            self.pending_end = Some(ch);
+        } else {
+            self.end_chunk(true);
+        }
+    }
+
+    fn end_chunk(&mut self, follows: bool) {
+        let chunk = core::mem::take(&mut self.chunk);
+        self.chunks.push_back((chunk, follows));
+    }
+}
+
+// Holds the state of the line iteration which continues over chunks: the
+// number of the output lines, the escape sequences active at the end of them,
+// and whether the iteration is over.
+pub(crate) struct ChunkLines {
+    line_no: usize,
+    ansi_state: AnsiState,
+    is_done: bool,
+}
+
+impl ChunkLines {
+    pub fn new() -> ChunkLines {
+        ChunkLines {
+            line_no: 0,
+            ansi_state: AnsiState::new(),
+            is_done: false,
+        }
+    }
+
+    // Returns true if no more line is output, because the last chunk is broken
+    // or the maximum number of lines is reached.
+    pub fn is_done(&self) -> bool {
+        self.is_done
+    }
+
+    // Breaks the specified chunk into lines as a part of the whole text, which
+    // `LineIter` would output for the text.
+    pub fn wrap<'a>(
+        &mut self,
+        chunk: &'a str,
+        follows: bool,
+        opts: &'a Options,
+    ) -> Result<Vec<Cow<'a, str>>, OptionsError> {
+        let mut iter = LineIter::with_options(chunk, opts)?;
+        iter.set_line_no(self.line_no);
+        iter.set_ansi_state(core::mem::replace(&mut self.ansi_state, AnsiState::new()));
+        iter.set_text_follows(follows);
+        let lines = iter.by_ref().collect();
+        self.line_no = iter.line_no();
+        self.ansi_state = iter.take_ansi_state();
+        // the iteration stops at an overflowing line as `LineIter` does.
+        self.is_done = !follows
+            || (opts.max_lines > 0 && self.line_no >= opts.max_lines)
+            || iter.overflow_error().is_some();
+        Ok(lines)
+    }
+}
+
+#[cfg(test)]
+mod test_of_chunk {
+    use super::*;
+
+    fn chunks(text: &str, opts: &Options) -> Vec<(String, bool)> {
+        let mut chunker = Chunker::new(opts);
+        text.chars().for_each(|ch| chunker.push(ch));
+        chunker.finish();
+        let mut chunks = Vec::new();
+        while let Some(chunk) = chunker.pop() {
+            chunks.push(chunk);
+        }
+        chunks
+    }
+
+    fn s(chunk: &str, follows: bool) -> (String, bool) {
+        (String::from(chunk), follows)
+    }
+
+    #[test]
+    fn test_chunks_of_lines() {
+        let opts = Options::new(10);
+        assert_eq!(chunks("", &opts), [s("", false)]);
+        assert_eq!(
+            chunks("abc\r\ndef\n", &opts),
+            [s("abc", true), s("def", true), s("", false)]
+        );
+        assert_eq!(chunks("a\rb\r", &opts), [s("a\rb\r", false)]);
+
+        let opts = Options::new(10).strict_line_breaks(true);
+        assert_eq!(
+            chunks("a\r\nb", &opts),
+            [s("a", true), s("", true), s("b", false)]
+        );
+    }
+
+    #[test]
+    fn test_chunks_of_paragraphs() {
+        let opts = Options::new(10).paragraph_mode(true);
+        assert_eq!(
+            chunks("abc\ndef\n\nghi\n", &opts),
+            [
+                s("abc\ndef", true),
+                s("", true),
+                s("ghi", true),
+                s("", false)
+            ]
+        );
+        assert_eq!(chunks("\nabc", &opts), [s("", true), s("abc", false)]);
+    }
+
+    #[test]
+    fn test_chunk_lines() {
+        let opts = Options::new(7).max_lines(2);
+        let mut lines = ChunkLines::new();
+        assert_eq!(lines.wrap("abc", true, &opts).unwrap(), ["abc"]);
+        assert!(!lines.is_done());
+        assert_eq!(lines.wrap("def ghi jkl", true, &opts).unwrap(), ["def g…"]);
+        assert!(lines.is_done());
+
+        let opts = Options::new(7).line_numbers(1);
+        let mut lines = ChunkLines::new();
+        assert_eq!(lines.wrap("abc", true, &opts).unwrap(), ["1 abc"]);
+        assert_eq!(lines.wrap("def", false, &opts).unwrap(), ["2 def"]);
+        assert!(lines.is_done());
+    }
+}
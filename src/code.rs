@@ -0,0 +1,162 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use crate::linebreak::{LboState, LboType};
+
+// Determines the line break opportunities of source code, which is broken
+// after commas, semicolons, opening brackets and operators, and at spaces,
+// but never inside of string literals.
+// The string literals are tracked with the quotation states of `LboState`.
+pub struct CodeState {
+    prev: Option<char>,
+    escaped: bool,
+}
+
+impl CodeState {
+    pub fn new() -> CodeState {
+        CodeState {
+            prev: None,
+            escaped: false,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.prev = None;
+        self.escaped = false;
+    }
+
+    pub fn line_break_opportunity(&mut self, ch: char, state: &mut LboState) {
+        state.lbo_prev = state.lbo_type;
+        state.lbo_type = self.classify(ch, state);
+
+        // a backslash escapes the next character, unless it is escaped.
+        self.escaped = ch == '\\' && !self.escaped;
+        self.prev = Some(ch);
+    }
+
+    fn classify(&self, ch: char, state: &mut LboState) -> LboType {
+        if matches!(ch, '\n' | '\r' | '\u{000B}' | '\u{000C}' | '\u{0085}')
+            || matches!(ch, '\u{2028}' | '\u{2029}')
+        {
+            state.open_quot = 0;
+            state.open_apos = 0;
+            return LboType::Break;
+        }
+        if state.open_quot > 0 || state.open_apos > 0 {
+            match ch {
+                '"' if state.open_quot > 0 && !self.escaped => state.open_quot = 0,
+                '\'' if state.open_apos > 0 && !self.escaped => state.open_apos = 0,
+                _ => (),
+            }
+            return LboType::Never;
+        }
+        match ch {
+            '"' => {
+                state.open_quot = 1;
+                LboType::Never
+            }
+            // an apostrophe after `&` or `<` starts a lifetime of Rust, such as
+            // `&'a str` and `<'a>`, rather than a character literal.
+            '\'' if !matches!(self.prev, Some('&' | '<')) => {
+                state.open_apos = 1;
+                LboType::Never
+            }
+            ',' | ';' | '(' | '[' | '{' => LboType::After,
+            _ if ch.is_whitespace() => LboType::Space,
+            // a run of operator characters, such as `==` and `->`, is not
+            // broken, and a line can be broken after it.
+            _ if is_operator(ch) => LboType::Never,
+            _ if self.prev.map_or(false, is_operator) => LboType::Before,
+            _ => LboType::Never,
+        }
+    }
+}
+
+fn is_operator(ch: char) -> bool {
+    matches!(
+        ch,
+        '+' | '-' | '*' | '/' | '%' | '=' | '<' | '>' | '&' | '|' | '^' | '!' | '~' | '?'
+    )
+}
+
+#[cfg(test)]
+mod test_of_code {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn classify_all(text: &str) -> Vec<LboType> {
+        let mut code = CodeState::new();
+        let mut state = LboState {
+            lbo_type: LboType::Never,
+            lbo_prev: LboType::Never,
+            open_apos: 0,
+            open_quot: 0,
+        };
+        text.chars()
+            .map(|ch| {
+                code.line_break_opportunity(ch, &mut state);
+                state.lbo_type
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_breaks_after_commas_and_opening_brackets() {
+        assert_eq!(
+            classify_all("f(a,b)"),
+            [
+                LboType::Never,
+                LboType::After,
+                LboType::Never,
+                LboType::After,
+                LboType::Never,
+                LboType::Never,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_breaks_after_operators() {
+        assert_eq!(
+            classify_all("a==b"),
+            [
+                LboType::Never,
+                LboType::Never,
+                LboType::Never,
+                LboType::Before,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_never_breaks_inside_strings() {
+        let types = classify_all(r#"("a, \"b\"",'c')"#);
+        assert_eq!(types[0], LboType::After);
+        assert!(types[1..11].iter().all(|t| *t == LboType::Never));
+        assert_eq!(types[11], LboType::After);
+        assert!(types[12..].iter().all(|t| *t == LboType::Never));
+    }
+
+    #[test]
+    fn test_lifetime_is_not_a_string() {
+        let types = classify_all("&'a str,x");
+        assert_eq!(types[3], LboType::Space);
+        assert_eq!(types[7], LboType::After);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut code = CodeState::new();
+        let mut state = LboState {
+            lbo_type: LboType::Never,
+            lbo_prev: LboType::Never,
+            open_apos: 0,
+            open_quot: 0,
+        };
+        code.line_break_opportunity('+', &mut state);
+        code.reset();
+        code.line_break_opportunity('a', &mut state);
+        assert_eq!(state.lbo_type, LboType::Never);
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::line_iter::LineIter;
+use crate::unicode::text_width;
+
+/// Breaks each of the specified texts into lines independently, and lays them
+/// out side by side in columns within the specified total width.
+///
+/// The total width except for the gaps between the columns is divided equally
+/// among the columns, and the remainder is given to the left columns.
+/// Each line is padded with spaces by its display width, so that the columns
+/// are aligned even if the texts contain wide characters.
+/// The rows are joined with `\n`, and the trailing spaces of each row are
+/// removed.
+///
+/// ```rust
+///     use linebreak::columns;
+///
+///     let texts = ["The Rust programming language", "日本語の文章も揃う"];
+///     assert_eq!(
+///         columns(&texts, 30, 2),
+///         "The Rust        日本語の文章も\n\
+///          programming     揃う\n\
+///          language"
+///     );
+/// ```
+pub fn columns(texts: &[&str], total_width: usize, gap: usize) -> String {
+    let n = texts.len();
+    if n == 0 {
+        return String::new();
+    }
+    let room = total_width.saturating_sub(gap.saturating_mul(n - 1));
+    let widths: Vec<usize> = (0..n)
+        .map(|i| (room / n + usize::from(i < room % n)).max(1))
+        .collect();
+    let cells: Vec<Vec<Cow<str>>> = texts
+        .iter()
+        .zip(&widths)
+        .map(|(text, width)| LineIter::new(text, *width).collect())
+        .collect();
+    let separator = " ".repeat(gap);
+
+    let mut laid_out = String::with_capacity(total_width * 4);
+    push_side_by_side(&mut laid_out, &cells, &widths, &separator);
+    laid_out
+}
+
+// Pushes the lines of the specified cells side by side, which are padded to
+// the widths of their columns and separated with the specified separator,
+// until the lines of all cells are pushed.
+// The rows are joined with `\n`, and the trailing spaces of each row are
+// removed.
+pub(crate) fn push_side_by_side(
+    laid_out: &mut String,
+    cells: &[Vec<Cow<str>>],
+    widths: &[usize],
+    separator: &str,
+) {
+    let rows = cells.iter().map(|lines| lines.len()).max().unwrap_or(0);
+    for row in 0..rows {
+        if row > 0 {
+            laid_out.push('\n');
+        }
+        let start = laid_out.len();
+        for (i, lines) in cells.iter().enumerate() {
+            let line = lines.get(row).map_or("", |line| line);
+            laid_out.push_str(line);
+            if i + 1 < cells.len() {
+                let pad = widths[i].saturating_sub(text_width(line));
+                laid_out.extend(core::iter::repeat(' ').take(pad));
+                laid_out.push_str(separator);
+            }
+        }
+        let end = start + laid_out[start..].trim_end_matches(' ').len();
+        laid_out.truncate(end);
+    }
+}
+
+#[cfg(test)]
+mod test_of_columns {
+    use super::*;
+
+    #[test]
+    fn test_no_texts() {
+        assert_eq!(columns(&[], 80, 2), "");
+        assert_eq!(columns(&["", ""], 80, 2), "");
+    }
+
+    #[test]
+    fn test_single_column() {
+        assert_eq!(columns(&["abc def ghi"], 7, 2), "abc def\nghi");
+    }
+
+    #[test]
+    fn test_divides_width_equally() {
+        let texts = ["aaa bbb", "ccc ddd", "eee fff"];
+        assert_eq!(columns(&texts, 24, 1), "aaa bbb  ccc ddd eee fff");
+        assert_eq!(
+            columns(&texts, 20, 1),
+            "aaa    ccc    eee\nbbb    ddd    fff"
+        );
+    }
+
+    #[test]
+    fn test_pads_shorter_columns() {
+        let texts = ["a", "b c d", "e f"];
+        assert_eq!(columns(&texts, 5, 1), "a b e\n  c f\n  d");
+    }
+
+    #[test]
+    fn test_aligns_wide_characters() {
+        let texts = ["あいう えお", "abc"];
+        assert_eq!(columns(&texts, 14, 2), "あいう  abc\nえお");
+    }
+
+    #[test]
+    fn test_too_narrow_width() {
+        assert_eq!(columns(&["ab", "cd"], 2, 2), "a  c\nb  d");
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+
+use crate::line_iter::LineIter;
+use crate::options::{Options, OptionsError};
+
+/// Formats the specified term and its definition in two columns, as the
+/// options of a command are listed in its help message.
+///
+/// The term is put in the left column, whose width is the width of the
+/// indentation of the specified `Options`, and the definition is broken into
+/// lines in the rest of the line width and indented with the indentation.
+/// If the term does not fit in the left column with at least one space after
+/// it, the term is put on its own line and the definition starts on the next
+/// line.
+/// The other settings of the `Options`, such as the prefix and the line
+/// ending, are applied as `LineIter::with_options` does, except for the first
+/// indentation which is replaced with the term.
+///
+/// If the settings are invalid, this function returns an `OptionsError`.
+///
+/// ```rust
+///     use linebreak::{format_definition, Options};
+///
+///     let opts = Options::new(40).indent(&" ".repeat(16));
+///     assert_eq!(
+///         format_definition("-v, --verbose", "Prints the details of the processing.", &opts)
+///             .unwrap(),
+///         "-v, --verbose   Prints the details of\n\
+///         \x20               the processing."
+///     );
+///     assert_eq!(
+///         format_definition("--config-file <FILE>", "Sets a config file.", &opts)
+///             .unwrap(),
+///         "--config-file <FILE>\n\
+///         \x20               Sets a config file."
+///     );
+/// ```
+pub fn format_definition(
+    term: &str,
+    definition: &str,
+    opts: &Options,
+) -> Result<String, OptionsError> {
+    let mut iter = LineIter::with_options(definition, opts)?;
+    let indent_width = iter.text_width(&opts.indent);
+    let term_width = iter.text_width(term);
+    let ending = opts.line_ending.as_str();
+
+    let mut formatted = String::with_capacity(term.len() + definition.len() * 2);
+    let fits = term_width < indent_width;
+    if fits {
+        let mut head = String::from(term);
+        head.extend(core::iter::repeat(' ').take(indent_width - term_width));
+        iter.set_first_indent(Cow::Owned(head));
+    } else {
+        formatted.push_str(&opts.prefix);
+        formatted.push_str(term);
+        iter.set_first_indent(opts.indent.as_str());
+    }
+
+    match iter.next() {
+        Some(line) if !line.is_empty() => {
+            if !fits {
+                formatted.push_str(ending);
+            }
+            formatted.push_str(&line);
+        }
+        // a definition which starts with an empty line leaves the term alone
+        // on the first line.
+        Some(_) | None if fits => {
+            formatted.push_str(&opts.prefix);
+            formatted.push_str(term);
+        }
+        _ => (),
+    }
+    // the first lines of the subsequent paragraphs are indented as the other
+    // lines.
+    iter.set_first_indent(opts.indent.as_str());
+    for line in iter {
+        formatted.push_str(ending);
+        formatted.push_str(&line);
+    }
+    Ok(formatted)
+}
+
+#[cfg(test)]
+mod test_of_definition {
+    use super::*;
+    use crate::options::LineEnding;
+
+    #[test]
+    fn test_term_in_left_column() {
+        let opts = Options::new(20).indent("      ");
+        assert_eq!(
+            format_definition("-a", "abc def ghi jkl mno", &opts).unwrap(),
+            "-a    abc def ghi\n      jkl mno"
+        );
+        assert_eq!(
+            format_definition("あい", "abc", &opts).unwrap(),
+            "あい  abc"
+        );
+    }
+
+    #[test]
+    fn test_term_on_its_own_line() {
+        let opts = Options::new(20).indent("      ");
+        assert_eq!(
+            format_definition("--all", "abc", &opts).unwrap(),
+            "--all abc"
+        );
+        assert_eq!(
+            format_definition("--long", "abc", &opts).unwrap(),
+            "--long\n      abc"
+        );
+        assert_eq!(format_definition("--long", "", &opts).unwrap(), "--long");
+    }
+
+    #[test]
+    fn test_paragraphs_of_definition() {
+        let opts = Options::new(20).indent("    ");
+        assert_eq!(
+            format_definition("-a", "abc\ndef", &opts).unwrap(),
+            "-a  abc\n    def"
+        );
+        assert_eq!(format_definition("-a", "", &opts).unwrap(), "-a");
+        assert_eq!(
+            format_definition("-a", "\nabc", &opts).unwrap(),
+            "-a\n    abc"
+        );
+    }
+
+    #[test]
+    fn test_with_prefix_and_line_ending() {
+        let opts = Options::new(12)
+            .indent("    ")
+            .prefix("# ")
+            .line_ending(LineEnding::CrLf);
+        assert_eq!(
+            format_definition("-a", "abc def", &opts).unwrap(),
+            "# -a  abc\r\n#     def"
+        );
+        assert_eq!(
+            format_definition("-abc", "def", &opts).unwrap(),
+            "# -abc\r\n#     def"
+        );
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        let opts = Options::new(4).indent("    ");
+        assert!(format_definition("-a", "abc", &opts).is_err());
+    }
+}
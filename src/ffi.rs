@@ -0,0 +1,139 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+//! The C interface of this crate, which is available with the `ffi` feature.
+//!
+//! The declarations for C are in `include/linebreak.h`, which is generated
+//! with `cbindgen --config cbindgen.toml --output include/linebreak.h`.
+//! The declaration of `linebreak_term_cols` is enabled by defining the macro
+//! `LINEBREAK_FEATURE_TERMINAL`, because it is available only with the
+//! `terminal` feature.
+//! A shared or static library is built with
+//! `cargo rustc --release --features ffi --crate-type cdylib` (or
+//! `staticlib`).
+
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+
+use crate::fill::fill;
+use crate::unicode::text_width;
+
+// Converts the specified C string to a string slice, or returns `None` if the
+// pointer is null or the string is not valid UTF-8.
+unsafe fn to_str<'a>(text: *const c_char) -> Option<&'a str> {
+    if text.is_null() {
+        return None;
+    }
+    CStr::from_ptr(text).to_str().ok()
+}
+
+/// Breaks the specified NUL-terminated UTF-8 text into lines within the
+/// specified width, and returns a newly allocated string which joins the lines
+/// with `\n`.
+///
+/// This function returns a null pointer if the text is null or not valid
+/// UTF-8, or if the width is zero.
+/// The returned string must be released with `linebreak_free_string`.
+///
+/// # Safety
+///
+/// `text` must be null or a pointer to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn linebreak_wrap(text: *const c_char, width: usize) -> *mut c_char {
+    if width == 0 {
+        return ptr::null_mut();
+    }
+    let text = match to_str(text) {
+        Some(text) => text,
+        None => return ptr::null_mut(),
+    };
+    match CString::new(fill(text, width)) {
+        Ok(wrapped) => wrapped.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Releases a string returned by `linebreak_wrap`.
+/// This function does nothing if the pointer is null.
+///
+/// # Safety
+///
+/// `text` must be null or a pointer returned by `linebreak_wrap` which is not
+/// released yet.
+#[no_mangle]
+pub unsafe extern "C" fn linebreak_free_string(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
+
+/// Returns the display width of the specified NUL-terminated UTF-8 text.
+///
+/// This function returns 0 if the text is null or not valid UTF-8.
+///
+/// # Safety
+///
+/// `text` must be null or a pointer to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn linebreak_text_width(text: *const c_char) -> usize {
+    match to_str(text) {
+        Some(text) => text_width(text),
+        None => 0,
+    }
+}
+
+/// Returns the column number of the current terminal, or 80 if failing to
+/// retrieve it.
+/// This function is available with the `terminal` feature.
+#[cfg(feature = "terminal")]
+#[no_mangle]
+pub extern "C" fn linebreak_term_cols() -> usize {
+    crate::term_cols()
+}
+
+#[cfg(test)]
+mod test_of_ffi {
+    use super::*;
+
+    #[test]
+    fn test_wrap() {
+        let text = CString::new("abc def ghi").unwrap();
+        unsafe {
+            let wrapped = linebreak_wrap(text.as_ptr(), 7);
+            assert!(!wrapped.is_null());
+            assert_eq!(CStr::from_ptr(wrapped).to_str().unwrap(), "abc def\nghi");
+            linebreak_free_string(wrapped);
+        }
+    }
+
+    #[test]
+    fn test_wrap_invalid_arguments() {
+        let text = CString::new("abc").unwrap();
+        let invalid = CString::new(vec![0xffu8, 0xfe]).unwrap();
+        unsafe {
+            assert!(linebreak_wrap(ptr::null(), 7).is_null());
+            assert!(linebreak_wrap(text.as_ptr(), 0).is_null());
+            assert!(linebreak_wrap(invalid.as_ptr(), 7).is_null());
+            linebreak_free_string(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_text_width() {
+        let text = CString::new("あいう abc").unwrap();
+        let invalid = CString::new(vec![0xffu8]).unwrap();
+        unsafe {
+            assert_eq!(linebreak_text_width(text.as_ptr()), 10);
+            assert_eq!(linebreak_text_width(ptr::null()), 0);
+            assert_eq!(linebreak_text_width(invalid.as_ptr()), 0);
+        }
+    }
+
+    #[cfg(feature = "terminal")]
+    #[test]
+    fn test_term_cols() {
+        assert!(linebreak_term_cols() > 0);
+    }
+}
@@ -0,0 +1,438 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::line::LineMeta;
+use crate::line_iter::LineIter;
+use crate::options::LineEnding;
+
+/// Breaks the specified text into lines within the specified width, and
+/// returns a string which joins the lines with `\n`.
+///
+/// ```rust
+///     use linebreak::fill;
+///
+///     let text = "The Rust programming language helps you write faster, \
+///         more reliable software.";
+///     assert_eq!(
+///         fill(text, 30),
+///         "The Rust programming language\n\
+///          helps you write faster, more\n\
+///          reliable software."
+///     );
+/// ```
+pub fn fill(text: &str, width: usize) -> String {
+    let mut filled = String::with_capacity(text.len());
+    fill_into(text, width, &mut filled);
+    filled
+}
+
+/// Breaks the specified text into lines within the specified width as `fill`
+/// does, and appends them joined with `\n` to the specified string.
+///
+/// This function does not allocate a new string, so the caller can reuse its
+/// buffer, for example, to re-wrap a text on every frame.
+///
+/// ```rust
+///     use linebreak::fill_into;
+///
+///     let mut buf = String::from("> ");
+///     fill_into("abc def ghi", 7, &mut buf);
+///     assert_eq!(buf, "> abc def\nghi");
+///
+///     buf.clear();
+///     fill_into("jkl mno", 3, &mut buf);
+///     assert_eq!(buf, "jkl\nmno");
+/// ```
+pub fn fill_into(text: &str, width: usize, buf: &mut String) {
+    join_lines_into(LineIter::new(text, width), LineEnding::Lf, buf);
+}
+
+/// Breaks the specified text into lines within the specified width, and stores
+/// them into the specified vector.
+///
+/// The vector is overwritten with the lines, and the strings already in it
+/// are reused to store the lines, so that re-wrapping a text repeatedly does
+/// not allocate new strings once the vector has enough of them.
+///
+/// ```rust
+///     use linebreak::wrap_into;
+///
+///     let mut lines = Vec::new();
+///     wrap_into("abc def ghi", 7, &mut lines);
+///     assert_eq!(lines, ["abc def", "ghi"]);
+///
+///     wrap_into("jkl", 7, &mut lines);
+///     assert_eq!(lines, ["jkl"]);
+/// ```
+pub fn wrap_into(text: &str, width: usize, lines: &mut Vec<String>) {
+    let mut n = 0;
+    for line in LineIter::new(text, width) {
+        match lines.get_mut(n) {
+            Some(s) => {
+                s.clear();
+                s.push_str(&line);
+            }
+            None => lines.push(line.into_owned()),
+        }
+        n += 1;
+    }
+    lines.truncate(n);
+}
+
+/// Re-flows the specified text which was already broken into lines within the
+/// specified new width, and returns a string which joins the lines with `\n`.
+///
+/// The lines in each paragraph, which is separated by blank lines, are joined
+/// into one line with their indentations collapsed, and then the joined line
+/// is broken into lines again.
+///
+/// ```rust
+///     use linebreak::refill;
+///
+///     let text = "The Rust programming\n  language helps you\n  write faster.\n\n\
+///                 More reliable\nsoftware.";
+///     assert_eq!(
+///         refill(text, 30),
+///         "The Rust programming language\n\
+///          helps you write faster.\n\
+///          \n\
+///          More reliable software."
+///     );
+/// ```
+pub fn refill(text: &str, width: usize) -> String {
+    let mut iter = LineIter::new(text, width);
+    iter.set_paragraph_mode(true);
+    let mut filled = String::with_capacity(text.len());
+    join_lines_into(iter, LineEnding::Lf, &mut filled);
+    filled
+}
+
+/// Breaks the specified text into lines within the specified width, and
+/// returns the byte offsets in the text at which the lines end.
+///
+/// This function computes the positions in a single pass without building the
+/// line strings, for the callers which need only the positions, such as pagers.
+/// Each offset is the end of the range which a line covers, which does not
+/// include the trailing whitespace, and the offset of an empty line is at its
+/// line break.
+///
+/// ```rust
+///     use linebreak::break_points;
+///
+///     let text = "abc def ghi\n\njkl";
+///     assert_eq!(break_points(text, 7), [7, 11, 12, 16]);
+///     assert_eq!(&text[..7], "abc def");
+///     assert_eq!(&text[8..11], "ghi");
+/// ```
+pub fn break_points(text: &str, width: usize) -> Vec<usize> {
+    let mut iter = LineIter::new(text, width);
+    let mut points = Vec::new();
+    while let Some((_, range)) = iter.next_with_range() {
+        points.push(range.end);
+    }
+    points
+}
+
+/// Returns the number of the lines which the specified text occupies when it
+/// is broken into lines within the specified width with the specified
+/// indentation.
+///
+/// This function does not build the line strings, so it is useful to know the
+/// height of a text before rendering it, or for pagination.
+///
+/// ```rust
+///     use linebreak::count_lines;
+///
+///     let text = "The Rust programming language helps you write faster, \
+///         more reliable software.";
+///     assert_eq!(count_lines(text, 30, ""), 3);
+///     assert_eq!(count_lines(text, 30, "        "), 4);
+/// ```
+pub fn count_lines(text: &str, width: usize, indent: &str) -> usize {
+    let mut iter = LineIter::new(text, width);
+    iter.set_indent(indent);
+    let mut count = 0;
+    while iter.skip_line() {
+        count += 1;
+    }
+    count
+}
+
+/// Breaks the specified text into lines within the specified width, and calls
+/// the specified closure with each line and its metadata.
+///
+/// This function does not collect the lines, so it is useful to stream the
+/// wrapped text into an arbitrary sink.
+///
+/// ```rust
+///     use linebreak::for_each_line;
+///
+///     let mut out = String::new();
+///     for_each_line("abc def ghi", 7, |line, meta| {
+///         out.push_str(&format!("{}: {} ({})\n", meta.index, line, meta.width));
+///     });
+///     assert_eq!(out, "0: abc def (7)\n1: ghi (3)\n");
+/// ```
+pub fn for_each_line<F>(text: &str, width: usize, mut f: F)
+where
+    F: FnMut(&str, LineMeta),
+{
+    let mut iter = LineIter::new(text, width);
+    let mut index = 0;
+    while let Some(line) = iter.next_line() {
+        let meta = LineMeta {
+            index,
+            width: line.width,
+            range: line.range,
+            is_forced: line.is_forced,
+            is_hard_break: line.is_hard_break,
+        };
+        f(&line.text, meta);
+        index += 1;
+    }
+}
+
+/// Breaks the specified text into lines within the specified width so that the
+/// lines have approximately equal widths, and returns the lines.
+///
+/// This function breaks the text into the same number of lines as a greedy
+/// breaking within the specified width does, but with the narrowest width
+/// which keeps that number.
+/// This is suitable for short texts such as labels, tooltips and captions,
+/// like `text-wrap: balance` of CSS.
+///
+/// ```rust
+///     use linebreak::wrap_balanced;
+///
+///     let text = "Save all the changes and close the window";
+///     assert_eq!(
+///         wrap_balanced(text, 36),
+///         ["Save all the changes", "and close the window"]
+///     );
+/// ```
+pub fn wrap_balanced(text: &str, width: usize) -> Vec<String> {
+    let n = count_lines(text, width, "");
+    let mut lo = 1;
+    let mut hi = width;
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+        if count_lines(text, mid, "") <= n {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    LineIter::new(text, hi)
+        .map(|line| line.into_owned())
+        .collect()
+}
+
+/// Joins the specified lines, such as the lines of a `LineIter`, into a string
+/// with the specified line ending.
+///
+/// ```rust
+///     use linebreak::{join_lines, LineEnding, LineIter};
+///
+///     let iter = LineIter::new("abc def ghi", 7);
+///     assert_eq!(join_lines(iter, LineEnding::CrLf), "abc def\r\nghi");
+///     assert_eq!(join_lines(["abc", "def"], LineEnding::None), "abcdef");
+/// ```
+pub fn join_lines<I>(lines: I, ending: LineEnding) -> String
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut joined = String::new();
+    join_lines_into(lines, ending, &mut joined);
+    joined
+}
+
+fn join_lines_into<I>(lines: I, ending: LineEnding, buf: &mut String)
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    for (i, line) in lines.into_iter().enumerate() {
+        if i > 0 {
+            buf.push_str(ending.as_str());
+        }
+        buf.push_str(line.as_ref());
+    }
+}
+
+#[cfg(test)]
+mod test_of_fill {
+    use super::*;
+
+    #[test]
+    fn test_fill_empty() {
+        assert_eq!(fill("", 10), "");
+    }
+
+    #[test]
+    fn test_fill_short_text() {
+        assert_eq!(fill("abc", 10), "abc");
+    }
+
+    #[test]
+    fn test_fill_long_text() {
+        assert_eq!(fill("abc def ghi jkl", 7), "abc def\nghi jkl");
+    }
+
+    #[test]
+    fn test_fill_text_containing_line_breaks() {
+        assert_eq!(fill("abc\n\ndef ghi", 5), "abc\n\ndef\nghi");
+        assert_eq!(fill("abc\n", 5), "abc\n");
+    }
+
+    #[test]
+    fn test_fill_japanese() {
+        assert_eq!(
+            fill("句読点は、行頭に置くことは禁止である。", 8),
+            "句読点\nは、行頭\nに置くこ\nとは禁止\nである。"
+        );
+    }
+
+    #[test]
+    fn test_break_points() {
+        assert_eq!(break_points("", 10), [0]);
+        assert_eq!(break_points("abc", 10), [3]);
+        assert_eq!(break_points("abc def ghi jkl", 7), [7, 15]);
+        assert_eq!(break_points("abc\n", 5), [3, 4]);
+    }
+
+    #[test]
+    fn test_break_points_of_japanese() {
+        let text = "句読点は、行頭に置く";
+        let points = break_points(text, 8);
+        assert_eq!(points, [9, 21, 30]);
+        assert_eq!(&text[..9], "句読点");
+        assert_eq!(&text[9..21], "は、行頭");
+    }
+
+    #[test]
+    fn test_count_lines() {
+        assert_eq!(count_lines("", 10, ""), 1);
+        assert_eq!(count_lines("abc def ghi jkl", 7, ""), 2);
+        assert_eq!(count_lines("abc def ghi jkl", 7, "  "), 4);
+        assert_eq!(count_lines("abc\n\ndef\n", 7, ""), 4);
+        assert_eq!(
+            count_lines("句読点は、行頭に置くことは禁止である。", 8, ""),
+            5
+        );
+        assert_eq!(count_lines("漢字、\n\nx", 4, ""), 4);
+        assert_eq!(count_lines("aあ\nb", 2, ""), 3);
+    }
+
+    #[test]
+    fn test_count_lines_is_same_as_number_of_lines() {
+        let text = "The Rust programming language helps you write faster, more \
+            reliable software.\nHigh-level ergonomics and low-level control are \
+            often at odds in programming language design;\n\nRust challenges \
+            that conflict.";
+        for width in 3..40 {
+            for indent in ["", "  ", "> "] {
+                let mut iter = LineIter::new(text, width);
+                iter.set_indent(indent);
+                assert_eq!(count_lines(text, width, indent), iter.count());
+            }
+        }
+    }
+
+    #[test]
+    fn test_for_each_line() {
+        let mut lines = Vec::new();
+        for_each_line("abc defghijkl\nmn", 6, |line, meta| {
+            lines.push((String::from(line), meta));
+        });
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0].0, "abc");
+        assert_eq!(lines[0].1.range, 0..3);
+        assert!(!lines[0].1.is_forced);
+        assert_eq!(lines[1].0, "defghi");
+        assert_eq!(lines[1].1.index, 1);
+        assert!(lines[1].1.is_forced);
+        assert_eq!(lines[2].0, "jkl");
+        assert!(lines[2].1.is_hard_break);
+        assert_eq!(lines[3].0, "mn");
+        assert_eq!(lines[3].1.width, 2);
+    }
+
+    #[test]
+    fn test_wrap_balanced() {
+        assert_eq!(wrap_balanced("", 10), [""]);
+        assert_eq!(wrap_balanced("abc def", 10), ["abc def"]);
+        assert_eq!(
+            wrap_balanced("aaa bbb ccc ddd eee", 16),
+            ["aaa bbb ccc", "ddd eee"]
+        );
+        assert_eq!(
+            wrap_balanced("句読点は、行頭に置くことは禁止である。", 24),
+            ["句読点は、行頭に置く", "ことは禁止である。"]
+        );
+    }
+
+    #[test]
+    fn test_join_lines() {
+        let lines: [&str; 0] = [];
+        assert_eq!(join_lines(lines, LineEnding::Lf), "");
+        assert_eq!(join_lines(["abc"], LineEnding::CrLf), "abc");
+        assert_eq!(join_lines(["abc", "", "d"], LineEnding::Lf), "abc\n\nd");
+        assert_eq!(
+            join_lines(["abc", "", "d"], LineEnding::CrLf),
+            "abc\r\n\r\nd"
+        );
+        assert_eq!(join_lines(["abc", "", "d"], LineEnding::None), "abcd");
+    }
+
+    #[test]
+    fn test_fill_into() {
+        let mut buf = String::new();
+        fill_into("abc def ghi jkl", 7, &mut buf);
+        assert_eq!(buf, "abc def\nghi jkl");
+        fill_into("", 7, &mut buf);
+        assert_eq!(buf, "abc def\nghi jkl");
+        buf.push('\n');
+        fill_into("mno pqr", 3, &mut buf);
+        assert_eq!(buf, "abc def\nghi jkl\nmno\npqr");
+    }
+
+    #[test]
+    fn test_wrap_into_reuses_strings() {
+        let mut lines = Vec::new();
+        wrap_into("abc def ghi jkl mno", 7, &mut lines);
+        assert_eq!(lines, ["abc def", "ghi jkl", "mno"]);
+
+        let ptr = lines[0].as_ptr();
+        wrap_into("pqr stu", 7, &mut lines);
+        assert_eq!(lines, ["pqr stu"]);
+        assert_eq!(lines[0].as_ptr(), ptr);
+
+        wrap_into("", 7, &mut lines);
+        assert_eq!(lines, [""]);
+    }
+
+    #[test]
+    fn test_refill_empty() {
+        assert_eq!(refill("", 10), "");
+    }
+
+    #[test]
+    fn test_refill_joins_lines_in_paragraph() {
+        assert_eq!(refill("abc\ndef\nghi jkl", 20), "abc def ghi jkl");
+        assert_eq!(refill("abc def ghi jkl", 7), "abc def\nghi jkl");
+        assert_eq!(refill("abc\n    def\n\tghi", 7), "abc def\nghi");
+    }
+
+    #[test]
+    fn test_refill_keeps_paragraphs() {
+        assert_eq!(
+            refill("abc\ndef\n\nghi\r\njkl\n", 20),
+            "abc def\n\nghi jkl\n"
+        );
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::string::String;
+
+/// `Gutter` is the trait for generating a string which is put at the beginning
+/// of each output line of `LineIter`, such as a line number or a change mark.
+///
+/// The gutter is put before the prefix, and its width is given to
+/// `LineIter::set_gutter` and subtracted from the line width.
+///
+/// A closure which takes a line number and returns a string implements this
+/// trait.
+///
+/// ```rust
+///     use linebreak::{Gutter, LineIter};
+///
+///     struct Marker;
+///
+///     impl Gutter for Marker {
+///         fn gutter(&self, line_no: usize) -> String {
+///             if line_no == 1 { "+ ".to_string() } else { "  ".to_string() }
+///         }
+///     }
+///
+///     let marker = Marker;
+///     let mut iter = LineIter::new("abc def ghi", 9);
+///     iter.set_gutter(2, &marker);
+///     assert_eq!(iter.next().unwrap(), "+ abc def");
+///     assert_eq!(iter.next().unwrap(), "  ghi");
+///     assert!(iter.next().is_none());
+/// ```
+pub trait Gutter {
+    /// Returns the gutter string of the output line with the specified line
+    /// number, which starts at 1.
+    fn gutter(&self, line_no: usize) -> String;
+}
+
+impl<F> Gutter for F
+where
+    F: Fn(usize) -> String,
+{
+    fn gutter(&self, line_no: usize) -> String {
+        self(line_no)
+    }
+}
+
+#[cfg(test)]
+mod test_of_gutter {
+    use super::*;
+
+    #[test]
+    fn test_closure_as_gutter() {
+        let f = |n: usize| format!("{}:", n);
+        assert_eq!(f.gutter(1), "1:");
+        assert_eq!(f.gutter(12), "12:");
+    }
+}
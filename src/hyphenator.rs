@@ -0,0 +1,78 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::vec::Vec;
+
+/// `Hyphenator` is the trait for splitting a word which does not fit in the
+/// rest of a line at syllable boundaries.
+///
+/// `LineIter` consults this trait when a word runs over the end of a line.
+/// The word is split at the largest position which fits in the line, and a
+/// hyphen is appended to the first part.
+///
+/// A word which is wider than a line is divided with `split` instead, if the
+/// long word policy is `LongWordPolicy::Split`.
+///
+/// A closure which takes a word and returns the split positions implements
+/// this trait.
+///
+/// ```rust
+///     use linebreak::{Hyphenator, LineIter};
+///
+///     struct Dictionary;
+///
+///     impl Hyphenator for Dictionary {
+///         fn hyphenate(&self, word: &str) -> Vec<usize> {
+///             match word {
+///                 "hyphenation" => vec![2, 6], // hy-phen-ation
+///                 _ => Vec::new(),
+///             }
+///         }
+///     }
+///
+///     let dict = Dictionary;
+///     let mut iter = LineIter::new("the hyphenation hook", 12);
+///     iter.set_hyphenator(&dict);
+///     assert_eq!(iter.next().unwrap(), "the hyphen-");
+///     assert_eq!(iter.next().unwrap(), "ation hook");
+///     assert!(iter.next().is_none());
+/// ```
+pub trait Hyphenator {
+    /// Returns the byte positions in the specified word at which the word can
+    /// be split with a hyphen.
+    fn hyphenate(&self, word: &str) -> Vec<usize>;
+
+    /// Returns the byte position in the specified word which is wider than a
+    /// line, at which the word is divided without a hyphen so that the first
+    /// part fits within the specified width.
+    /// If this method returns `None` or an invalid position, the word is
+    /// broken at the end of the line, which is what the default
+    /// implementation does.
+    fn split(&self, word: &str, width: usize) -> Option<usize> {
+        let _ = (word, width);
+        None
+    }
+}
+
+impl<F> Hyphenator for F
+where
+    F: Fn(&str) -> Vec<usize>,
+{
+    fn hyphenate(&self, word: &str) -> Vec<usize> {
+        self(word)
+    }
+}
+
+#[cfg(test)]
+mod test_of_hyphenator {
+    use super::*;
+
+    #[test]
+    fn test_closure_as_hyphenator() {
+        let f = |word: &str| (1..word.len()).collect::<Vec<usize>>();
+        assert_eq!(f.hyphenate("abc"), vec![1, 2]);
+        assert_eq!(f.hyphenate(""), Vec::<usize>::new());
+        assert_eq!(f.split("abcdef", 4), None);
+    }
+}
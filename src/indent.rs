@@ -0,0 +1,217 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::string::String;
+
+use crate::unicode::{text_width, EAST_ASIAN_WIDTH};
+use icu::properties::EastAsianWidth;
+
+const TAB_WIDTH: usize = 8;
+
+/// Removes the longest common leading whitespace from all lines of the
+/// specified text.
+///
+/// The leading whitespace is measured in display width: a tab advances to
+/// the next multiple of 8 columns and a fullwidth space (U+3000) is 2 columns
+/// wide.
+/// If a tab or a fullwidth space straddles the common width, the rest of it is
+/// replaced with spaces.
+/// Lines which consist only of whitespace are ignored for computing the common
+/// width, and are output as empty lines.
+///
+/// ```rust
+///     use linebreak::dedent;
+///
+///     let text = "    fn main() {\n        println!();\n    }\n";
+///     assert_eq!(dedent(text), "fn main() {\n    println!();\n}\n");
+///
+///     assert_eq!(dedent("\tabc\n        def"), "abc\ndef");
+///     assert_eq!(dedent("\u{3000}abc\n  def"), "abc\ndef");
+/// ```
+pub fn dedent(text: &str) -> String {
+    let common = text
+        .split('\n')
+        .filter_map(|line| {
+            let (col, rest) = leading_width(line);
+            match rest.trim().is_empty() {
+                true => None,
+                false => Some(col),
+            }
+        })
+        .min()
+        .unwrap_or(0);
+
+    let mut dedented = String::with_capacity(text.len());
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            dedented.push('\n');
+        }
+        let (body, cr) = match line.strip_suffix('\r') {
+            Some(body) => (body, "\r"),
+            None => (line, ""),
+        };
+        if body.trim().is_empty() {
+            dedented.push_str(cr);
+            continue;
+        }
+        let mut col = 0;
+        let mut rest = body;
+        while col < common {
+            let mut chars = rest.chars();
+            let ch = match chars.next() {
+                Some(ch) => ch,
+                None => break,
+            };
+            col = advance(col, ch);
+            rest = chars.as_str();
+        }
+        for _ in common..col {
+            dedented.push(' ');
+        }
+        dedented.push_str(rest);
+        dedented.push_str(cr);
+    }
+    dedented
+}
+
+/// Puts the specified prefix at the beginning of every line of the specified
+/// text, and returns the indented text and the largest display width of its
+/// lines.
+///
+/// A line break at the end of the text does not start a new line, so the
+/// prefix is not put after it.
+///
+/// ```rust
+///     use linebreak::indent;
+///
+///     assert_eq!(indent("abc\n\ndefg\n", "> "), ("> abc\n> \n> defg\n".to_string(), 6));
+/// ```
+pub fn indent(text: &str, prefix: &str) -> (String, usize) {
+    indent_lines(text, prefix, false)
+}
+
+/// Puts the specified prefix at the beginning of every line of the specified
+/// text except for blank lines, and returns the indented text and the largest
+/// display width of its lines.
+///
+/// ```rust
+///     use linebreak::indent_non_blank;
+///
+///     assert_eq!(
+///         indent_non_blank("abc\n\ndefg\n", "* "),
+///         ("* abc\n\n* defg\n".to_string(), 6)
+///     );
+/// ```
+pub fn indent_non_blank(text: &str, prefix: &str) -> (String, usize) {
+    indent_lines(text, prefix, true)
+}
+
+fn indent_lines(text: &str, prefix: &str, skips_blank: bool) -> (String, usize) {
+    let mut indented = String::with_capacity(text.len() + prefix.len() * 8);
+    if text.is_empty() {
+        return (indented, 0);
+    }
+    let prefix_width = text_width(prefix);
+    let body = text.strip_suffix('\n').unwrap_or(text);
+    let mut max_width = 0;
+    for (i, line) in body.split('\n').enumerate() {
+        if i > 0 {
+            indented.push('\n');
+        }
+        if skips_blank && line.trim().is_empty() {
+            indented.push_str(line);
+            max_width = max_width.max(text_width(line));
+            continue;
+        }
+        indented.push_str(prefix);
+        indented.push_str(line);
+        max_width = max_width.max(prefix_width + text_width(line));
+    }
+    if body.len() < text.len() {
+        indented.push('\n');
+    }
+    (indented, max_width)
+}
+
+// Returns the display width of the leading whitespace of the specified line
+// and the rest of the line.
+fn leading_width(line: &str) -> (usize, &str) {
+    let mut col = 0;
+    for (i, ch) in line.char_indices() {
+        if !ch.is_whitespace() {
+            return (col, &line[i..]);
+        }
+        col = advance(col, ch);
+    }
+    (col, "")
+}
+
+// Returns the column after the specified whitespace character.
+fn advance(col: usize, ch: char) -> usize {
+    if ch == '\t' {
+        return (col / TAB_WIDTH + 1) * TAB_WIDTH;
+    }
+    match EAST_ASIAN_WIDTH.get(ch) {
+        EastAsianWidth::Wide | EastAsianWidth::Fullwidth => col + 2,
+        _ => col + 1,
+    }
+}
+
+#[cfg(test)]
+mod test_of_indent {
+    use super::*;
+
+    #[test]
+    fn test_dedent_empty() {
+        assert_eq!(dedent(""), "");
+        assert_eq!(dedent("\n"), "\n");
+    }
+
+    #[test]
+    fn test_dedent_without_common_indent() {
+        assert_eq!(dedent("abc\n  def"), "abc\n  def");
+    }
+
+    #[test]
+    fn test_dedent_ignores_blank_lines() {
+        assert_eq!(dedent("  abc\n \n\n    def\n"), "abc\n\n\n  def\n");
+        assert_eq!(dedent("  abc\r\n   \r\n  def\r\n"), "abc\r\n\r\ndef\r\n");
+    }
+
+    #[test]
+    fn test_dedent_with_tabs_and_fullwidth_spaces() {
+        assert_eq!(dedent("\tabc\n    def"), "    abc\ndef");
+        assert_eq!(dedent("  \tabc\n\t def"), "abc\n def");
+        assert_eq!(dedent("\u{3000}abc\n def"), " abc\ndef");
+        assert_eq!(
+            dedent("\u{3000}\u{3000}abc\n\u{3000}def"),
+            "\u{3000}abc\ndef"
+        );
+    }
+
+    #[test]
+    fn test_indent() {
+        assert_eq!(indent("", "> "), ("".to_string(), 0));
+        assert_eq!(indent("\n", "> "), ("> \n".to_string(), 2));
+        assert_eq!(indent("abc", "> "), ("> abc".to_string(), 5));
+        assert_eq!(
+            indent("abc\n  \nあい", "> "),
+            ("> abc\n>   \n> あい".to_string(), 6)
+        );
+        assert_eq!(
+            indent("abc\r\ndef\r\n", "- "),
+            ("- abc\r\n- def\r\n".to_string(), 5)
+        );
+    }
+
+    #[test]
+    fn test_indent_non_blank() {
+        assert_eq!(indent_non_blank("", "> "), ("".to_string(), 0));
+        assert_eq!(indent_non_blank("\n", "> "), ("\n".to_string(), 0));
+        assert_eq!(
+            indent_non_blank("abc\n   \n\ndef\n", "> "),
+            ("> abc\n   \n\n> def\n".to_string(), 5)
+        );
+    }
+}
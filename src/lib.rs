@@ -55,19 +55,194 @@
 //! _______Rust gives you the option to control low-level details (such as memory
 //! _______usage) without all the hassle traditionally associated with such control.
 //! ```
+//!
+//! ## `no_std` support
+//!
+//! The `std` feature is enabled by default.
+//! When disabling it, this crate is built for `no_std` environments which
+//! provide `alloc`, and the terminal functions and `WrapWriter` are not
+//! available.
+//!
+//! ```toml
+//! [dependencies]
+//! linebreak = { version = "0.3.1", default-features = false }
+//! ```
+//!
+//! ## `terminal` feature
+//!
+//! The `terminal` feature is enabled by default, and provides the functions
+//! to get the size and the capabilities of the current terminal, such as
+//! `term_cols`, `term_size` and `term_capabilities`, and
+//! `LineIter::new_for_term`.
+//! When disabling it while keeping the `std` feature, this crate does not
+//! depend on `libc` and `windows` at all, and provides only the wrapping and
+//! width calculation functions (and `WrapWriter`).
+//!
+//! ```toml
+//! [dependencies]
+//! linebreak = { version = "0.3.1", default-features = false, features = ["std"] }
+//! ```
+//!
+//! ## `rustix` feature
+//!
+//! The `rustix` feature makes the terminal functions on Unix use
+//! [rustix](https://docs.rs/rustix) instead of the raw `ioctl` calls of
+//! `libc`, which is the default.
+//! This implementation borrows the file descriptors safely and reports the
+//! errors through `rustix::io::Errno`, and the functions behave in the same
+//! way as the default implementation.
+//!
+//! ```toml
+//! [dependencies]
+//! linebreak = { version = "0.3.1", features = ["rustix"] }
+//! ```
+//!
+//! ## `parallel` feature
+//!
+//! The `parallel` feature enables `par_fill` and `par_refill`, which wrap the
+//! paragraphs of a large text concurrently with [rayon](https://docs.rs/rayon).
+//!
+//! ```toml
+//! [dependencies]
+//! linebreak = { version = "0.3.1", features = ["parallel"] }
+//! ```
+//!
+//! ## WebAssembly support
+//!
+//! This crate can be built for `wasm32-unknown-unknown`.
+//! On such targets, the width calculation and the line breaking work as on the
+//! other targets, but the terminal functions fail with
+//! `std::io::ErrorKind::Unsupported` (and `term_cols` returns the tentative
+//! value `80`).
+//! The `web` feature enables `element_cols`, which calculates the column
+//! number from the width of a DOM element for web-based terminals.
+//!
+//! ```toml
+//! [dependencies]
+//! linebreak = { version = "0.3.1", features = ["web"] }
+//! ```
+//!
+//! ## `ffi` feature
+//!
+//! The `ffi` feature enables the `ffi` module, which provides the C functions
+//! for wrapping texts and measuring their widths, so that the programs written
+//! in the other languages can use the line breaking of this crate.
+//! The C header is `include/linebreak.h`.
+//!
+//! ## `serde` feature
+//!
+//! The `serde` feature derives `Serialize` and `Deserialize` on `Options`,
+//! `Size` and the enums for the settings, so that the wrapping settings can be
+//! loaded from configuration files.
+//!
+//! ## `bidi` feature
+//!
+//! The `bidi` feature enables `LineIter::set_bidi` and `Options::bidi`, which
+//! reorder the lines of Hebrew or Arabic texts for display.
+//!
+//! ```toml
+//! [dependencies]
+//! linebreak = { version = "0.3.1", features = ["bidi"] }
+//! ```
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+mod ansi;
+#[cfg(feature = "bidi")]
+mod bidi;
+mod break_classifier;
 mod char_buffer;
+mod char_line_iter;
+mod chunk;
+mod code;
+mod columns;
+mod definition;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod fill;
+mod gutter;
+mod hyphenator;
+mod indent;
+mod line;
 mod line_iter;
+mod line_iter_buf;
 mod linebreak;
+mod list_item;
+mod long_word;
+mod no_break;
+mod options;
+mod paragraph_iter;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod protected;
+mod quote;
+mod quoted_printable;
+mod table;
+#[cfg(feature = "terminal")]
 mod terminal;
+mod uax14;
 mod unicode;
+mod url;
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+mod web;
+mod width_provider;
+mod word_iter;
+#[cfg(feature = "std")]
+mod wrap_writer;
+mod wrapped;
 
+pub use ansi::{strip_ansi, visible_width, AnsiPolicy};
+pub use break_classifier::BreakClassifier;
+pub use char_line_iter::CharLineIter;
+#[cfg(feature = "std")]
+pub use char_line_iter::IoChars;
+pub use columns::columns;
+pub use definition::format_definition;
+pub use fill::{
+    break_points, count_lines, fill, fill_into, for_each_line, join_lines, refill, wrap_balanced,
+    wrap_into,
+};
+pub use gutter::Gutter;
+pub use hyphenator::Hyphenator;
+pub use indent::{dedent, indent, indent_non_blank};
+pub use line::{Line, LineMeta};
 pub use line_iter::LineIter;
-pub use terminal::Size;
-pub use unicode::{char_width, is_print, text_width};
+pub use line_iter_buf::LineIterBuf;
+pub use linebreak::{HangingPunctuation, KinsokuLevel, LboType};
+pub use long_word::{LongWordPolicy, OverflowError, OverflowPolicy};
+pub use no_break::{NoBreakPair, NoBreakRule};
+pub use options::{
+    Align, BreakMode, FormFeed, IdeographicSpace, LineEnding, Options, OptionsError,
+};
+pub use paragraph_iter::ParagraphIter;
+#[cfg(feature = "parallel")]
+pub use parallel::{par_fill, par_refill};
+pub use quote::fill_quoted;
+pub use quoted_printable::wrap_quoted_printable;
+pub use table::format_table;
+#[cfg(feature = "terminal")]
+pub use terminal::{Capabilities, Size, TermStream};
+pub use unicode::{
+    char_width, char_width_cjk, is_print, text_width, text_width_cjk, unicode_version,
+    AmbiguousWidth, NonPrintPolicy,
+};
+pub use url::UrlBreak;
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+pub use web::element_cols;
+pub use width_provider::{text_width_by, WidthProvider};
+pub use word_iter::{Word, WordIter, WordKind};
+#[cfg(feature = "std")]
+pub use wrap_writer::WrapWriter;
+pub use wrapped::Wrapped;
 
 /// Returns the column number of the current terminal.
 ///
+/// If the standard output is redirected, this function retrieves the column
+/// number of the controlling terminal via `/dev/tty` on Unix, or via the
+/// standard error or `CONOUT$` on Windows.
+///
 /// If failing to retrieve the column number, this function returns the
 /// tentative value `80`.
 /// This is because this crate would be used on character output terminals,
@@ -75,15 +250,49 @@ pub use unicode::{char_width, is_print, text_width};
 /// execution.
 /// In such circumstances, it is assumed that returning a tentative value would
 /// be beneficial than returning an error.
+#[cfg(feature = "terminal")]
 pub fn term_cols() -> usize {
-    match terminal::term_cols() {
-        Ok(cols) => cols,
-        Err(_) => 80,
-    }
+    term_cols_or(80)
+}
+
+/// Breaks the specified text into lines within the column number of the
+/// current terminal, and returns a string which joins the lines with `\n`.
+///
+/// If failing to retrieve the column number, the text is broken within the
+/// tentative value `80`, as `term_cols` does.
+///
+/// ```rust
+///     use linebreak::{fill, term_cols, wrap_to_term};
+///
+///     let text = "The Rust programming language helps you write faster, \
+///         more reliable software.";
+///     assert_eq!(wrap_to_term(text), fill(text, term_cols()));
+/// ```
+#[cfg(feature = "terminal")]
+pub fn wrap_to_term(text: &str) -> String {
+    fill(text, term_cols())
+}
+
+/// Returns the column number of the current terminal, or the specified
+/// default value if failing to retrieve it.
+#[cfg(feature = "terminal")]
+pub fn term_cols_or(default: usize) -> usize {
+    terminal::term_cols().unwrap_or(default)
+}
+
+/// Returns the column number of the current terminal, or an error if failing
+/// to retrieve it.
+#[cfg(feature = "terminal")]
+pub fn try_term_cols() -> std::io::Result<usize> {
+    terminal::term_cols()
 }
 
 /// Returns the size of the current terminal.
 ///
+/// If the standard output is redirected, this function retrieves the size of
+/// the controlling terminal via `/dev/tty` on Unix, or via the standard error
+/// or `CONOUT$` on Windows.
+///
 /// If failing to retrieve the column number, this function returns the
 /// tentative size `{ col: 80, row: 24 }`.
 /// This is because this crate would be used on character output terminals,
@@ -91,9 +300,68 @@ pub fn term_cols() -> usize {
 /// execution.
 /// In such circumstances, it is assumed that returning a tentative value would
 /// be beneficial than returning an error.
+#[cfg(feature = "terminal")]
 pub fn term_size() -> Size {
-    match terminal::term_size() {
-        Ok(size) => size,
-        Err(_) => Size { col: 80, row: 24 },
-    }
+    term_size_or(Size::new(80, 24))
+}
+
+/// Returns the size of the current terminal, or the specified default size if
+/// failing to retrieve it.
+#[cfg(feature = "terminal")]
+pub fn term_size_or(default: Size) -> Size {
+    terminal::term_size().unwrap_or(default)
+}
+
+/// Returns the size of the current terminal, or an error if failing to
+/// retrieve it.
+#[cfg(feature = "terminal")]
+pub fn try_term_size() -> std::io::Result<Size> {
+    terminal::term_size()
+}
+
+/// Returns the size of the terminal which the specified stream is connected
+/// to, or an error if failing to retrieve it.
+///
+/// This function is useful for programs which pipe the standard output but
+/// still want the size of the terminal via the standard error.
+///
+/// ```rust
+///     use linebreak::{term_size_of, TermStream};
+///
+///     let cols = match term_size_of(TermStream::Stderr) {
+///         Ok(size) => size.col,
+///         Err(_) => 80,
+///     };
+/// ```
+#[cfg(feature = "terminal")]
+pub fn term_size_of(stream: TermStream) -> std::io::Result<Size> {
+    terminal::term_size_of(stream)
+}
+
+/// Returns whether the standard output is connected to a terminal.
+///
+/// This function is useful for deciding whether to wrap texts to the terminal
+/// width or to a fixed width when the output is piped or redirected.
+#[cfg(feature = "terminal")]
+pub fn is_tty_stdout() -> bool {
+    terminal::is_tty_stdout()
+}
+
+/// Returns whether the standard error is connected to a terminal.
+#[cfg(feature = "terminal")]
+pub fn is_tty_stderr() -> bool {
+    terminal::is_tty_stderr()
+}
+
+/// Returns the capabilities of the current terminal, such as whether it
+/// processes ANSI escape sequences.
+///
+/// On Windows, this function reports whether `ENABLE_VIRTUAL_TERMINAL_PROCESSING`
+/// is active in the console mode of the standard output, or of the standard
+/// error if the standard output is redirected.
+/// On Unix, a terminal connected to the standard output or the standard error
+/// is assumed to process the sequences unless `TERM` is `dumb`.
+#[cfg(feature = "terminal")]
+pub fn term_capabilities() -> Capabilities {
+    terminal::capabilities()
 }
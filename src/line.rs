@@ -0,0 +1,78 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::borrow::Cow;
+use core::ops::Range;
+
+/// `Line` is the struct for storing a line output by `LineIter::next_line`
+/// with its metadata.
+///
+/// ```rust
+///     use linebreak::LineIter;
+///
+///     let mut iter = LineIter::new("abc defghijkl\nmno", 6);
+///     iter.set_indent("> ");
+///
+///     let line = iter.next_line().unwrap();
+///     assert_eq!(line.text, "> abc");
+///     assert_eq!(line.width, 5);
+///     assert_eq!(line.range, 0..3);
+///     assert_eq!(line.indent, "> ");
+///     assert!(!line.is_forced);
+///     assert!(!line.is_hard_break);
+///
+///     let line = iter.next_line().unwrap();
+///     assert_eq!(line.text, "> defg");
+///     assert!(line.is_forced);
+///
+///     let line = iter.next_line().unwrap();
+///     assert_eq!(line.text, "> hijk");
+///     assert!(line.is_forced);
+///
+///     let line = iter.next_line().unwrap();
+///     assert_eq!(line.text, "> l");
+///     assert!(line.is_hard_break);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Line<'a> {
+    /// The line string including the indentation, the prefix and the suffix.
+    pub text: Cow<'a, str>,
+
+    /// The display width of the line string.
+    pub width: usize,
+
+    /// The byte range in the original text which the line covers.
+    pub range: Range<usize>,
+
+    /// The indentation applied to the line.
+    pub indent: Cow<'a, str>,
+
+    /// Whether the line is broken at a position which is not a line break
+    /// opportunity, because there is no opportunity within the line width.
+    pub is_forced: bool,
+
+    /// Whether the line ends at a line break character in the text.
+    pub is_hard_break: bool,
+}
+
+/// `LineMeta` is the struct for storing the metadata of a line which is passed
+/// to the callback of `for_each_line`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineMeta {
+    /// The zero-based index of the line.
+    pub index: usize,
+
+    /// The display width of the line string.
+    pub width: usize,
+
+    /// The byte range in the original text which the line covers.
+    pub range: Range<usize>,
+
+    /// Whether the line is broken at a position which is not a line break
+    /// opportunity, because there is no opportunity within the line width.
+    pub is_forced: bool,
+
+    /// Whether the line ends at a line break character in the text.
+    pub is_hard_break: bool,
+}
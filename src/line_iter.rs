@@ -2,26 +2,160 @@
 // This program is free software under MIT License.
 // See the file LICENSE in this distribution for more details.
 
-use std::str::Chars;
-
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+use core::str::Chars;
+
+use crate::ansi::{escape_len, find_escape, strip_ansi, AnsiPolicy, AnsiState};
+#[cfg(feature = "bidi")]
+use crate::bidi::{is_rtl_paragraph, reorder_line, strong_rtl};
 use crate::char_buffer::CharBuffer;
+use crate::code::CodeState;
+use crate::gutter::Gutter;
+use crate::hyphenator::Hyphenator;
+use crate::line::Line;
 use crate::linebreak::*;
-use crate::unicode::char_width;
+use crate::list_item::list_marker_width;
+use crate::long_word::{is_identifier_boundary, LongWordPolicy, OverflowError, OverflowPolicy};
+use crate::no_break::{is_abbreviation, NoBreakRule};
+use crate::options::{Align, BreakMode, FormFeed, IdeographicSpace, Options, OptionsError};
+use crate::protected::ProtectedSpans;
+use crate::uax14::Uax14State;
+use crate::unicode::{
+    char_width_after, char_width_with, escape_non_print, is_non_print, is_regional_indicator,
+    is_zero_width_attached, text_width_with, AmbiguousWidth, NonPrintPolicy,
+};
+use crate::url::{find_url, UrlBreak, UrlState};
+use crate::width_provider::WidthProvider;
 
 /// `LineIter` is the struct that outputs the given string line by line.
 /// This struct can control the overall line width and the indentation from any
 /// desired line.
 pub struct LineIter<'a> {
+    text: &'a str,
     scanner: Chars<'a>,
+    pos: usize,
+    range: Range<usize>,
+    line_indent: Cow<'a, str>,
+    is_forced: bool,
+    is_hard_break: bool,
     buffer: CharBuffer,
     width: [usize; 2],
     lbo_pos: usize,
     limit: usize,
-    indent: &'a str,
+    indent: Cow<'a, str>,
     indent_width: usize,
+    first_indent: Option<Cow<'a, str>>,
+    first_indent_width: usize,
+    is_first_line: bool,
+    first_line_offset: usize,
+    first_line_width: Option<usize>,
+    first_line_count: usize,
+    prefix: &'a str,
+    prefix_width: usize,
+    suffix: &'a str,
+    suffix_width: usize,
+    continuation: &'a str,
+    continuation_width: usize,
+    shell_command: bool,
+    line_numbers: usize,
+    gutter: Option<&'a dyn Gutter>,
+    gutter_width: usize,
+    line_no: usize,
+    max_lines: usize,
+    placeholder: &'a str,
+    placeholder_width: usize,
     open_quot: u8,
     open_apos: u8,
+    break_mode: BreakMode,
+    uax14: Uax14State,
+    code: CodeState,
+    ri_odd: bool,
+    after_joiner: bool,
+    tab_width: usize,
+    tab_spaces: usize,
+    ambiguous_width: AmbiguousWidth,
+    width_overrides: BTreeMap<char, usize>,
+    align: Align,
+    hyphenator: Option<&'a dyn Hyphenator>,
+    width_provider: Option<&'a dyn WidthProvider>,
+    paragraph_mode: bool,
+    break_on_hyphen: bool,
+    quote_aware: bool,
+    break_hangul: bool,
+    kinsoku_level: KinsokuLevel,
+    burasage: bool,
+    hanging: HangingPunctuation,
+    no_break_rules: Vec<&'a dyn NoBreakRule>,
+    protected: ProtectedSpans,
+    protected_delimiters: Vec<(&'a str, &'a str)>,
+    sentence_slack: usize,
+    sentence_start: Option<usize>,
+    widow_control: bool,
+    url_break: UrlBreak,
+    url: UrlState,
+    long_word_policy: LongWordPolicy,
+    overflow_policy: OverflowPolicy,
+    overflow_error: Option<OverflowError>,
+    keep_zero_width: bool,
+    form_feed: FormFeed,
+    ideographic_space: IdeographicSpace,
+    preserve_leading_whitespace: bool,
+    auto_indent: bool,
+    auto_indent_hang: usize,
+    list_items: bool,
+    #[cfg(feature = "bidi")]
+    bidi: bool,
+    #[cfg(feature = "bidi")]
+    bidi_rtl: bool,
+    #[cfg(feature = "bidi")]
+    bidi_run: usize,
+    auto_indent_range: Range<usize>,
+    auto_indent_width: usize,
+    auto_indent_pad: usize,
+    line_start: usize,
+    strict_line_breaks: bool,
+    kinsoku: Kinsoku,
+    non_print: NonPrintPolicy,
+    escaped: Vec<char>,
+    ansi_policy: AnsiPolicy,
+    ansi_spans: ProtectedSpans,
+    ansi_state: AnsiState,
+    is_blank_line: bool,
+    pending_line: Option<Range<usize>>,
+    peeked: Option<Peeked<'a>>,
     has_next: bool,
+    text_follows: bool,
+}
+
+// The default continuation marker and the additional indentation of the
+// continued lines in the shell command mode.
+const SHELL_CONTINUATION: &str = " \\";
+const SHELL_INDENT: &str = "  ";
+
+// The layout of the decoration around a line which is broken off.
+struct Layout<'a> {
+    indent: Cow<'a, str>,
+    indent_width: usize,
+    auto_indent_width: usize,
+    auto_indent_range: Range<usize>,
+    limit: usize,
+    is_continued: bool,
+    is_decorated: bool,
+}
+
+// The line which is read ahead by `LineIter::peek`, with the metadata of it.
+struct Peeked<'a> {
+    line: Option<Cow<'a, str>>,
+    range: Range<usize>,
+    indent: Cow<'a, str>,
+    is_forced: bool,
+    is_hard_break: bool,
+    consumed: usize,
 }
 
 impl<'a> LineIter<'a> {
@@ -36,21 +170,247 @@ impl<'a> LineIter<'a> {
     /// ```
     pub fn new(text: &'a str, line_width: usize) -> LineIter<'a> {
         LineIter {
+            text,
             scanner: text.chars(),
+            pos: 0,
+            range: 0..0,
+            line_indent: Cow::Borrowed(""),
+            is_forced: false,
+            is_hard_break: false,
             buffer: CharBuffer::new(line_width),
             width: [0; 2],
             lbo_pos: 0,
             limit: line_width,
-            indent: "",
+            indent: Cow::Borrowed(""),
             indent_width: 0,
+            first_indent: None,
+            first_indent_width: 0,
+            is_first_line: true,
+            first_line_offset: 0,
+            first_line_width: None,
+            first_line_count: 1,
+            prefix: "",
+            prefix_width: 0,
+            suffix: "",
+            suffix_width: 0,
+            continuation: "",
+            continuation_width: 0,
+            shell_command: false,
+            line_numbers: 0,
+            gutter: None,
+            gutter_width: 0,
+            line_no: 0,
+            max_lines: 0,
+            placeholder: "…",
+            placeholder_width: 2,
             open_quot: 0,
             open_apos: 0,
+            break_mode: BreakMode::Basic,
+            uax14: Uax14State::new(),
+            code: CodeState::new(),
+            ri_odd: false,
+            after_joiner: false,
+            tab_width: 0,
+            tab_spaces: 0,
+            ambiguous_width: AmbiguousWidth::Wide,
+            width_overrides: BTreeMap::new(),
+            align: Align::Left,
+            hyphenator: None,
+            width_provider: None,
+            paragraph_mode: false,
+            break_on_hyphen: false,
+            quote_aware: true,
+            break_hangul: false,
+            kinsoku_level: KinsokuLevel::Strict,
+            burasage: false,
+            hanging: HangingPunctuation::default(),
+            no_break_rules: Vec::new(),
+            protected: ProtectedSpans::new(),
+            protected_delimiters: Vec::new(),
+            sentence_slack: 0,
+            sentence_start: None,
+            widow_control: false,
+            url_break: UrlBreak::Normal,
+            url: UrlState::new(),
+            long_word_policy: LongWordPolicy::BreakAnywhere,
+            overflow_policy: OverflowPolicy::ForceBreak,
+            overflow_error: None,
+            keep_zero_width: true,
+            form_feed: FormFeed::Normal,
+            ideographic_space: IdeographicSpace::Space,
+            preserve_leading_whitespace: false,
+            auto_indent: false,
+            auto_indent_hang: 0,
+            list_items: false,
+            #[cfg(feature = "bidi")]
+            bidi: false,
+            #[cfg(feature = "bidi")]
+            bidi_rtl: false,
+            #[cfg(feature = "bidi")]
+            bidi_run: 0,
+            auto_indent_range: 0..0,
+            auto_indent_width: 0,
+            auto_indent_pad: 0,
+            line_start: 0,
+            strict_line_breaks: false,
+            kinsoku: Kinsoku::new(),
+            non_print: NonPrintPolicy::Strip,
+            escaped: Vec::new(),
+            ansi_policy: AnsiPolicy::Ignore,
+            ansi_spans: ProtectedSpans::new(),
+            ansi_state: AnsiState::new(),
+            is_blank_line: true,
+            pending_line: None,
+            peeked: None,
             has_next: true,
+            text_follows: false,
+        }
+    }
+
+    /// Creates a `LineIter` instance which outputs the given string line by
+    /// line within the column number of the current terminal.
+    ///
+    /// If failing to retrieve the column number, the tentative value `80` is
+    /// used, as `term_cols` does.
+    ///
+    /// ```rust
+    ///     use linebreak::{term_cols, LineIter};
+    ///
+    ///     let mut iter = LineIter::new_for_term("abc def ghi");
+    ///     assert!(iter.next().unwrap().len() <= term_cols());
+    /// ```
+    #[cfg(feature = "terminal")]
+    pub fn new_for_term(text: &'a str) -> LineIter<'a> {
+        LineIter::new(text, crate::term_cols())
+    }
+
+    /// Creates a `LineIter` instance with the settings in the specified
+    /// `Options`.
+    /// If the settings are invalid, this function returns an `OptionsError`.
+    ///
+    /// ```rust
+    ///     use linebreak::{LineIter, Options};
+    ///
+    ///     let opts = Options::new(10).prefix("> ");
+    ///     let mut iter = LineIter::with_options("abc def ghi", &opts).unwrap();
+    ///     assert_eq!(iter.next().unwrap(), "> abc def");
+    ///     assert_eq!(iter.next().unwrap(), "> ghi");
+    ///     assert!(iter.next().is_none());
+    ///
+    ///     let opts = Options::new(2).prefix("> ");
+    ///     assert!(LineIter::with_options("abc def ghi", &opts).is_err());
+    /// ```
+    pub fn with_options(text: &'a str, opts: &'a Options) -> Result<LineIter<'a>, OptionsError> {
+        opts.validate()?;
+        Ok(LineIter::from_options(text, opts))
+    }
+
+    // Creates a `LineIter` instance with the settings in the specified
+    // `Options` without validating them.
+    // As with the setters, a line holds at least one column of text even if
+    // the settings leave no room for it.
+    pub(crate) fn from_options(text: &'a str, opts: &'a Options) -> LineIter<'a> {
+        let mut iter = LineIter::new(text, opts.width);
+        iter.ambiguous_width = opts.ambiguous_width;
+        iter.width_overrides = opts.width_overrides.clone();
+        iter.tab_width = opts.tab_width;
+        iter.break_mode = opts.break_mode;
+        iter.align = opts.align;
+        iter.paragraph_mode = opts.paragraph_mode;
+        iter.break_on_hyphen = opts.break_on_hyphen;
+        iter.quote_aware = opts.quote_aware;
+        iter.break_hangul = opts.break_hangul;
+        iter.kinsoku_level = opts.kinsoku_level;
+        iter.burasage = opts.burasage;
+        iter.hanging = opts.hanging;
+        for rule in &opts.no_break_rules {
+            iter.no_break_rules.push(&*rule.0);
+        }
+        for (open, close) in &opts.protected_delimiters {
+            iter.add_protected_delimiters(open, close);
+        }
+        iter.sentence_slack = opts.sentence_slack;
+        iter.widow_control = opts.widow_control;
+        iter.url_break = opts.url_break;
+        iter.long_word_policy = opts.long_word_policy;
+        iter.overflow_policy = opts.overflow_policy;
+        iter.keep_zero_width = opts.keep_zero_width;
+        iter.non_print = opts.non_print_policy;
+        iter.set_ansi_policy(opts.ansi_policy);
+        iter.strict_line_breaks = opts.strict_line_breaks;
+        iter.form_feed = opts.form_feed;
+        iter.set_ideographic_space(opts.ideographic_space);
+        iter.preserve_leading_whitespace = opts.preserve_leading_whitespace;
+        iter.auto_indent = opts.auto_indent;
+        iter.auto_indent_hang = opts.auto_indent_hang;
+        iter.list_items = opts.list_items;
+        #[cfg(feature = "bidi")]
+        {
+            iter.bidi = opts.bidi;
+        }
+        if let Some(hyphenator) = &opts.hyphenator {
+            iter.hyphenator = Some(&*hyphenator.0);
+        }
+        if let Some(provider) = &opts.width_provider {
+            iter.width_provider = Some(&*provider.0);
+        }
+        iter.set_indent(&opts.indent);
+        if let Some(first_indent) = &opts.first_indent {
+            iter.set_first_indent(first_indent);
+        }
+        iter.first_line_offset = opts.first_line_offset;
+        iter.first_line_width = opts.first_line_width;
+        iter.first_line_count = opts.first_line_count;
+        iter.set_prefix(&opts.prefix);
+        iter.set_suffix(&opts.suffix);
+        iter.shell_command = opts.shell_command;
+        match opts.continuation_marker.is_empty() && opts.shell_command {
+            true => iter.set_continuation_marker(SHELL_CONTINUATION),
+            false => iter.set_continuation_marker(&opts.continuation_marker),
         }
+        for (open, close) in &opts.quote_pairs {
+            iter.add_quote_pair(*open, *close);
+        }
+        iter.set_line_numbers(opts.line_numbers);
+        iter.set_max_lines(opts.max_lines);
+        iter.set_placeholder(&opts.placeholder);
+        if let Some((width, gutter)) = &opts.gutter {
+            iter.set_gutter(*width, &*gutter.0);
+        }
+        iter
+    }
+
+    /// Sets the width of the subsequent lines.
+    /// The characters which are already read but not output yet are broken
+    /// again within the new width.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("abc def ghi jkl mno pqr", 7);
+    ///     assert_eq!(iter.next().unwrap(), "abc def");
+    ///     iter.set_width(11);
+    ///     assert_eq!(iter.next().unwrap(), "ghi jkl mno");
+    ///     iter.set_width(3);
+    ///     assert_eq!(iter.next().unwrap(), "pqr");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_width(&mut self, line_width: usize) {
+        self.limit = line_width;
+    }
+
+    /// Returns the width of the output lines.
+    pub fn width(&self) -> usize {
+        self.limit
     }
 
     /// Sets an indentation for the subsequent lines.
     ///
+    /// If the total width of the indentation, the prefix and the suffix is not
+    /// less than the line width, the text of each line is limited to one column
+    /// and the lines overflow the line width.
+    /// (`Options::validate` reports such settings as an error.)
+    ///
     /// ```rust
     ///     use linebreak::LineIter;
     ///
@@ -62,680 +422,5104 @@ impl<'a> LineIter<'a> {
     ///     assert_eq!(iter.next().unwrap(), "    wxyz");
     ///     assert_eq!(iter.next().is_none(), true);
     /// ```
-    pub fn set_indent(&mut self, indent: &'a str) {
-        self.indent = indent;
-        self.indent_width = crate::text_width(indent);
+    ///
+    /// An owned string can be set, so that an indentation built dynamically
+    /// need not outlive this instance.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("abc def ghi", 6);
+    ///     let mut lines = Vec::new();
+    ///     for n in 1..=3 {
+    ///         iter.set_indent("-".repeat(n));
+    ///         lines.push(iter.next().unwrap().into_owned());
+    ///     }
+    ///     assert_eq!(lines, ["-abc", "--def", "---ghi"]);
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_indent<S>(&mut self, indent: S)
+    where
+        S: Into<Cow<'a, str>>,
+    {
+        self.indent = indent.into();
+        self.indent_width = self.text_width(&self.indent);
     }
 
-    /// Re-initializes with an argument string for reusing this instance.
+    /// Returns the indentation for the subsequent lines.
+    pub fn indent(&self) -> &str {
+        &self.indent
+    }
+
+    /// Returns the display width of the indentation for the subsequent lines.
+    pub fn indent_width(&self) -> usize {
+        self.indent_width
+    }
+
+    /// Sets an indentation for the first line of each paragraph.
+    /// A paragraph starts at the beginning of the text and after each line
+    /// break character.
+    /// The other lines are indented with the string set by `set_indent`.
     ///
     /// ```rust
     ///     use linebreak::LineIter;
     ///
-    ///     let mut iter = LineIter::new("abcdefghijklmn", 10);
-    ///     assert_eq!(iter.next().unwrap(), "abcdefghij");
-    ///     assert_eq!(iter.next().unwrap(), "klmn");
-    ///     assert_eq!(iter.next().is_none(), true);
+    ///     let mut iter = LineIter::new("abcde fghij klmno\npqrst", 10);
+    ///     iter.set_first_indent("* ");
+    ///     iter.set_indent("  ");
+    ///     assert_eq!(iter.next().unwrap(), "* abcde");
+    ///     assert_eq!(iter.next().unwrap(), "  fghij");
+    ///     assert_eq!(iter.next().unwrap(), "  klmno");
+    ///     assert_eq!(iter.next().unwrap(), "* pqrst");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_first_indent<S>(&mut self, indent: S)
+    where
+        S: Into<Cow<'a, str>>,
+    {
+        let indent = indent.into();
+        self.first_indent_width = self.text_width(&indent);
+        self.first_indent = Some(indent);
+    }
+
+    /// Sets the number of the columns by which the first output line is
+    /// shortened, such as the width of a prompt or a label which is already
+    /// printed before the text.
+    /// The subsequent lines, including the first lines of the subsequent
+    /// paragraphs, are not affected.
     ///
-    ///     iter.init("opqrstuvwxyz");
-    ///     assert_eq!(iter.next().unwrap(), "opqrstuvwx");
-    ///     assert_eq!(iter.next().unwrap(), "yz");
-    ///     assert_eq!(iter.next().is_none(), true);
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("abc def ghi jkl", 11);
+    ///     iter.set_first_line_offset(7);
+    ///     assert_eq!(iter.next().unwrap(), "abc");
+    ///     assert_eq!(iter.next().unwrap(), "def ghi jkl");
+    ///     assert!(iter.next().is_none());
     /// ```
-    pub fn init(&mut self, text: &'a str) {
-        self.scanner = text.chars();
-        self.buffer.clear();
-        self.width[0] = 0;
-        self.width[1] = 0;
-        self.lbo_pos = 0;
-        self.open_quot = 0;
-        self.open_apos = 0;
-        self.has_next = true;
+    pub fn set_first_line_offset(&mut self, offset: usize) {
+        self.first_line_offset = offset;
     }
 
-    /// Returns an Option of a line string.
-    /// If there is a line string to be printed, this method returns a
-    /// `Some(String)`, otherwise returns `None.`
+    /// Sets the width of the first output lines, which are as many as the
+    /// number set by `set_first_line_count` (one by default).
+    /// The subsequent lines are broken within the width set by `set_width`.
+    /// This is useful for the layouts in which the first lines are narrower or
+    /// wider than the others, such as a paragraph with a drop cap or a text
+    /// flowing around a sidebar.
     ///
     /// ```rust
     ///     use linebreak::LineIter;
     ///
-    ///     let text = "The Rust programming language helps you write faster, \
-    ///         more reliable software.";
-    ///     let mut iter = LineIter::new(&text, 30);
-    ///     assert_eq!(iter.next().unwrap(), "The Rust programming language");
-    ///     assert_eq!(iter.next().unwrap(), "helps you write faster, more");
-    ///     assert_eq!(iter.next().unwrap(), "reliable software.");
-    ///     assert_eq!(iter.next().is_none(), true);
+    ///     let mut iter = LineIter::new("abc def ghi jkl mno pqr", 11);
+    ///     iter.set_first_line_width(7);
+    ///     iter.set_first_line_count(2);
+    ///     assert_eq!(iter.next().unwrap(), "abc def");
+    ///     assert_eq!(iter.next().unwrap(), "ghi jkl");
+    ///     assert_eq!(iter.next().unwrap(), "mno pqr");
+    ///     assert!(iter.next().is_none());
     /// ```
-    pub fn next(&mut self) -> Option<String> {
-        if !self.has_next {
-            return None;
-        }
+    pub fn set_first_line_width(&mut self, line_width: usize) {
+        self.first_line_width = Some(line_width);
+    }
 
-        let limit = self.limit - self.indent_width;
+    /// Sets the number of the first output lines to which the width set by
+    /// `set_first_line_width` is applied.
+    pub fn set_first_line_count(&mut self, count: usize) {
+        self.first_line_count = count;
+    }
 
-        if self.width[0] > limit {
-            let mut diff = self.width[0] - limit;
-            self.width[0] = diff;
-            let mut i = self.buffer.len();
-            while i > 0 {
-                i -= 1;
-                if let Some(ch) = self.buffer.get(i) {
-                    let ch_width = char_width(ch);
-                    if diff <= ch_width {
-                        let mut line = self.buffer.substring_trimmed_end(0, i);
-                        self.buffer.cr(i);
-                        if !line.is_empty() {
-                            line.insert_str(0, self.indent);
-                        }
-                        return Some(line);
-                    }
-                    diff -= ch_width;
-                } else {
-                    break;
-                }
-            }
-        } else if self.width[0] == limit {
-            self.width[0] = 0;
-            let mut line = self.buffer.to_string_trimmed_end();
-            self.buffer.cr(0);
-            if !line.is_empty() {
-                line.insert_str(0, self.indent);
-            }
-            return Some(line);
-        }
+    /// Sets a string which is put at the beginning of every line, before the
+    /// indentation.
+    /// The width of this string is subtracted from the line width.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("abc def ghi", 10);
+    ///     iter.set_prefix("// ");
+    ///     assert_eq!(iter.next().unwrap(), "// abc def");
+    ///     assert_eq!(iter.next().unwrap(), "// ghi");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_prefix(&mut self, prefix: &'a str) {
+        self.prefix = prefix;
+        self.prefix_width = self.text_width(prefix);
+    }
 
-        let mut state = LboState {
-            lbo_type: LboType::Never,
-            lbo_prev: LboType::Never,
-            open_quot: self.open_quot,
-            open_apos: self.open_apos,
-        };
+    /// Sets a string which is put at the end of every line.
+    /// The width of this string is subtracted from the line width, and each
+    /// line is padded with spaces so that the suffixes are aligned.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("abc def ghi", 11);
+    ///     iter.set_prefix("| ");
+    ///     iter.set_suffix(" |");
+    ///     assert_eq!(iter.next().unwrap(), "| abc def |");
+    ///     assert_eq!(iter.next().unwrap(), "| ghi     |");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_suffix(&mut self, suffix: &'a str) {
+        self.suffix = suffix;
+        self.suffix_width = self.text_width(suffix);
+    }
 
-        while let Some(ch) = self.scanner.next() {
-            line_break_opportunity(ch, &mut state);
+    /// Sets a marker which is put at the end of every line which is wrapped
+    /// softly, that is, every line which is continued to the next line.
+    /// The width of this marker is subtracted from the line width of all
+    /// lines, so that the marker fits in it.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("abc def ghi\njkl", 8);
+    ///     iter.set_continuation_marker(" ↩");
+    ///     assert_eq!(iter.next().unwrap(), "abc ↩");
+    ///     assert_eq!(iter.next().unwrap(), "def ↩");
+    ///     assert_eq!(iter.next().unwrap(), "ghi");
+    ///     assert_eq!(iter.next().unwrap(), "jkl");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_continuation_marker(&mut self, marker: &'a str) {
+        self.continuation = marker;
+        self.continuation_width = self.text_width(marker);
+    }
 
-            if state.lbo_type == LboType::Break {
-                let mut line = self.buffer.to_string_trimmed_end();
-                self.buffer.clear();
-                self.width[0] = 0;
-                self.width[1] = 0;
-                self.lbo_pos = 0;
-                self.open_quot = 0;
-                self.open_apos = 0;
-                if !line.is_empty() {
-                    line.insert_str(0, self.indent);
-                }
-                self.has_next = true;
-                return Some(line);
-            }
+    /// Sets whether to wrap the text as shell command lines.
+    /// This is disabled by default.
+    ///
+    /// If enabled, a line is broken only at the spaces between arguments,
+    /// which are outside of quotations and not escaped by backslashes, each
+    /// continued line ends with ` \\`, and the following lines are indented
+    /// by two spaces.
+    /// Enabling this mode sets the continuation marker to ` \\`, and disabling
+    /// it clears the marker.
+    /// An argument which is longer than a line is broken according to the
+    /// `OverflowPolicy`, so `OverflowPolicy::Overflow` keeps it unbroken.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let text = "git commit -m 'Fix the typo in README' --author=me";
+    ///     let mut iter = LineIter::new(text, 30);
+    ///     iter.set_shell_command(true);
+    ///     assert_eq!(iter.next().unwrap(), "git commit -m \\");
+    ///     assert_eq!(iter.next().unwrap(), "  'Fix the typo in README' \\");
+    ///     assert_eq!(iter.next().unwrap(), "  --author=me");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_shell_command(&mut self, enabled: bool) {
+        self.shell_command = enabled;
+        match enabled {
+            true => self.set_continuation_marker(SHELL_CONTINUATION),
+            false => self.set_continuation_marker(""),
+        }
+    }
 
-            if self.buffer.is_empty() && state.lbo_type == LboType::Space {
-                continue;
-            }
+    // Checks whether the next character is escaped by a backslash outside of
+    // single quotes in a shell command.
+    fn is_escaped_in_shell(&self, state: &LboState) -> bool {
+        if state.open_apos > 0 {
+            return false;
+        }
+        let mut n = 0;
+        while self.buffer.get(self.buffer.len().wrapping_sub(n + 1)) == Some('\\') {
+            n += 1;
+        }
+        n % 2 == 1
+    }
 
-            let ch_width = char_width(ch);
-            let mut lbo_pos = self.lbo_pos;
+    /// Sets the number of the digits of the line numbers which are put at the
+    /// beginning of every line, before the prefix.
+    /// The line numbers are right-aligned within the digits and followed by a
+    /// space, and the width of them is subtracted from the line width.
+    /// If this value is zero (default), no line number is put.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("abc def ghi\n\njkl", 10);
+    ///     iter.set_line_numbers(2);
+    ///     assert_eq!(iter.next().unwrap(), " 1 abc def");
+    ///     assert_eq!(iter.next().unwrap(), " 2 ghi");
+    ///     assert_eq!(iter.next().unwrap(), " 3 ");
+    ///     assert_eq!(iter.next().unwrap(), " 4 jkl");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_line_numbers(&mut self, digits: usize) {
+        self.line_numbers = digits;
+        if self.gutter.is_none() {
+            self.gutter_width = if digits > 0 { digits + 1 } else { 0 };
+        }
+    }
 
-            if self.width[0] + self.width[1] + ch_width > limit {
-                if state.lbo_prev == LboType::Before {
-                    let mut line = self.buffer.substring_trimmed_end(0, lbo_pos);
-                    self.buffer.cr(lbo_pos);
+    /// Sets a `Gutter` which generates a string put at the beginning of every
+    /// line, before the prefix, instead of the line numbers.
+    /// The first argument is the width of the gutter, which is subtracted from
+    /// the line width, and a gutter string narrower than it is padded with
+    /// spaces.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let gutter = |n: usize| format!("{}|", n * 10);
+    ///     let mut iter = LineIter::new("abc def ghi", 8);
+    ///     iter.set_gutter(4, &gutter);
+    ///     assert_eq!(iter.next().unwrap(), "10| abc");
+    ///     assert_eq!(iter.next().unwrap(), "20| def");
+    ///     assert_eq!(iter.next().unwrap(), "30| ghi");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_gutter(&mut self, width: usize, gutter: &'a dyn Gutter) {
+        self.gutter = Some(gutter);
+        self.gutter_width = width;
+    }
 
-                    self.buffer.add(ch);
-                    self.width[0] = self.width[1] + ch_width;
-                    self.width[1] = 0;
-                    self.lbo_pos = self.buffer.len();
+    /// Sets the maximum number of the output lines.
+    /// If the text needs more lines, the last line is truncated so that the
+    /// placeholder is put at its end, and the rest of the text is discarded.
+    /// If this value is zero (default), the number of lines is not limited.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("abc def ghi jkl", 7);
+    ///     iter.set_max_lines(1);
+    ///     assert_eq!(iter.next().unwrap(), "abc d…");
+    ///     assert!(iter.next().is_none());
+    ///
+    ///     iter.init("abc def ghi jkl");
+    ///     iter.set_width(10);
+    ///     iter.set_placeholder(" [more]");
+    ///     assert_eq!(iter.next().unwrap(), "abc [more]");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_max_lines(&mut self, max_lines: usize) {
+        self.max_lines = max_lines;
+    }
 
-                    self.open_quot = state.open_quot;
-                    self.open_apos = state.open_apos;
+    /// Sets a string which is put at the end of the last line truncated by
+    /// the maximum number of lines.
+    /// The default placeholder is `"…"`.
+    pub fn set_placeholder(&mut self, placeholder: &'a str) {
+        self.placeholder = placeholder;
+        self.placeholder_width = self.text_width(placeholder);
+    }
 
-                    if !line.is_empty() {
-                        line.insert_str(0, self.indent);
-                    }
-                    self.has_next = true;
-                    return Some(line);
-                }
+    /// Sets the alignment of the lines within the line width.
+    /// The lines are padded with spaces according to their display widths.
+    ///
+    /// ```rust
+    ///     use linebreak::{Align, LineIter};
+    ///
+    ///     let mut iter = LineIter::new("abc def ghi", 8);
+    ///     iter.set_align(Align::Right);
+    ///     assert_eq!(iter.next().unwrap(), " abc def");
+    ///     assert_eq!(iter.next().unwrap(), "     ghi");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_align(&mut self, align: Align) {
+        self.align = align;
+    }
 
-                match state.lbo_type {
-                    LboType::Before | LboType::Both | LboType::Space => {
-                        lbo_pos = self.buffer.len();
-                    }
-                    _ => (),
-                }
-                // break forcely when no lbo in the current line
-                if lbo_pos == 0 {
-                    self.width[0] += self.width[1];
-                    self.width[1] = 0;
-                    lbo_pos = self.buffer.len();
-                }
+    /// Sets whether to reflow the text by paragraphs.
+    /// In the paragraph mode, a single line break inside a paragraph is treated
+    /// as a space, and only blank lines separate paragraphs.
+    /// A LINE SEPARATOR (U+2028) always breaks a line, and a PARAGRAPH
+    /// SEPARATOR (U+2029) ends a paragraph as a blank line does.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("abc\ndef ghi\n\njkl\nmno", 10);
+    ///     iter.set_paragraph_mode(true);
+    ///     assert_eq!(iter.next().unwrap(), "abc def");
+    ///     assert_eq!(iter.next().unwrap(), "ghi");
+    ///     assert_eq!(iter.next().unwrap(), "");
+    ///     assert_eq!(iter.next().unwrap(), "jkl mno");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_paragraph_mode(&mut self, enabled: bool) {
+        self.paragraph_mode = enabled;
+    }
 
-                let mut line = self.buffer.substring_trimmed_end(0, lbo_pos);
-                self.buffer.cr(lbo_pos);
+    /// Sets a `Hyphenator` which splits a word running over the end of a line
+    /// at a syllable boundary with a trailing hyphen.
+    pub fn set_hyphenator(&mut self, hyphenator: &'a dyn Hyphenator) {
+        self.hyphenator = Some(hyphenator);
+    }
 
-                match state.lbo_type {
-                    LboType::Space => {
-                        self.width[0] = 0;
-                        self.width[1] = 0;
-                        self.lbo_pos = 0;
-                    }
-                    LboType::Before | LboType::Both => {
-                        self.buffer.add(ch);
-                        self.width[0] = ch_width;
-                        self.width[1] = 0;
-                        self.lbo_pos = 0;
-                    }
-                    LboType::After => {
-                        self.buffer.add(ch);
-                        self.width[0] = self.width[1] + ch_width;
-                        self.width[1] = 0;
-                        self.lbo_pos = self.buffer.len();
-                    }
-                    _ => {
-                        self.buffer.add(ch);
-                        self.width[0] = self.width[1] + ch_width;
-                        self.width[1] = 0;
-                        self.lbo_pos = 0;
-                    }
-                }
+    /// Sets a `WidthProvider` which measures the display widths of characters
+    /// instead of the built-in `char_width` function.
+    /// The widths of the indentations, the prefix and the suffix which are
+    /// already set are measured again with it.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     // treats every character as width 1.
+    ///     let provider = |_: char| 1;
+    ///
+    ///     let mut iter = LineIter::new("あいう えお", 6);
+    ///     iter.set_width_provider(&provider);
+    ///     assert_eq!(iter.next().unwrap(), "あいう えお");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_width_provider(&mut self, provider: &'a dyn WidthProvider) {
+        self.width_provider = Some(provider);
+        self.measure_decorations();
+    }
 
-                self.open_quot = state.open_quot;
-                self.open_apos = state.open_apos;
+    /// Sets the display width of the specified character, which overrides the
+    /// width given by the built-in `char_width` function or a `WidthProvider`.
+    /// This is used to correct the widths of specific characters which a
+    /// terminal or a font renders differently, such as Powerline glyphs or
+    /// Nerd Font icons.
+    /// A character whose width is overridden with a positive value is output
+    /// even if it is non-printable, such as a character in the private use
+    /// area.
+    /// The widths of the indentations, the prefix and the suffix which are
+    /// already set are measured again with it.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     // U+E0B0 is a Powerline glyph and U+F101 is a Nerd Font icon, which
+    ///     // are in the private use area and non-printable by default.
+    ///     let mut iter = LineIter::new("\u{E0B0} abc \u{F101}", 6);
+    ///     assert_eq!(iter.next().unwrap(), "abc");
+    ///     assert!(iter.next().is_none());
+    ///
+    ///     iter.init("\u{E0B0} abc \u{F101}");
+    ///     iter.set_width_override('\u{E0B0}', 1);
+    ///     iter.set_width_override('\u{F101}', 2);
+    ///     assert_eq!(iter.next().unwrap(), "\u{E0B0} abc");
+    ///     assert_eq!(iter.next().unwrap(), "\u{F101}");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_width_override(&mut self, ch: char, width: usize) {
+        self.width_overrides.insert(ch, width);
+        self.measure_decorations();
+    }
 
-                if !line.is_empty() {
-                    line.insert_str(0, self.indent);
-                }
+    /// Sets whether to measure the widths of characters in CJK contexts, in
+    /// which the characters whose East-Asian-Width is Ambiguous are wide, as
+    /// the `_cjk` functions of the `unicode-width` crate do.
+    /// If disabled, they are narrow as the other functions of that crate.
+    /// This is enabled by default.
+    /// The widths of the indentations, the prefix and the suffix which are
+    /// already set are measured again.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("αβγ δεζ", 7);
+    ///     assert_eq!(iter.next().unwrap(), "αβγ");
+    ///     assert_eq!(iter.next().unwrap(), "δεζ");
+    ///
+    ///     iter.init("αβγ δεζ");
+    ///     iter.set_cjk(false);
+    ///     assert_eq!(iter.next().unwrap(), "αβγ δεζ");
+    /// ```
+    pub fn set_cjk(&mut self, enabled: bool) {
+        self.ambiguous_width = match enabled {
+            true => AmbiguousWidth::Wide,
+            false => AmbiguousWidth::Narrow,
+        };
+        self.measure_decorations();
+    }
+
+    // Measures the widths of the indentations, the prefix, the suffix and the
+    // placeholder again after the way of measuring widths is changed.
+    fn measure_decorations(&mut self) {
+        self.indent_width = self.text_width(&self.indent);
+        if let Some(first_indent) = &self.first_indent {
+            self.first_indent_width = self.text_width(first_indent);
+        }
+        self.prefix_width = self.text_width(self.prefix);
+        self.suffix_width = self.text_width(self.suffix);
+        self.continuation_width = self.text_width(self.continuation);
+        self.placeholder_width = self.text_width(self.placeholder);
+    }
+
+    /// Sets the rules which determine line break opportunities.
+    /// The default mode is `BreakMode::Basic`, and `BreakMode::Uax14` makes
+    /// this instance follow the Unicode Line Breaking Algorithm (UAX #14).
+    /// `BreakMode::Code` is for source code, and breaks a line after commas,
+    /// operators and opening brackets but never inside of string literals.
+    ///
+    /// ```rust
+    ///     use linebreak::{BreakMode, LineIter};
+    ///
+    ///     let mut iter = LineIter::new("state-of-the-art design", 10);
+    ///     iter.set_break_mode(BreakMode::Uax14);
+    ///     assert_eq!(iter.next().unwrap(), "state-of-");
+    ///     assert_eq!(iter.next().unwrap(), "the-art");
+    ///     assert_eq!(iter.next().unwrap(), "design");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_break_mode(&mut self, mode: BreakMode) {
+        self.break_mode = mode;
+    }
+
+    /// Sets the strictness of the Japanese line break rules in JIS X 4051.
+    /// The default is `KinsokuLevel::Strict`, in which a line is not broken
+    /// before small kana and prolonged sound marks.
+    ///
+    /// ```rust
+    ///     use linebreak::{KinsokuLevel, LineIter};
+    ///
+    ///     let mut iter = LineIter::new("ジャズ ミュージック", 8);
+    ///     assert_eq!(iter.next().unwrap(), "ジャズ");
+    ///     assert_eq!(iter.next().unwrap(), "ミュー");
+    ///     assert_eq!(iter.next().unwrap(), "ジック");
+    ///     assert!(iter.next().is_none());
+    ///
+    ///     iter.init("ジャズ ミュージック");
+    ///     iter.set_kinsoku_level(KinsokuLevel::Normal);
+    ///     assert_eq!(iter.next().unwrap(), "ジャズ");
+    ///     assert_eq!(iter.next().unwrap(), "ミュージ");
+    ///     assert_eq!(iter.next().unwrap(), "ック");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_kinsoku_level(&mut self, level: KinsokuLevel) {
+        self.kinsoku_level = level;
+    }
+
+    /// Sets whether to let the Japanese comma and full stop (`、`, `。`, `，`
+    /// and `．`) hang past the end of a line (burasage), instead of moving them
+    /// to the next line with the preceding character.
+    /// If enabled, a line can be wider than the line width by the width of
+    /// the hanging character.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("あいうえお。かきくけこ", 10);
+    ///     assert_eq!(iter.next().unwrap(), "あいうえ");
+    ///     assert_eq!(iter.next().unwrap(), "お。かきく");
+    ///     assert_eq!(iter.next().unwrap(), "けこ");
+    ///     assert!(iter.next().is_none());
+    ///
+    ///     iter.init("あいうえお。かきくけこ");
+    ///     iter.set_burasage(true);
+    ///     assert_eq!(iter.next().unwrap(), "あいうえお。");
+    ///     assert_eq!(iter.next().unwrap(), "かきくけこ");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_burasage(&mut self, enabled: bool) {
+        self.burasage = enabled;
+    }
+
+    /// Sets the classes of the Latin punctuation which hang outside the text
+    /// block.
+    /// By default, no punctuation hangs.
+    ///
+    /// ```rust
+    ///     use linebreak::{HangingPunctuation, LineIter};
+    ///
+    ///     let text = "He said, \"Go on.\"";
+    ///     let mut iter = LineIter::new(text, 9);
+    ///     iter.set_indent("  ");
+    ///     iter.set_hanging_punctuation(HangingPunctuation::ALL);
+    ///     assert_eq!(iter.next().unwrap(), "  He said,");
+    ///     assert_eq!(iter.next().unwrap(), " \"Go on.\"");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_hanging_punctuation(&mut self, hanging: HangingPunctuation) {
+        self.hanging = hanging;
+    }
+
+    /// Adds a `NoBreakRule` which decides whether a line must not be broken at
+    /// a space between two words.
+    /// The built-in rules are provided by `NoBreakPair`.
+    ///
+    /// ```rust
+    ///     use linebreak::{LineIter, NoBreakPair};
+    ///
+    ///     let mut iter = LineIter::new("Byl jsem v Praze", 10);
+    ///     iter.add_no_break_rule(&NoBreakPair::SingleLetterWord);
+    ///     assert_eq!(iter.next().unwrap(), "Byl jsem");
+    ///     assert_eq!(iter.next().unwrap(), "v Praze");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn add_no_break_rule(&mut self, rule: &'a dyn NoBreakRule) {
+        self.no_break_rules.push(rule);
+    }
+
+    /// Adds a byte range of the text which must not be broken, such as an
+    /// inline code span or a placeholder.
+    /// The range is treated as an atomic token, and is broken only if it is
+    /// wider than the line.
+    /// The ranges added by this method are cleared by `init`.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let text = "run cargo test --all now";
+    ///     let mut iter = LineIter::new(text, 16);
+    ///     iter.add_protected_range(4..20);
+    ///     assert_eq!(iter.next().unwrap(), "run");
+    ///     assert_eq!(iter.next().unwrap(), "cargo test --all");
+    ///     assert_eq!(iter.next().unwrap(), "now");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn add_protected_range(&mut self, range: Range<usize>) {
+        self.protected.add(range);
+    }
+
+    /// Adds a pair of the delimiters which enclose a span of the text which
+    /// must not be broken, such as `` ` `` and `` ` ``, or `{` and `}`.
+    /// The span including the delimiters is treated as an atomic token, and
+    /// is broken only if it is wider than the line.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let text = "Hello, {user name}! Welcome.";
+    ///     let mut iter = LineIter::new(text, 12);
+    ///     iter.add_protected_delimiters("{", "}");
+    ///     assert_eq!(iter.next().unwrap(), "Hello,");
+    ///     assert_eq!(iter.next().unwrap(), "{user name}!");
+    ///     assert_eq!(iter.next().unwrap(), "Welcome.");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn add_protected_delimiters(&mut self, open: &'a str, close: &'a str) {
+        self.protected_delimiters.push((open, close));
+        self.protected.add_delimited(self.text, open, close);
+    }
+
+    /// Sets the maximum width which can be left unused at the end of a line to
+    /// break the line at a sentence boundary instead of the last line break
+    /// opportunity.
+    /// The default is 0, which disables this feature.
+    ///
+    /// A period after an abbreviation or an initial, such as `e.g.` and
+    /// `Dr.`, is not taken as the end of a sentence.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let text = "It rained. We stayed at home all day.";
+    ///
+    ///     let mut iter = LineIter::new(text, 16);
+    ///     assert_eq!(iter.next().unwrap(), "It rained. We");
+    ///     assert_eq!(iter.next().unwrap(), "stayed at home");
+    ///     assert_eq!(iter.next().unwrap(), "all day.");
+    ///     assert!(iter.next().is_none());
+    ///
+    ///     let mut iter = LineIter::new(text, 16);
+    ///     iter.set_sentence_slack(6);
+    ///     assert_eq!(iter.next().unwrap(), "It rained.");
+    ///     assert_eq!(iter.next().unwrap(), "We stayed at");
+    ///     assert_eq!(iter.next().unwrap(), "home all day.");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_sentence_slack(&mut self, slack: usize) {
+        self.sentence_slack = slack;
+    }
+
+    /// Sets whether to avoid leaving a single word alone on the last line of a
+    /// paragraph, which is called a widow.
+    /// If enabled, the last word of the previous line is moved down to the
+    /// last line when both words fit in it.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let text = "The quick brown fox jumps over the dog.";
+    ///
+    ///     let mut iter = LineIter::new(text, 34);
+    ///     assert_eq!(iter.next().unwrap(), "The quick brown fox jumps over the");
+    ///     assert_eq!(iter.next().unwrap(), "dog.");
+    ///     assert!(iter.next().is_none());
+    ///
+    ///     let mut iter = LineIter::new(text, 34);
+    ///     iter.set_widow_control(true);
+    ///     assert_eq!(iter.next().unwrap(), "The quick brown fox jumps over");
+    ///     assert_eq!(iter.next().unwrap(), "the dog.");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_widow_control(&mut self, enabled: bool) {
+        self.widow_control = enabled;
+    }
+
+    /// Adds the specified character to the set of the characters before which
+    /// a line must not be broken, such as closing brackets and small kana.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("あいうえおか〜", 6);
+    ///     assert_eq!(iter.next().unwrap(), "あいう");
+    ///     assert_eq!(iter.next().unwrap(), "えおか");
+    ///     assert_eq!(iter.next().unwrap(), "〜");
+    ///
+    ///     let mut iter = LineIter::new("あいうえおか〜", 6);
+    ///     iter.add_no_break_before('〜');
+    ///     assert_eq!(iter.next().unwrap(), "あいう");
+    ///     assert_eq!(iter.next().unwrap(), "えお");
+    ///     assert_eq!(iter.next().unwrap(), "か〜");
+    /// ```
+    pub fn add_no_break_before(&mut self, ch: char) {
+        self.kinsoku.set_no_break_before(ch, true);
+    }
+
+    /// Removes the specified character from the set of the characters before
+    /// which a line must not be broken.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("あいうえ、お", 8);
+    ///     assert_eq!(iter.next().unwrap(), "あいう");
+    ///     assert_eq!(iter.next().unwrap(), "え、お");
+    ///
+    ///     let mut iter = LineIter::new("あいうえ、お", 8);
+    ///     iter.remove_no_break_before('、');
+    ///     assert_eq!(iter.next().unwrap(), "あいうえ");
+    ///     assert_eq!(iter.next().unwrap(), "、お");
+    /// ```
+    pub fn remove_no_break_before(&mut self, ch: char) {
+        self.kinsoku.set_no_break_before(ch, false);
+    }
+
+    /// Adds the specified character to the set of the characters after which
+    /// a line must not be broken, such as opening brackets.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("price: $ 100", 8);
+    ///     assert_eq!(iter.next().unwrap(), "price: $");
+    ///     assert_eq!(iter.next().unwrap(), "100");
+    ///
+    ///     let mut iter = LineIter::new("price: $100", 8);
+    ///     iter.add_no_break_after('$');
+    ///     assert_eq!(iter.next().unwrap(), "price:");
+    ///     assert_eq!(iter.next().unwrap(), "$100");
+    /// ```
+    pub fn add_no_break_after(&mut self, ch: char) {
+        self.kinsoku.set_no_break_after(ch, true);
+    }
+
+    /// Removes the specified character from the set of the characters after
+    /// which a line must not be broken.
+    ///
+    /// In `BreakMode::Uax14`, the line break rules which depend on the
+    /// preceding characters are still applied to the following character.
+    pub fn remove_no_break_after(&mut self, ch: char) {
+        self.kinsoku.set_no_break_after(ch, false);
+    }
+
+    /// Adds a pair of the opening and the closing quotation marks, so that a
+    /// line is not broken after the opening mark nor before the closing mark.
+    ///
+    /// The curly quotation marks, such as `“ ”` and `‘ ’`, the guillemets and
+    /// the CJK corner brackets are recognized by default, and this method is
+    /// used for the other pairs.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("say ⸢hello⸣ world", 10);
+    ///     iter.add_quote_pair('⸢', '⸣');
+    ///     assert_eq!(iter.next().unwrap(), "say");
+    ///     assert_eq!(iter.next().unwrap(), "⸢hello⸣");
+    ///     assert_eq!(iter.next().unwrap(), "world");
+    /// ```
+    pub fn add_quote_pair(&mut self, open: char, close: char) {
+        self.kinsoku.set_no_break_after(open, true);
+        self.kinsoku.set_no_break_before(close, true);
+    }
+
+    /// Sets whether to break a line after a hyphen between letters or digits,
+    /// such as in "state-of-the-art".
+    /// This is disabled by default, because some words like file names should
+    /// not be broken at their hyphens.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("a state-of-the-art design", 12);
+    ///     iter.set_break_on_hyphen(true);
+    ///     assert_eq!(iter.next().unwrap(), "a state-of-");
+    ///     assert_eq!(iter.next().unwrap(), "the-art");
+    ///     assert_eq!(iter.next().unwrap(), "design");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_break_on_hyphen(&mut self, enabled: bool) {
+        self.break_on_hyphen = enabled;
+    }
+
+    /// Sets whether to pair the ASCII quotation marks `"` and `'`.
+    /// This is enabled by default.
+    /// If disabled, these marks are treated as ordinary characters around
+    /// which a line is not broken, so that unbalanced quotes in source code,
+    /// shell commands or CSV do not affect the following break positions.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("x=\"a b\" y='c", 10);
+    ///     iter.set_quote_aware(false);
+    ///     assert_eq!(iter.next().unwrap(), "x=\"a b\"");
+    ///     assert_eq!(iter.next().unwrap(), "y='c");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_quote_aware(&mut self, enabled: bool) {
+        self.quote_aware = enabled;
+    }
+
+    /// Sets whether to break a line between any Hangul characters like the
+    /// other CJK characters.
+    /// This is disabled by default, because Korean text is broken at spaces
+    /// like English.
+    /// This setting is effective only in `BreakMode::Basic`.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("한국어 텍스트", 11);
+    ///     assert_eq!(iter.next().unwrap(), "한국어");
+    ///     assert_eq!(iter.next().unwrap(), "텍스트");
+    ///     assert!(iter.next().is_none());
+    ///
+    ///     iter.init("한국어 텍스트");
+    ///     iter.set_break_hangul(true);
+    ///     assert_eq!(iter.next().unwrap(), "한국어 텍스");
+    ///     assert_eq!(iter.next().unwrap(), "트");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_break_hangul(&mut self, enabled: bool) {
+        self.break_hangul = enabled;
+    }
+
+    /// Sets how to break URLs and file paths.
+    /// By default, they are not recognized and are broken as the other words.
+    ///
+    /// ```rust
+    ///     use linebreak::{LineIter, UrlBreak};
+    ///
+    ///     let text = "See https://example.com/docs/index.html?lang=en for details.";
+    ///
+    ///     let mut iter = LineIter::new(text, 20);
+    ///     iter.set_url_break(UrlBreak::Keep);
+    ///     assert_eq!(iter.next().unwrap(), "See");
+    ///     assert_eq!(iter.next().unwrap(), "https://example.com/docs/index.html?lang=en");
+    ///     assert_eq!(iter.next().unwrap(), "for details.");
+    ///     assert!(iter.next().is_none());
+    ///
+    ///     let mut iter = LineIter::new(text, 20);
+    ///     iter.set_url_break(UrlBreak::AtDelimiters);
+    ///     assert_eq!(iter.next().unwrap(), "See");
+    ///     assert_eq!(iter.next().unwrap(), "https://example.com/");
+    ///     assert_eq!(iter.next().unwrap(), "docs/index.html?");
+    ///     assert_eq!(iter.next().unwrap(), "lang=en for details.");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_url_break(&mut self, url_break: UrlBreak) {
+        self.url_break = url_break;
+    }
+
+    /// Sets how to divide a word which has no line break opportunity within
+    /// the line width, when it is broken by `OverflowPolicy::ForceBreak`.
+    /// The default policy is `LongWordPolicy::BreakAnywhere`.
+    pub fn set_long_word_policy(&mut self, policy: LongWordPolicy) {
+        self.long_word_policy = policy;
+    }
+
+    /// Sets how to treat a content which cannot be broken and is wider than a
+    /// line, such as a long word, a URL kept by `UrlBreak::Keep` and a
+    /// character wider than the line.
+    /// The default policy is `OverflowPolicy::ForceBreak`.
+    pub fn set_overflow_policy(&mut self, policy: OverflowPolicy) {
+        self.overflow_policy = policy;
+    }
+
+    /// Returns the error which stopped this iterator with
+    /// `OverflowPolicy::Error`, or `None` if no error occurred.
+    pub fn overflow_error(&self) -> Option<OverflowError> {
+        self.overflow_error
+    }
+
+    /// Sets whether to keep the characters which have no width and are
+    /// attached to the previous character, such as combining marks, variation
+    /// selectors and ZERO WIDTH JOINER, in the output lines.
+    /// This is enabled by default.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let text = "cafe\u{0301} 👩\u{200D}💻";
+    ///
+    ///     let mut iter = LineIter::new(text, 10);
+    ///     assert_eq!(iter.next().unwrap(), "cafe\u{0301} 👩\u{200D}💻");
+    ///
+    ///     let mut iter = LineIter::new(text, 10);
+    ///     iter.set_keep_zero_width(false);
+    ///     assert_eq!(iter.next().unwrap(), "cafe 👩💻");
+    /// ```
+    pub fn set_keep_zero_width(&mut self, enabled: bool) {
+        self.keep_zero_width = enabled;
+    }
+
+    /// Sets whether to keep the spaces at the start of each line of the source
+    /// text, such as the indentation of code blocks.
+    /// The spaces at the start of the lines which are wrapped are skipped
+    /// regardless of this setting.
+    /// To keep tabs, set a tab width with `Options::tab_width`.
+    /// This is disabled by default.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let text = "code:\n    let x = 1;";
+    ///
+    ///     let mut iter = LineIter::new(text, 12);
+    ///     assert_eq!(iter.next().unwrap(), "code:");
+    ///     assert_eq!(iter.next().unwrap(), "let x = 1;");
+    ///
+    ///     let mut iter = LineIter::new(text, 12);
+    ///     iter.set_preserve_leading_whitespace(true);
+    ///     assert_eq!(iter.next().unwrap(), "code:");
+    ///     assert_eq!(iter.next().unwrap(), "    let x =");
+    ///     assert_eq!(iter.next().unwrap(), "1;");
+    /// ```
+    pub fn set_preserve_leading_whitespace(&mut self, enabled: bool) {
+        self.preserve_leading_whitespace = enabled;
+    }
+
+    /// Sets whether to indent the wrapped lines of each source line with the
+    /// leading whitespace of the source line, so that re-flowing already
+    /// indented text keeps its structure.
+    /// The leading whitespace of the source line itself is kept.
+    /// In the paragraph mode, the leading whitespace of the first line of each
+    /// paragraph is used.
+    /// The detected indentation is applied only if it is at most a half of the
+    /// line width.
+    /// This is disabled by default.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let text = "notes:\n  - abc def ghi\n  - jkl";
+    ///
+    ///     let mut iter = LineIter::new(text, 10);
+    ///     iter.set_auto_indent(true);
+    ///     assert_eq!(iter.next().unwrap(), "notes:");
+    ///     assert_eq!(iter.next().unwrap(), "  - abc");
+    ///     assert_eq!(iter.next().unwrap(), "  def ghi");
+    ///     assert_eq!(iter.next().unwrap(), "  - jkl");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_auto_indent(&mut self, enabled: bool) {
+        self.auto_indent = enabled;
+    }
+
+    /// Sets the number of the spaces which are added to the indentation
+    /// detected by the auto indent mode for the wrapped lines.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let text = "notes:\n  - abc def ghi\n  - jkl";
+    ///
+    ///     let mut iter = LineIter::new(text, 10);
+    ///     iter.set_auto_indent(true);
+    ///     iter.set_auto_indent_hang(2);
+    ///     assert_eq!(iter.next().unwrap(), "notes:");
+    ///     assert_eq!(iter.next().unwrap(), "  - abc");
+    ///     assert_eq!(iter.next().unwrap(), "    def");
+    ///     assert_eq!(iter.next().unwrap(), "    ghi");
+    ///     assert_eq!(iter.next().unwrap(), "  - jkl");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_auto_indent_hang(&mut self, hang: usize) {
+        self.auto_indent_hang = hang;
+    }
+
+    /// Sets whether to recognize bullet and numbered list markers, such as
+    /// `- `, `* `, `1. ` and `a) `, at the start of each source line or each
+    /// paragraph in the paragraph mode, and indent the wrapped lines of a list
+    /// item with the width of its marker.
+    /// This mode also works as the auto indent mode.
+    /// This is disabled by default.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let text = "1. abc def ghi\n  - jkl mno pqr";
+    ///
+    ///     let mut iter = LineIter::new(text, 12);
+    ///     iter.set_list_items(true);
+    ///     assert_eq!(iter.next().unwrap(), "1. abc def");
+    ///     assert_eq!(iter.next().unwrap(), "   ghi");
+    ///     assert_eq!(iter.next().unwrap(), "  - jkl mno");
+    ///     assert_eq!(iter.next().unwrap(), "    pqr");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_list_items(&mut self, enabled: bool) {
+        self.list_items = enabled;
+    }
+
+    /// Sets whether to support bidirectional texts, such as Hebrew and Arabic.
+    ///
+    /// In this mode, the direction of each source line or paragraph is
+    /// determined by its first strong character, and each output line is
+    /// reordered from the logical order to the display order with a simplified
+    /// Unicode Bidirectional Algorithm (UAX #9) which has no explicit
+    /// embeddings.
+    /// A run of the opposite direction to the paragraph is not broken, if it
+    /// fits in a line.
+    /// This is disabled by default, and is available with the `bidi` feature.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let text = "\u{05D0}\u{05D1}\u{05D2} \u{05D3}\u{05D4} abc def \u{05D6}\u{05D7}";
+    ///
+    ///     let mut iter = LineIter::new(text, 10);
+    ///     iter.set_bidi(true);
+    ///     assert_eq!(iter.next().unwrap(), "\u{05D4}\u{05D3} \u{05D2}\u{05D1}\u{05D0}");
+    ///     assert_eq!(iter.next().unwrap(), "\u{05D7}\u{05D6} abc def");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    #[cfg(feature = "bidi")]
+    pub fn set_bidi(&mut self, enabled: bool) {
+        self.bidi = enabled;
+    }
+
+    /// Sets how to treat form feeds (U+000C), which separate pages in texts
+    /// such as the outputs of man pages.
+    /// The default is `FormFeed::Normal`.
+    ///
+    /// ```rust
+    ///     use linebreak::{FormFeed, LineIter};
+    ///
+    ///     let text = "abc\u{000C}def\n\u{000C}\nghi";
+    ///
+    ///     let mut iter = LineIter::new(text, 10);
+    ///     iter.set_form_feed(FormFeed::Break);
+    ///     assert_eq!(iter.next().unwrap(), "abc");
+    ///     assert_eq!(iter.next().unwrap(), "def");
+    ///     assert_eq!(iter.next().unwrap(), "ghi");
+    ///     assert!(iter.next().is_none());
+    ///
+    ///     let mut iter = LineIter::new(text, 10);
+    ///     iter.set_form_feed(FormFeed::Emit);
+    ///     assert_eq!(iter.next().unwrap(), "abc");
+    ///     assert_eq!(iter.next().unwrap(), "\u{000C}");
+    ///     assert_eq!(iter.next().unwrap(), "def");
+    ///     assert_eq!(iter.next().unwrap(), "\u{000C}");
+    ///     assert_eq!(iter.next().unwrap(), "ghi");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_form_feed(&mut self, form_feed: FormFeed) {
+        self.form_feed = form_feed;
+    }
+
+    /// Sets how to treat ideographic spaces (U+3000).
+    /// The default is `IdeographicSpace::Space`, with which an ideographic
+    /// space is trimmed at the start and the end of a line.
+    ///
+    /// ```rust
+    ///     use linebreak::{IdeographicSpace, LineIter};
+    ///
+    ///     let text = "\u{3000}吾輩は猫である。\u{3000}名前はまだ無い。";
+    ///
+    ///     let mut iter = LineIter::new(text, 14);
+    ///     assert_eq!(iter.next().unwrap(), "吾輩は猫であ");
+    ///     assert_eq!(iter.next().unwrap(), "る。\u{3000}名前はま");
+    ///     assert_eq!(iter.next().unwrap(), "だ無い。");
+    ///     assert!(iter.next().is_none());
+    ///
+    ///     let mut iter = LineIter::new(text, 14);
+    ///     iter.set_ideographic_space(IdeographicSpace::Printable);
+    ///     assert_eq!(iter.next().unwrap(), "\u{3000}吾輩は猫であ");
+    ///     assert_eq!(iter.next().unwrap(), "る。\u{3000}名前はま");
+    ///     assert_eq!(iter.next().unwrap(), "だ無い。");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_ideographic_space(&mut self, ideographic_space: IdeographicSpace) {
+        self.ideographic_space = ideographic_space;
+        self.buffer
+            .set_keeps_ideographic_space(ideographic_space == IdeographicSpace::Printable);
+    }
+
+    /// Sets whether to treat each of CR and LF in a CRLF sequence as a line
+    /// break.
+    /// This is disabled by default, so that CRLF, a lone CR and a lone LF are
+    /// all treated as one line break.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("abc\r\ndef", 10);
+    ///     assert_eq!(iter.next().unwrap(), "abc");
+    ///     assert_eq!(iter.next().unwrap(), "def");
+    ///     assert!(iter.next().is_none());
+    ///
+    ///     let mut iter = LineIter::new("abc\r\ndef", 10);
+    ///     iter.set_strict_line_breaks(true);
+    ///     assert_eq!(iter.next().unwrap(), "abc");
+    ///     assert_eq!(iter.next().unwrap(), "");
+    ///     assert_eq!(iter.next().unwrap(), "def");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_strict_line_breaks(&mut self, enabled: bool) {
+        self.strict_line_breaks = enabled;
+    }
+
+    /// Sets how to output the non-printable characters, such as control
+    /// characters.
+    /// The default policy is `NonPrintPolicy::Strip`.
+    ///
+    /// ```rust
+    ///     use linebreak::{LineIter, NonPrintPolicy};
+    ///
+    ///     let text = "abc\u{0002}def";
+    ///
+    ///     let mut iter = LineIter::new(text, 10);
+    ///     assert_eq!(iter.next().unwrap(), "abcdef");
+    ///
+    ///     let mut iter = LineIter::new(text, 10);
+    ///     iter.set_non_print_policy(NonPrintPolicy::Keep);
+    ///     assert_eq!(iter.next().unwrap(), "abc\u{0002}def");
+    ///
+    ///     let mut iter = LineIter::new(text, 10);
+    ///     iter.set_non_print_policy(NonPrintPolicy::EscapeCaret);
+    ///     assert_eq!(iter.next().unwrap(), "abc^Bdef");
+    ///
+    ///     let mut iter = LineIter::new("abc \u{0002} def", 12);
+    ///     iter.set_non_print_policy(NonPrintPolicy::EscapeUnicode);
+    ///     assert_eq!(iter.next().unwrap(), "abc \\u{0002}");
+    ///     assert_eq!(iter.next().unwrap(), "def");
+    /// ```
+    pub fn set_non_print_policy(&mut self, policy: NonPrintPolicy) {
+        self.non_print = policy;
+    }
+
+    /// Sets how to treat the ANSI escape sequences, such as the SGR sequences
+    /// for colors.
+    /// The default policy is `AnsiPolicy::Ignore`.
+    ///
+    /// ```rust
+    ///     use linebreak::{AnsiPolicy, LineIter};
+    ///
+    ///     let text = "\u{1b}[1mbold and \u{1b}[32mgreen\u{1b}[0m";
+    ///
+    ///     let mut iter = LineIter::new(text, 9);
+    ///     iter.set_ansi_policy(AnsiPolicy::Keep);
+    ///     assert_eq!(iter.next().unwrap(), "\u{1b}[1mbold and");
+    ///     assert_eq!(iter.next().unwrap(), "\u{1b}[32mgreen\u{1b}[0m");
+    ///     assert!(iter.next().is_none());
+    ///
+    ///     iter.set_ansi_policy(AnsiPolicy::Reapply);
+    ///     iter.init(text);
+    ///     assert_eq!(iter.next().unwrap(), "\u{1b}[1mbold and\u{1b}[0m");
+    ///     assert_eq!(iter.next().unwrap(), "\u{1b}[1m\u{1b}[32mgreen\u{1b}[0m");
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn set_ansi_policy(&mut self, policy: AnsiPolicy) {
+        self.ansi_policy = policy;
+        self.scan_escapes();
+    }
+
+    // Finds the escape sequences in the text, which are kept as they are and
+    // have width 0 unless the policy is `AnsiPolicy::Ignore`.
+    fn scan_escapes(&mut self) {
+        self.ansi_spans.clear();
+        if self.ansi_policy == AnsiPolicy::Ignore {
+            return;
+        }
+        let mut from = 0;
+        while let Some(i) = find_escape(&self.text[from..]) {
+            let start = from + i;
+            from = start + escape_len(&self.text[start..]);
+            self.ansi_spans.add(start..from);
+        }
+    }
+
+    // Checks whether the specified byte position is in an escape sequence.
+    fn is_in_escape(&self, pos: usize) -> bool {
+        self.ansi_spans.find(pos).is_some()
+    }
+
+    // Checks whether the buffer holds only escape sequences, which are not
+    // visible at the start of a line.
+    fn has_only_escapes(&self) -> bool {
+        (0..self.buffer.len())
+            .all(|i| matches!(self.buffer.pos(i), Some(p) if self.is_in_escape(p)))
+    }
+
+    // Resets the SGR attributes and closes the hyperlink which are active at
+    // the end of the specified line, and re-applies the attributes and the
+    // hyperlink which are active at its start.
+    fn reapply_ansi(&mut self, line: Cow<'a, str>) -> Cow<'a, str> {
+        if line.is_empty() {
+            return line;
+        }
+        let was_active = self.ansi_state.is_active();
+        let mut reapplied = String::new();
+        self.ansi_state.push_start(&mut reapplied);
+        self.ansi_state.update(&line);
+        if !was_active && !self.ansi_state.is_active() {
+            return line;
+        }
+        reapplied.push_str(&line);
+        self.ansi_state.push_end(&mut reapplied);
+        Cow::Owned(reapplied)
+    }
+
+    /// Advances this iterator by the specified number of lines without building
+    /// the line strings, and returns the number of the lines actually skipped,
+    /// which is less than the specified number if the text runs out.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("abc def ghi jkl mno", 3);
+    ///     assert_eq!(iter.skip_lines(3), 3);
+    ///     assert_eq!(iter.next().unwrap(), "jkl");
+    ///     assert_eq!(iter.skip_lines(3), 1);
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn skip_lines(&mut self, n: usize) -> usize {
+        let mut count = 0;
+        while count < n && self.skip_line() {
+            count += 1;
+        }
+        count
+    }
+
+    /// Returns whether this iterator has output all lines.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("abc def", 4);
+    ///     assert_eq!(iter.next().unwrap(), "abc");
+    ///     assert!(!iter.is_exhausted());
+    ///     assert_eq!(iter.next().unwrap(), "def");
+    ///     assert!(iter.is_exhausted());
+    /// ```
+    pub fn is_exhausted(&self) -> bool {
+        match &self.peeked {
+            Some(peeked) => peeked.line.is_none(),
+            None => !self.has_next,
+        }
+    }
+
+    /// Returns the number of the bytes at the beginning of the text which are
+    /// already output as lines, including the whitespace and the line breaks
+    /// after them.
+    /// The rest of the text starts at this offset.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let text = "abc def\nghi";
+    ///     let mut iter = LineIter::new(text, 4);
+    ///     assert_eq!(iter.consumed_bytes(), 0);
+    ///     assert_eq!(iter.next().unwrap(), "abc");
+    ///     assert_eq!(&text[iter.consumed_bytes()..], "def\nghi");
+    ///     assert_eq!(iter.next().unwrap(), "def");
+    ///     assert_eq!(&text[iter.consumed_bytes()..], "ghi");
+    ///     assert_eq!(iter.next().unwrap(), "ghi");
+    ///     assert_eq!(iter.consumed_bytes(), text.len());
+    /// ```
+    pub fn consumed_bytes(&self) -> usize {
+        if let Some(peeked) = &self.peeked {
+            return peeked.consumed;
+        }
+        match self.buffer.pos(0) {
+            Some(pos) => pos,
+            None => self.text.len() - self.scanner.as_str().len(),
+        }
+    }
+
+    /// Re-initializes with an argument string for reusing this instance.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("abcdefghijklmn", 10);
+    ///     assert_eq!(iter.next().unwrap(), "abcdefghij");
+    ///     assert_eq!(iter.next().unwrap(), "klmn");
+    ///     assert_eq!(iter.next().is_none(), true);
+    ///
+    ///     iter.init("opqrstuvwxyz");
+    ///     assert_eq!(iter.next().unwrap(), "opqrstuvwx");
+    ///     assert_eq!(iter.next().unwrap(), "yz");
+    ///     assert_eq!(iter.next().is_none(), true);
+    /// ```
+    pub fn init(&mut self, text: &'a str) {
+        self.text = text;
+        self.scanner = text.chars();
+        self.pos = 0;
+        self.range = 0..0;
+        self.buffer.clear();
+        self.width[0] = 0;
+        self.width[1] = 0;
+        self.lbo_pos = 0;
+        self.open_quot = 0;
+        self.open_apos = 0;
+        self.uax14.reset();
+        self.code.reset();
+        self.ri_odd = false;
+        self.after_joiner = false;
+        self.sentence_start = None;
+        self.url.reset();
+        self.tab_spaces = 0;
+        self.escaped.clear();
+        self.is_first_line = true;
+        self.is_blank_line = true;
+        self.line_no = 0;
+        self.pending_line = None;
+        self.peeked = None;
+        self.overflow_error = None;
+        self.auto_indent_range = 0..0;
+        self.auto_indent_width = 0;
+        self.auto_indent_pad = 0;
+        self.line_start = 0;
+        #[cfg(feature = "bidi")]
+        {
+            self.bidi_run = 0;
+        }
+        self.protected.clear();
+        for (open, close) in &self.protected_delimiters {
+            self.protected.add_delimited(text, open, close);
+        }
+        self.ansi_state.clear();
+        self.scan_escapes();
+        self.has_next = true;
+    }
+
+    /// Returns an Option of a `Line` which holds a line string and its
+    /// metadata, such as the display width and whether the line is broken
+    /// forcely.
+    pub fn next_line(&mut self) -> Option<Line<'a>> {
+        let text = self.next()?;
+        Some(Line {
+            width: self.text_width(&text),
+            text,
+            range: self.range.clone(),
+            indent: self.line_indent.clone(),
+            is_forced: self.is_forced,
+            is_hard_break: self.is_hard_break,
+        })
+    }
+
+    /// Returns a reference to the next line string without consuming it.
+    /// The next call of `next` returns the same line, even if the settings are
+    /// changed between them.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("abc def ghi", 7);
+    ///     assert_eq!(iter.peek().unwrap(), "abc def");
+    ///     assert_eq!(iter.peek().unwrap(), "abc def");
+    ///     assert_eq!(iter.next().unwrap(), "abc def");
+    ///     assert_eq!(iter.peek().unwrap(), "ghi");
+    ///     assert_eq!(iter.next().unwrap(), "ghi");
+    ///     assert!(iter.peek().is_none());
+    ///     assert!(iter.next().is_none());
+    /// ```
+    pub fn peek(&mut self) -> Option<&Cow<'a, str>> {
+        if self.peeked.is_none() {
+            let consumed = self.consumed_bytes();
+            let line = self.read_line();
+            self.peeked = Some(Peeked {
+                line,
+                range: self.range.clone(),
+                indent: self.line_indent.clone(),
+                is_forced: self.is_forced,
+                is_hard_break: self.is_hard_break,
+                consumed,
+            });
+        }
+        self.peeked.as_ref().and_then(|peeked| peeked.line.as_ref())
+    }
+
+    /// Returns an Option of a line string and the byte range in the original
+    /// text which the line covers.
+    /// The range does not include the indentation, the prefix, the suffix, the
+    /// padding spaces and the hyphen added by a `Hyphenator`.
+    /// The range of an empty line is an empty range at its line break.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let text = "abc def\n\nghi";
+    ///     let mut iter = LineIter::new(text, 7);
+    ///     iter.set_indent("> ");
+    ///     assert_eq!(iter.next_with_range(), Some(("> abc".into(), 0..3)));
+    ///     assert_eq!(iter.next_with_range(), Some(("> def".into(), 4..7)));
+    ///     assert_eq!(iter.next_with_range(), Some(("".into(), 8..8)));
+    ///     assert_eq!(iter.next_with_range(), Some(("> ghi".into(), 9..12)));
+    ///     assert_eq!(iter.next_with_range(), None);
+    ///     assert_eq!(&text[4..7], "def");
+    /// ```
+    pub fn next_with_range(&mut self) -> Option<(Cow<'a, str>, Range<usize>)> {
+        let line = self.next()?;
+        Some((line, self.range.clone()))
+    }
+
+    /// Writes the next line string into the specified buffer, and returns false
+    /// if there is no more line.
+    /// The buffer is cleared before writing, so that the caller can reuse it
+    /// for all lines without allocating a string for each line.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let mut iter = LineIter::new("abc def ghi", 7);
+    ///     iter.set_indent("> ");
+    ///     let mut buf = String::new();
+    ///     assert!(iter.next_into(&mut buf));
+    ///     assert_eq!(buf, "> abc");
+    ///     assert!(iter.next_into(&mut buf));
+    ///     assert_eq!(buf, "> def");
+    ///     assert!(iter.next_into(&mut buf));
+    ///     assert_eq!(buf, "> ghi");
+    ///     assert!(!iter.next_into(&mut buf));
+    ///     assert_eq!(buf, "");
+    /// ```
+    pub fn next_into(&mut self, buf: &mut String) -> bool {
+        buf.clear();
+        if self.peeked.is_some() {
+            return match self.next() {
+                Some(line) => {
+                    buf.push_str(&line);
+                    true
+                }
+                None => false,
+            };
+        }
+        match self.read_raw_line() {
+            Some((line, layout)) if layout.is_decorated => {
+                self.decorate_into(&line, &layout, buf);
+                true
+            }
+            Some((line, _)) => {
+                buf.push_str(&line);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Checks whether the line break just read is inside a paragraph, and if so,
+    // skips the leading whitespaces of the next line.
+    fn skip_soft_line_break(&mut self) -> bool {
+        if self.is_blank_line {
+            return false;
+        }
+        let mut scanner = self.scanner.clone();
+        loop {
+            match scanner.next() {
+                Some('\n' | '\u{2028}' | '\u{2029}') | None => return false,
+                Some(ch) if ch.is_whitespace() => continue,
+                Some(_) => break,
+            }
+        }
+        while let Some(ch) = self.scanner.clone().next() {
+            if !ch.is_whitespace() || ch == '\u{2028}' || ch == '\u{2029}' {
+                break;
+            }
+            self.scanner.next();
+        }
+        true
+    }
+
+    // Skips a line feed, a carriage return or a pair of them which comes next,
+    // unless it is a soft line break in paragraph mode, which is a space.
+    fn skip_hard_line_break(&mut self) -> bool {
+        if self.tab_spaces > 0 || !self.escaped.is_empty() {
+            return false;
+        }
+        let rest = self.scanner.as_str();
+        let len = if rest.starts_with("\r\n") && !self.strict_line_breaks {
+            2
+        } else if rest.starts_with('\n') || rest.starts_with('\r') {
+            1
+        } else {
+            return false;
+        };
+        if self.paragraph_mode && rest[..len].ends_with('\n') {
+            for ch in rest[len..].chars() {
+                match ch {
+                    '\n' | '\u{2028}' | '\u{2029}' => break,
+                    ch if ch.is_whitespace() => continue,
+                    _ => return false,
+                }
+            }
+        }
+        self.scanner.nth(len - 1);
+        true
+    }
+
+    // Skips a line break which follows a form feed, so that the form feed on
+    // its own line does not produce an extra blank line.
+    fn skip_line_break(&mut self) {
+        let rest = self.scanner.as_str();
+        if rest.starts_with("\r\n") {
+            self.scanner.nth(1);
+        } else if rest.starts_with('\n') {
+            self.scanner.next();
+        }
+    }
+
+    fn char_width(&self, ch: char) -> usize {
+        if let Some(width) = self.width_overrides.get(&ch) {
+            return *width;
+        }
+        match self.width_provider {
+            Some(provider) => provider.char_width(ch),
+            // an ideographic space is put on a line as a fullwidth character.
+            None if ch == '\u{3000}' => 2,
+            None => char_width_with(ch, self.ambiguous_width),
+        }
+    }
+
+    fn char_width_after(&self, prev: Option<char>, ch: char) -> usize {
+        if let Some(width) = self.width_overrides.get(&ch) {
+            return *width;
+        }
+        match self.width_provider {
+            Some(provider) => provider.char_width(ch),
+            None if ch == '\u{3000}' => 2,
+            None => char_width_after(prev, ch, self.ambiguous_width),
+        }
+    }
+
+    pub(crate) fn text_width(&self, text: &str) -> usize {
+        if self.ansi_policy != AnsiPolicy::Ignore {
+            if let Cow::Owned(stripped) = strip_ansi(text) {
+                return self.text_width(&stripped);
+            }
+        }
+        if !self.width_overrides.is_empty()
+            && text.chars().any(|c| self.width_overrides.contains_key(&c))
+        {
+            let mut prev = None;
+            return text
+                .chars()
+                .map(|ch| {
+                    let width = self.char_width_after(prev, ch);
+                    prev = Some(ch);
+                    width
+                })
+                .sum();
+        }
+        match self.width_provider {
+            Some(provider) => provider.text_width(text),
+            None => text_width_with(text, self.ambiguous_width),
+        }
+    }
+
+    // Checks whether there are characters which are not read yet.
+    fn has_rest(&self) -> bool {
+        !self.scanner.as_str().is_empty() || !self.escaped.is_empty()
+    }
+
+    // Checks whether the next character is at the beginning of a word.
+    fn is_word_start(&self) -> bool {
+        match self.buffer.get(self.buffer.len().wrapping_sub(1)) {
+            Some(c) => c.is_whitespace() || matches!(c, '(' | '[' | '{' | '<' | '"' | '\''),
+            None => true,
+        }
+    }
+
+    // Checks whether the hyphen just read is between letters or digits.
+    fn is_hyphen_in_word(&self) -> bool {
+        let prev = self.buffer.get(self.buffer.len().wrapping_sub(1));
+        let next = self.scanner.clone().next();
+        matches!(prev, Some(c) if c.is_alphanumeric())
+            && matches!(next, Some(c) if c.is_alphanumeric())
+    }
+
+    // A punctuation can hang past the end of a line only if the next character
+    // can be put at the beginning of the next line.
+    fn can_hang(&self, ch: char, state: &LboState) -> bool {
+        let hangs = (self.burasage && is_hanging_punctuation(ch)) || self.hanging.hangs_at_end(ch);
+        if !hangs {
+            return false;
+        }
+        let Some(next) = self.scanner.clone().next() else {
+            return true;
+        };
+        let mut state = LboState {
+            lbo_type: LboType::Never,
+            lbo_prev: LboType::Never,
+            open_quot: state.open_quot,
+            open_apos: state.open_apos,
+        };
+        line_break_opportunity(next, &mut state);
+        self.kinsoku_level.apply(next, &mut state);
+        self.kinsoku.apply(next, &mut state);
+        state.lbo_type != LboType::After
+    }
+
+    // Returns the width by which an opening quote at the beginning of the next
+    // line hangs into the trailing spaces of the specified indentation.
+    fn left_hang_width(&self, indent: &str) -> usize {
+        if !self.hanging.opening_quotes {
+            return 0;
+        }
+        let first = match self.buffer.get(0) {
+            Some(ch) => ch,
+            None if self.tab_spaces == 0 && self.escaped.is_empty() => {
+                match self.scanner.clone().next() {
+                    Some(ch) => ch,
+                    None => return 0,
+                }
+            }
+            None => return 0,
+        };
+        if !self.hanging.hangs_at_start(first) {
+            return 0;
+        }
+        let w = self.char_width(first);
+        let spaces = indent.len() - indent.trim_end_matches(' ').len();
+        if w > spaces {
+            return 0;
+        }
+        w
+    }
+
+    // Returns the start of the last word in the buffer.
+    fn last_word_start(&self) -> usize {
+        let mut start = self.buffer.len();
+        while let Some(c) = self.buffer.get(start.wrapping_sub(1)) {
+            if c.is_whitespace() {
+                break;
+            }
+            start -= 1;
+        }
+        start
+    }
+
+    // Cancels the line break opportunities after the specified position in the
+    // buffer, so that the characters after it are kept together.
+    fn cancel_lbo_after(&mut self, start: usize) {
+        if self.lbo_pos > start {
+            let w: usize = (start..self.lbo_pos)
+                .filter_map(|i| self.buffer.get(i))
+                .map(|c| self.char_width(c))
+                .sum();
+            self.width[0] = self.width[0].saturating_sub(w);
+            self.width[1] += w;
+            self.lbo_pos = start;
+        }
+    }
+
+    // Checks whether the buffer ends with the end of a sentence, such as a
+    // period followed by spaces or an ideographic full stop, and the specified
+    // character can start the next sentence.
+    fn is_sentence_start(&self, ch: char) -> bool {
+        let mut end = self.buffer.len();
+        while let Some(c) = self.buffer.get(end.wrapping_sub(1)) {
+            if !c.is_whitespace() {
+                break;
+            }
+            end -= 1;
+        }
+        let spaced = end < self.buffer.len();
+        let mut i = end;
+        while let Some(c) = self.buffer.get(i.wrapping_sub(1)) {
+            if !matches!(
+                c,
+                ')' | ']' | '"' | '\'' | '”' | '’' | '»' | '）' | '」' | '』' | '】'
+            ) {
+                break;
+            }
+            i -= 1;
+        }
+        match self.buffer.get(i.wrapping_sub(1)) {
+            Some('。' | '．' | '！' | '？') => true,
+            Some('!' | '?') => spaced && !ch.is_lowercase(),
+            Some('.') if spaced && !ch.is_lowercase() => {
+                let mut start = i;
+                while let Some(c) = self.buffer.get(start.wrapping_sub(1)) {
+                    if c.is_whitespace() {
+                        break;
+                    }
+                    start -= 1;
+                }
+                let word: String = (start..i).filter_map(|k| self.buffer.get(k)).collect();
+                !is_abbreviation(&word)
+            }
+            _ => false,
+        }
+    }
+
+    // Returns the position in the buffer at which the last sentence starts, if
+    // the line before it leaves no more unused width than the slack and the
+    // rest of the buffer and the specified character fit in the next line.
+    fn sentence_break_pos(&self, lbo_pos: usize, ch_width: usize, limit: usize) -> Option<usize> {
+        let start = self.sentence_start?;
+        let n = self.buffer.len();
+        let pos = (1..lbo_pos.min(n)).find(|&i| self.buffer.pos(i) == Some(start))?;
+        let mut end = pos;
+        while matches!(self.buffer.get(end.wrapping_sub(1)), Some(c) if c.is_whitespace()) {
+            end -= 1;
+        }
+        if self.buffer_width(0..end) + self.sentence_slack < limit
+            || self.buffer_width(pos..n) + ch_width > limit
+        {
+            return None;
+        }
+        Some(pos)
+    }
+
+    // Returns the start of the last word before the line break opportunity in
+    // the buffer, if the rest of the paragraph after the opportunity is a
+    // single word and it fits in the next line together with that word.
+    fn widow_break_pos(
+        &self,
+        lbo_pos: usize,
+        ch: char,
+        ch_width: usize,
+        limit: usize,
+    ) -> Option<usize> {
+        if self.tab_spaces > 0 || !self.escaped.is_empty() {
+            return None;
+        }
+        let rest = self.scanner.as_str();
+        let (tail, after) = match rest.find('\n') {
+            Some(i) => (&rest[..i], &rest[i + 1..]),
+            None => (rest, ""),
+        };
+        // a soft line break in paragraph mode does not end the paragraph.
+        if self.paragraph_mode && !after.lines().next().map_or(true, |s| s.trim().is_empty()) {
+            return None;
+        }
+        let n = self.buffer.len();
+        let head = lbo_pos.min(n)..n;
+        let tail = match ch.is_whitespace() {
+            true => tail.trim(),
+            false => tail.trim_end(),
+        };
+        if tail.contains(char::is_whitespace)
+            || head
+                .clone()
+                .any(|i| matches!(self.buffer.get(i), Some(c) if c.is_whitespace()))
+            || (ch.is_whitespace() && head.is_empty() == tail.is_empty())
+        {
+            return None;
+        }
+
+        let mut end = lbo_pos.min(n);
+        while matches!(self.buffer.get(end.wrapping_sub(1)), Some(c) if c.is_whitespace()) {
+            end -= 1;
+        }
+        let mut start = end;
+        while matches!(self.buffer.get(start.wrapping_sub(1)), Some(c) if !c.is_whitespace()) {
+            start -= 1;
+        }
+        if (0..start).all(|i| matches!(self.buffer.get(i), Some(c) if c.is_whitespace())) {
+            return None;
+        }
+        if self.buffer_width(start..n) + ch_width + self.text_width(tail) > limit {
+            return None;
+        }
+        Some(start)
+    }
+
+    // Returns the line which consists of the characters before the specified
+    // position in the buffer and keeps the rest in it, then puts the specified
+    // character after them.
+    fn break_before(
+        &mut self,
+        pos: usize,
+        ch: char,
+        ch_width: usize,
+        state: &LboState,
+    ) -> Cow<'a, str> {
+        let line = self.line_until(pos);
+        self.buffer.cr(pos);
+        self.lbo_pos = self.lbo_pos.saturating_sub(pos);
+        self.width[0] = self.buffer_width(0..self.lbo_pos);
+        self.width[1] = self.buffer_width(self.lbo_pos..self.buffer.len());
+        self.put_char(ch, ch_width, state);
+        self.open_quot = state.open_quot;
+        self.open_apos = state.open_apos;
+        self.has_next = true;
+        line
+    }
+
+    // Returns the width of the characters in the specified range of the buffer.
+    fn buffer_width(&self, range: Range<usize>) -> usize {
+        range
+            .filter(|i| !matches!(self.buffer.pos(*i), Some(p) if self.is_in_escape(p)))
+            .filter_map(|i| self.buffer.get(i))
+            .map(|c| self.char_width(c))
+            .sum()
+    }
+
+    // Returns the start of the word before the current space in the buffer, if
+    // a rule keeps the words before and after the space together.
+    fn glued_word_start(&self) -> Option<usize> {
+        let n = self.buffer.len();
+        let start = self.last_word_start();
+        let after = self.scanner.as_str().split(char::is_whitespace).next();
+        let after = match after {
+            Some(s) if start < n && !s.is_empty() => s,
+            _ => return None,
+        };
+        let before: String = (start..n).filter_map(|i| self.buffer.get(i)).collect();
+        self.no_break_rules
+            .iter()
+            .any(|rule| rule.keeps_together(&before, after))
+            .then_some(start)
+    }
+
+    // An apostrophe between letters, such as in "don't", is a part of a word
+    // and does not open nor close a quotation.
+    fn is_apostrophe_in_word(&self) -> bool {
+        let prev = self.buffer.get(self.buffer.len().wrapping_sub(1));
+        let next = self.scanner.clone().next();
+        matches!(prev, Some(c) if c.is_alphabetic()) && matches!(next, Some(c) if c.is_alphabetic())
+    }
+
+    fn hyphenate(
+        &mut self,
+        hyphenator: &dyn Hyphenator,
+        ch: char,
+        ch_width: usize,
+        limit: usize,
+    ) -> Option<String> {
+        let n = self.buffer.len();
+        let mut start = n;
+        let mut word_width = 0;
+        while let Some(c) = self.buffer.get(start.wrapping_sub(1)) {
+            if !c.is_alphabetic() {
+                break;
+            }
+            start -= 1;
+            word_width += self.char_width(c);
+        }
+        if start == n {
+            return None;
+        }
+
+        let mut word = String::new();
+        for i in start..n {
+            word.push(self.buffer.get(i)?);
+        }
+        word.push(ch);
+        for c in self.scanner.clone() {
+            if !c.is_alphabetic() {
+                break;
+            }
+            word.push(c);
+        }
+
+        let head_width = (self.width[0] + self.width[1]).saturating_sub(word_width);
+        let mut split = 0;
+        let mut split_width = 0;
+        for pos in hyphenator.hyphenate(&word) {
+            if pos == 0 || !word.is_char_boundary(pos) {
+                continue;
+            }
+            let k = word[..pos].chars().count();
+            if k > n - start || k <= split {
+                continue;
+            }
+            let w = self.text_width(&word[..pos]);
+            if head_width + w < limit {
+                split = k;
+                split_width = w;
+            }
+        }
+        if split == 0 {
+            return None;
+        }
+
+        let mut line = self.line_until(start + split).into_owned();
+        line.push('-');
+        self.buffer.cr(start + split);
+        self.buffer.add(ch, self.pos);
+        self.width[0] = 0;
+        self.width[1] = word_width - split_width + ch_width;
+        self.lbo_pos = 0;
+        Some(line)
+    }
+
+    // Returns the line which consists of the characters before the specified
+    // position in the buffer, and stores the byte range which the line covers
+    // in the original text.
+    // `whole_line` does the same for all the characters in the buffer.
+    // The line borrows the original text if the characters are a contiguous
+    // part of it.
+    fn line_until(&mut self, end: usize) -> Cow<'a, str> {
+        match self.buffer.span_trimmed_end(0, end) {
+            Some(range) => {
+                self.range = self.snap_range(range);
+                match self.buffer.slice_trimmed_end(self.text, 0, end) {
+                    Some(slice) => Cow::Borrowed(slice),
+                    None => Cow::Owned(self.buffer.substring_trimmed_end(0, end)),
+                }
+            }
+            None => {
+                self.range = self.pos..self.pos;
+                Cow::Borrowed("")
+            }
+        }
+    }
+
+    // The end of a range which is computed from an escape of a non-printable
+    // character can be inside of the character, so it is moved to the next
+    // character boundary.
+    fn snap_range(&self, mut range: Range<usize>) -> Range<usize> {
+        while !self.text.is_char_boundary(range.end) {
+            range.end += 1;
+        }
+        range
+    }
+
+    fn whole_line(&mut self) -> Cow<'a, str> {
+        match self.buffer.span_trimmed_end(0, self.buffer.len()) {
+            Some(range) => {
+                self.range = self.snap_range(range);
+                match self
+                    .buffer
+                    .slice_trimmed_end(self.text, 0, self.buffer.len())
+                {
+                    Some(slice) => Cow::Borrowed(slice),
+                    None => Cow::Owned(self.buffer.to_string_trimmed_end()),
+                }
+            }
+            None => {
+                self.range = self.pos..self.pos;
+                Cow::Borrowed("")
+            }
+        }
+    }
+
+    fn split_long_word<F>(
+        &mut self,
+        split: F,
+        ch: char,
+        ch_width: usize,
+        state: &LboState,
+        limit: usize,
+    ) -> Option<Cow<'a, str>>
+    where
+        F: FnOnce(&Self, &str) -> Option<usize>,
+    {
+        let n = self.buffer.len();
+        let mut word = String::new();
+        for i in 0..n {
+            word.push(self.buffer.get(i)?);
+        }
+        word.push(ch);
+        for c in self.scanner.clone() {
+            if c.is_whitespace() {
+                break;
+            }
+            word.push(c);
+        }
+
+        let pos = split(self, &word)?;
+        if pos == 0 || !word.is_char_boundary(pos) {
+            return None;
+        }
+        let k = word[..pos].chars().count();
+        if k > n || self.text_width(&word[..pos]) > limit {
+            return None;
+        }
+
+        let line = self.line_until(k);
+        self.buffer.cr(k);
+        self.width[0] = 0;
+        self.width[1] = self.buffer_width(0..(n - k));
+        self.lbo_pos = 0;
+        self.put_char(ch, ch_width, state);
+        Some(line)
+    }
+
+    // Returns the last boundary of the parts of the specified identifier, at
+    // which the identifier can be divided within the specified width.
+    fn identifier_split_pos(&self, word: &str, limit: usize) -> Option<usize> {
+        let mut found = None;
+        let mut prev = None;
+        let mut escape_end = 0;
+        for (i, ch) in word.char_indices() {
+            // an identifier is not divided inside of an escape sequence.
+            if i < escape_end {
+                continue;
+            }
+            if self.ansi_policy != AnsiPolicy::Ignore && find_escape(&word[i..]) == Some(0) {
+                escape_end = i + escape_len(&word[i..]);
+                continue;
+            }
+            if let Some(p) = prev {
+                if is_identifier_boundary(p, ch) {
+                    if self.text_width(&word[..i]) > limit {
+                        break;
+                    }
+                    found = Some(i);
+                }
+            }
+            prev = Some(ch);
+        }
+        found
+    }
+
+    fn put_char(&mut self, ch: char, ch_width: usize, state: &LboState) {
+        if ch_width > 0
+            || (self.keep_zero_width && is_zero_width_attached(ch))
+            || (self.non_print == NonPrintPolicy::Keep && is_non_print(ch))
+        {
+            self.buffer.add(ch, self.pos);
+        }
+        match state.lbo_type {
+            LboType::Before => {
+                if state.lbo_prev != LboType::Before {
+                    self.lbo_pos = self.buffer.len() - 1;
+                }
+                self.width[0] += self.width[1];
+                self.width[1] = ch_width;
+            }
+            LboType::Both => {
+                self.lbo_pos = self.buffer.len() - 1;
+                self.width[0] += self.width[1];
+                self.width[1] = ch_width;
+            }
+            LboType::After | LboType::Space => {
+                self.lbo_pos = self.buffer.len();
+                self.width[0] += self.width[1] + ch_width;
+                self.width[1] = 0;
+            }
+            _ => {
+                self.width[1] += ch_width;
+            }
+        }
+    }
+
+    // keeps a run of the opposite direction to the paragraph unbroken, if the
+    // run fits in a line.
+    #[cfg(feature = "bidi")]
+    fn keep_bidi_run(&mut self, ch: char, limit: usize, state: &mut LboState) {
+        if self.bidi_run > 0 {
+            self.bidi_run -= 1;
+            state.lbo_type = LboType::Never;
+            return;
+        }
+        if strong_rtl(ch) != Some(!self.bidi_rtl) {
+            return;
+        }
+        let (mut n, mut w) = (0, 0);
+        let (mut len, mut width) = (0, 0);
+        for c in core::iter::once(ch).chain(self.scanner.clone()) {
+            if matches!(c, '\n' | '\r' | '\u{000C}' | '\u{2028}' | '\u{2029}') {
+                break;
+            }
+            n += 1;
+            w += self.char_width(c);
+            match strong_rtl(c) {
+                Some(rtl) if rtl == self.bidi_rtl => break,
+                Some(_) => (len, width) = (n, w),
+                None => (),
+            }
+        }
+        if width <= limit {
+            self.bidi_run = len - 1;
+        }
+    }
+
+    // Stops the iteration because of a content which starts at the specified
+    // offset and cannot be broken within the width, and returns the line
+    // before the content.
+    fn fail_overflow(&mut self, offset: usize, limit: usize) -> Cow<'a, str> {
+        self.overflow_error = Some(OverflowError {
+            offset,
+            width: limit,
+        });
+        self.has_next = false;
+        let end = (0..self.buffer.len())
+            .find(|i| self.buffer.pos(*i).map_or(true, |pos| pos >= offset))
+            .unwrap_or(self.buffer.len());
+        let line = self.line_until(end);
+        self.buffer.clear();
+        line
+    }
+
+    fn break_line(&mut self, limit: usize) -> Cow<'a, str> {
+        self.is_forced = false;
+        self.is_hard_break = false;
+
+        if let Some(range) = self.pending_line.take() {
+            self.is_hard_break = true;
+            self.range = range.clone();
+            return Cow::Borrowed(&self.text[range]);
+        }
+
+        if self.width[0] > limit {
+            let mut diff = self.width[0] - limit;
+            self.width[0] = diff;
+            let mut i = self.buffer.len();
+            while i > 0 {
+                i -= 1;
+                if let Some(ch) = self.buffer.get(i) {
+                    let prev = self.buffer.get(i.wrapping_sub(1));
+                    let ch_width = match self.buffer.pos(i) {
+                        Some(pos) if self.is_in_escape(pos) => 0,
+                        _ => self.char_width_after(prev, ch),
+                    };
+                    if diff <= ch_width {
+                        // a character wider than the line is put on the line
+                        // by itself.
+                        if i == 0 {
+                            let line = self.line_until(1);
+                            self.buffer.cr(1);
+                            self.width[0] = (diff + limit).saturating_sub(ch_width);
+                            self.is_forced = !self.buffer.is_empty();
+                            self.has_next = !self.buffer.is_empty() || self.has_rest();
+                            return line;
+                        }
+                        self.is_forced = true;
+                        let line = self.line_until(i);
+                        self.buffer.cr(i);
+                        return line;
+                    }
+                    diff -= ch_width;
+                } else {
+                    break;
+                }
+            }
+        } else if self.width[0] == limit {
+            self.width[0] = 0;
+            self.lbo_pos = 0;
+            let line = self.whole_line();
+            self.buffer.clear();
+            self.has_next = self.has_rest();
+            // a hard line break right after the line ends it, instead of
+            // making an empty line.
+            if self.skip_hard_line_break() {
+                self.line_start = self.text.len() - self.scanner.as_str().len();
+                self.open_quot = 0;
+                self.open_apos = 0;
+                self.ri_odd = false;
+                self.url.reset();
+                self.is_first_line = true;
+                self.is_blank_line = true;
+                self.is_hard_break = true;
+                self.has_next = true;
+            }
+            return line;
+        }
+
+        let mut state = LboState {
+            lbo_type: LboType::Never,
+            lbo_prev: LboType::Never,
+            open_quot: self.open_quot,
+            open_apos: self.open_apos,
+        };
+
+        loop {
+            let mut is_escape = false;
+            let ch = if self.tab_spaces > 0 {
+                self.tab_spaces -= 1;
+                ' '
+            } else if let Some(ch) = self.escaped.pop() {
+                is_escape = true;
+                ch
+            } else {
+                self.pos = self.text.len() - self.scanner.as_str().len();
+                let next = match self.scanner.next() {
+                    Some('\r')
+                        if !self.strict_line_breaks && self.scanner.as_str().starts_with('\n') =>
+                    {
+                        self.scanner.next();
+                        Some('\n')
+                    }
+                    next => next,
+                };
+                match next {
+                    // an escape sequence is kept as it is, and has neither
+                    // width nor line break opportunities.
+                    Some(ch) if self.is_in_escape(self.pos) => {
+                        self.buffer.add(ch, self.pos);
+                        continue;
+                    }
+                    Some('\t') if self.tab_width > 0 => {
+                        let col = self.width[0] + self.width[1];
+                        self.tab_spaces = self.tab_width - (col % self.tab_width) - 1;
+                        ' '
+                    }
+                    Some('\n') if self.paragraph_mode && self.skip_soft_line_break() => ' ',
+                    Some(ch)
+                        if matches!(
+                            self.non_print,
+                            NonPrintPolicy::EscapeUnicode | NonPrintPolicy::EscapeCaret
+                        ) && is_non_print(ch)
+                            && ch != '\u{200B}'
+                            && !self.width_overrides.contains_key(&ch) =>
+                    {
+                        escape_non_print(ch, self.non_print, &mut self.escaped);
+                        is_escape = true;
+                        self.escaped.pop().unwrap_or(ch)
+                    }
+                    Some(ch) => ch,
+                    None => break,
+                }
+            };
+
+            if !ch.is_whitespace() {
+                if self.is_blank_line && (self.auto_indent || self.list_items) {
+                    self.auto_indent_pad = self.auto_indent_hang;
+                    if self.list_items {
+                        let rest = &self.text[self.pos..];
+                        if let Some(w) = list_marker_width(rest, self.ambiguous_width) {
+                            self.auto_indent_pad += w;
+                        }
+                    }
+                    self.auto_indent_range = self.line_start..self.pos;
+                    self.auto_indent_width = self.width[0] + self.width[1] + self.auto_indent_pad;
+                }
+                #[cfg(feature = "bidi")]
+                if self.is_blank_line && self.bidi {
+                    self.bidi_rtl = is_rtl_paragraph(&self.text[self.pos..]);
+                }
+                self.is_blank_line = false;
+                if self.sentence_slack > 0 && !is_escape && self.is_sentence_start(ch) {
+                    self.sentence_start = Some(self.pos);
+                }
+            }
+
+            match self.break_mode {
+                _ if self.shell_command && !is_escape => {
+                    let escaped = self.is_escaped_in_shell(&state);
+                    shell_break_opportunity(ch, escaped, &mut state);
+                }
+                BreakMode::Code => self.code.line_break_opportunity(ch, &mut state),
+                _ if matches!(ch, '\'' | '\u{2019}')
+                    && !is_escape
+                    && self.is_apostrophe_in_word() =>
+                {
+                    state.lbo_prev = state.lbo_type;
+                    state.lbo_type = LboType::Never;
+                }
+                _ if matches!(ch, '"' | '\'') && !self.quote_aware => {
+                    state.lbo_prev = state.lbo_type;
+                    state.lbo_type = LboType::Never;
+                }
+                BreakMode::Basic => {
+                    line_break_opportunity(ch, &mut state);
+                    if self.break_hangul && is_hangul(ch) {
+                        state.lbo_type = LboType::Both;
+                    }
+                }
+                BreakMode::Uax14 => self.uax14.line_break_opportunity(ch, &mut state),
+            }
+            self.kinsoku_level.apply(ch, &mut state);
+            self.kinsoku.apply(ch, &mut state);
+            if ch == '-'
+                && self.break_on_hyphen
+                && state.lbo_type == LboType::Never
+                && self.is_hyphen_in_word()
+            {
+                state.lbo_type = LboType::After;
+            }
+            if ch == ' '
+                && state.lbo_type == LboType::Space
+                && !self.no_break_rules.is_empty()
+                && self.text[self.pos..].starts_with(' ')
+            {
+                if let Some(start) = self.glued_word_start() {
+                    state.lbo_type = LboType::Never;
+                    // the line break opportunities inside of the word before
+                    // the space, such as after a period, are also cancelled.
+                    self.cancel_lbo_after(start);
+                }
+            }
+            // a word joiner prohibits line breaks before and after it.
+            if self.after_joiner && !is_escape {
+                state.lbo_type = match state.lbo_type {
+                    LboType::Before => LboType::Never,
+                    LboType::Both => LboType::After,
+                    t => t,
+                };
+            }
+            self.after_joiner = false;
+            if matches!(ch, '\u{2060}' | '\u{FEFF}') && !is_escape {
+                if self.lbo_pos == self.buffer.len() {
+                    let start = self.last_word_start();
+                    self.cancel_lbo_after(start);
+                }
+                state.lbo_type = LboType::Never;
+                self.after_joiner = true;
+            }
+            if ch == '\u{000C}' && self.form_feed != FormFeed::Normal {
+                state.lbo_type = LboType::Break;
+            }
+            if ch == '\u{3000}' && self.ideographic_space == IdeographicSpace::Printable {
+                state.lbo_type = LboType::Never;
+            }
+            // an escape of a non-printable character is not broken.
+            if is_escape {
+                state.lbo_type = LboType::Never;
+            }
+            #[cfg(feature = "bidi")]
+            if self.bidi {
+                self.keep_bidi_run(ch, limit, &mut state);
+            }
+
+            // a pair of regional indicators is an unbreakable flag of width 2.
+            let is_flag = if !is_regional_indicator(ch) {
+                self.ri_odd = false;
+                false
+            } else if self.ri_odd {
+                self.ri_odd = false;
+                state.lbo_type = LboType::Never;
+                true
+            } else if self
+                .scanner
+                .clone()
+                .next()
+                .map_or(false, is_regional_indicator)
+            {
+                self.ri_odd = true;
+                if self.break_mode == BreakMode::Basic {
+                    state.lbo_type = LboType::Both;
+                }
+                true
+            } else {
+                false
+            };
+
+            let in_url = if self.url.is_active() {
+                self.url
+                    .line_break_opportunity(ch, self.url_break, &mut state);
+                true
+            } else if self.url_break != UrlBreak::Normal
+                && !ch.is_whitespace()
+                && self.is_word_start()
+            {
+                match find_url(ch, self.scanner.clone()) {
+                    Some((len, head)) => {
+                        let url_width = self.char_width(ch)
+                            + self
+                                .scanner
+                                .clone()
+                                .take(len - 1)
+                                .map(|c| self.char_width(c))
+                                .sum::<usize>();
+                        let overflow = self.url_break == UrlBreak::Keep && url_width > limit;
+                        if overflow && self.overflow_policy == OverflowPolicy::Error {
+                            return self.fail_overflow(self.pos, limit);
+                        }
+                        self.url.start(len, head, overflow);
+                        self.url
+                            .line_break_opportunity(ch, self.url_break, &mut state);
+
+                        // put the URL which overflows the line on a new line
+                        if overflow && !self.buffer.is_empty() {
+                            let line = self.whole_line();
+                            self.buffer.clear();
+                            self.buffer.add(ch, self.pos);
+                            self.width[0] = 0;
+                            self.width[1] = self.char_width(ch);
+                            self.lbo_pos = 0;
+                            self.open_quot = state.open_quot;
+                            self.open_apos = state.open_apos;
+                            self.has_next = true;
+                            return line;
+                        }
+                        true
+                    }
+                    None => false,
+                }
+            } else {
+                false
+            };
+
+            // a protected span is broken as usual if it is wider than the line.
+            if let Some(range) = self.protected.find(self.pos).filter(|_| !is_escape) {
+                if self.text_width(&self.text[range.clone()]) <= limit {
+                    ProtectedSpans::apply(&range, self.pos, ch, &mut state);
+                }
+            }
+
+            if state.lbo_type == LboType::Break {
+                let is_form_feed = ch == '\u{000C}' && self.form_feed != FormFeed::Normal;
+                if is_form_feed {
+                    self.skip_line_break();
+                }
+                let mut line = self.whole_line();
+                self.line_start = self.text.len() - self.scanner.as_str().len();
+                self.buffer.clear();
+                self.width[0] = 0;
+                self.width[1] = 0;
+                self.lbo_pos = 0;
+                self.open_quot = 0;
+                self.open_apos = 0;
+                self.tab_spaces = 0;
+                self.escaped.clear();
+                self.ri_odd = false;
+                self.url.reset();
+                // a line separator does not end the paragraph, and a paragraph
+                // separator ends it as a blank line does in paragraph mode.
+                self.is_first_line = ch != '\u{2028}';
+                if ch == '\u{2029}' && self.paragraph_mode && !self.is_blank_line {
+                    self.pending_line = Some(self.pos..self.pos);
+                }
+                self.is_blank_line = true;
+                if is_form_feed {
+                    let form_feed = self.pos..(self.pos + 1);
+                    match self.form_feed {
+                        FormFeed::Emit if line.is_empty() => {
+                            self.range = form_feed.clone();
+                            line = Cow::Borrowed(&self.text[form_feed]);
+                        }
+                        FormFeed::Emit => self.pending_line = Some(form_feed),
+                        _ if line.is_empty() => continue,
+                        _ => (),
+                    }
+                }
+                self.is_hard_break = true;
                 self.has_next = true;
-                return Some(line);
+                return line;
             }
 
-            if ch_width > 0 {
-                self.buffer.add(ch);
-            }
-            match state.lbo_type {
-                LboType::Before => {
-                    if state.lbo_prev != LboType::Before {
-                        self.lbo_pos = self.buffer.len() - 1;
-                    }
-                    self.width[0] += self.width[1];
-                    self.width[1] = ch_width;
-                }
-                LboType::Both => {
-                    self.lbo_pos = self.buffer.len() - 1;
-                    self.width[0] += self.width[1];
-                    self.width[1] = ch_width;
-                }
-                LboType::After | LboType::Space => {
-                    self.lbo_pos = self.buffer.len();
-                    self.width[0] += self.width[1] + ch_width;
-                    self.width[1] = 0;
-                }
-                _ => {
-                    self.width[1] += ch_width;
-                }
-            }
-        }
+            if (self.buffer.is_empty() || self.has_only_escapes())
+                && state.lbo_type == LboType::Space
+                && !((self.preserve_leading_whitespace || self.auto_indent || self.list_items)
+                    && self.is_blank_line)
+            {
+                continue;
+            }
+
+            let prev = self.buffer.get(self.buffer.len().wrapping_sub(1));
+            let ch_width = match is_flag {
+                true => 1,
+                false => self.char_width_after(prev, ch),
+            };
+            let mut lbo_pos = self.lbo_pos;
+
+            let keeps_url = in_url && self.url.overflow;
+
+            if self.buffer.is_empty()
+                && ch_width > limit
+                && self.overflow_policy == OverflowPolicy::Error
+            {
+                return self.fail_overflow(self.pos, limit);
+            }
+            // a character wider than the line is put on the line by itself.
+            let fits = self.width[0] + self.width[1] + ch_width <= limit
+                || self.buffer.is_empty()
+                || (self.width[0] + self.width[1] <= limit && self.can_hang(ch, &state));
+            if !fits && !keeps_url {
+                if let Some(hyphenator) = self.hyphenator {
+                    if state.lbo_type == LboType::Never && ch.is_alphabetic() && !in_url {
+                        if let Some(line) = self.hyphenate(hyphenator, ch, ch_width, limit) {
+                            self.open_quot = state.open_quot;
+                            self.open_apos = state.open_apos;
+                            self.has_next = true;
+                            return Cow::Owned(line);
+                        }
+                    }
+                }
+
+                if state.lbo_prev == LboType::Before {
+                    let line = self.line_until(lbo_pos);
+                    self.buffer.cr(lbo_pos);
+
+                    self.buffer.add(ch, self.pos);
+                    self.width[0] = self.width[1] + ch_width;
+                    self.width[1] = 0;
+                    self.lbo_pos = self.buffer.len();
+
+                    self.open_quot = state.open_quot;
+                    self.open_apos = state.open_apos;
+
+                    self.has_next = true;
+                    return line;
+                }
+
+                match state.lbo_type {
+                    LboType::Before | LboType::Both | LboType::Space => {
+                        lbo_pos = self.buffer.len();
+                    }
+                    _ => (),
+                }
+                // a line is broken at the end of a sentence rather than at the
+                // last opportunity, if it does not leave much space unused.
+                if let Some(pos) = self.sentence_break_pos(lbo_pos, ch_width, limit) {
+                    return self.break_before(pos, ch, ch_width, &state);
+                }
+                // the last word of a paragraph is not left alone on a line.
+                if lbo_pos > 0 && self.widow_control {
+                    if let Some(pos) = self.widow_break_pos(lbo_pos, ch, ch_width, limit) {
+                        return self.break_before(pos, ch, ch_width, &state);
+                    }
+                }
+                // break forcely when no lbo in the current line
+                if lbo_pos == 0 {
+                    match self.overflow_policy {
+                        OverflowPolicy::ForceBreak => (),
+                        OverflowPolicy::Overflow => {
+                            self.put_char(ch, ch_width, &state);
+                            continue;
+                        }
+                        OverflowPolicy::Error => {
+                            let offset = self.buffer.pos(0).unwrap_or(self.pos);
+                            return self.fail_overflow(offset, limit);
+                        }
+                    }
+                    let split_line = match self.long_word_policy {
+                        LongWordPolicy::BreakAnywhere => None,
+                        LongWordPolicy::Identifier => {
+                            let split =
+                                |iter: &Self, word: &str| iter.identifier_split_pos(word, limit);
+                            self.split_long_word(split, ch, ch_width, &state, limit)
+                        }
+                        LongWordPolicy::Split => match self.hyphenator {
+                            Some(hyphenator) => {
+                                let split = |_: &Self, word: &str| hyphenator.split(word, limit);
+                                self.split_long_word(split, ch, ch_width, &state, limit)
+                            }
+                            None => None,
+                        },
+                    };
+                    if let Some(line) = split_line {
+                        self.is_forced = true;
+                        self.open_quot = state.open_quot;
+                        self.open_apos = state.open_apos;
+                        self.has_next = true;
+                        return line;
+                    }
+                    self.is_forced = true;
+                    self.width[0] += self.width[1];
+                    self.width[1] = 0;
+                    lbo_pos = self.buffer.len();
+                }
+
+                let line = self.line_until(lbo_pos);
+                self.buffer.cr(lbo_pos);
+
+                match state.lbo_type {
+                    LboType::Space => {
+                        self.width[0] = 0;
+                        self.width[1] = 0;
+                        self.lbo_pos = 0;
+                    }
+                    LboType::Before | LboType::Both => {
+                        self.buffer.add(ch, self.pos);
+                        self.width[0] = ch_width;
+                        self.width[1] = 0;
+                        self.lbo_pos = 0;
+                    }
+                    LboType::After => {
+                        self.buffer.add(ch, self.pos);
+                        self.width[0] = self.width[1] + ch_width;
+                        self.width[1] = 0;
+                        self.lbo_pos = self.buffer.len();
+                    }
+                    _ => {
+                        self.buffer.add(ch, self.pos);
+                        self.width[0] = self.width[1] + ch_width;
+                        self.width[1] = 0;
+                        self.lbo_pos = 0;
+                    }
+                }
+
+                self.open_quot = state.open_quot;
+                self.open_apos = state.open_apos;
+
+                self.has_next = true;
+                return line;
+            }
+
+            self.put_char(ch, ch_width, &state);
+        }
+
+        let line = self.whole_line();
+        self.buffer.clear();
+
+        self.has_next = false;
+        line
+    }
+}
+
+impl<'a> LineIter<'a> {
+    // Starts a new line, and returns the indentation, its width, the width of
+    // the auto indentation and the width which is available for the text.
+    fn start_line(&mut self) -> (Cow<'a, str>, usize, usize, usize) {
+        let (indent, indent_width) = match &self.first_indent {
+            Some(first_indent) if self.is_first_line => {
+                (first_indent.clone(), self.first_indent_width)
+            }
+            // the continued lines of a shell command are indented further.
+            _ if self.shell_command && !self.is_first_line => (
+                Cow::Owned(format!("{}{}", self.indent, SHELL_INDENT)),
+                self.indent_width + SHELL_INDENT.len(),
+            ),
+            _ => (self.indent.clone(), self.indent_width),
+        };
+        self.is_first_line = false;
+        self.line_indent = Cow::Borrowed("");
+        let offset = match self.line_no {
+            0 => self.first_line_offset,
+            _ => 0,
+        };
+        let line_width = match self.first_line_width {
+            Some(width) if self.line_no < self.first_line_count => width,
+            _ => self.limit,
+        };
+        self.line_no += 1;
+
+        // if the indentation, the prefix and the suffix leave no room for text,
+        // at least one column is given to the text and the line overflows.
+        let decoration_width = offset
+            + indent_width
+            + self.prefix_width
+            + self.suffix_width
+            + self.continuation_width
+            + self.gutter_width;
+        let limit = line_width.saturating_sub(decoration_width).max(1);
+
+        // the wrapped lines of a source line are indented with the leading
+        // whitespace of the source line, if it is at most a half of the width.
+        let auto_indent_width = match self.is_blank_line {
+            false
+                if (self.auto_indent || self.list_items) && self.auto_indent_width <= limit / 2 =>
+            {
+                self.auto_indent_width
+            }
+            _ => 0,
+        };
+        (
+            indent,
+            indent_width,
+            auto_indent_width,
+            limit - auto_indent_width,
+        )
+    }
+
+    // Returns the gutter string of the current line, which is padded to the
+    // width of the gutter.
+    fn gutter_string(&self) -> String {
+        let mut s = match self.gutter {
+            Some(gutter) => gutter.gutter(self.line_no),
+            None => format!("{:>1$} ", self.line_no, self.line_numbers),
+        };
+        let width = self.text_width(&s);
+        if width < self.gutter_width {
+            s.push_str(&" ".repeat(self.gutter_width - width));
+        }
+        s
+    }
+
+    // Returns the number of the lines which are already output.
+    pub(crate) fn line_no(&self) -> usize {
+        self.line_no
+    }
+
+    // Sets the number of the lines which are already output, so that the line
+    // numbers and the maximum number of lines continue from another iterator.
+    pub(crate) fn set_line_no(&mut self, line_no: usize) {
+        self.line_no = line_no;
+    }
+
+    // Sets whether more text follows the text of this iterator in another
+    // iterator, so that the last line is truncated if the maximum number of
+    // lines is reached.
+    pub(crate) fn set_text_follows(&mut self, follows: bool) {
+        self.text_follows = follows;
+    }
+
+    // Takes the state of the escape sequences which are active at the end of
+    // the output lines, so that another iterator continues from it.
+    pub(crate) fn take_ansi_state(&mut self) -> AnsiState {
+        core::mem::replace(&mut self.ansi_state, AnsiState::new())
+    }
+
+    pub(crate) fn set_ansi_state(&mut self, state: AnsiState) {
+        self.ansi_state = state;
+    }
+
+    // Breaks off the next line and decorates it.
+    fn read_line(&mut self) -> Option<Cow<'a, str>> {
+        let (line, layout) = self.read_raw_line()?;
+        if !layout.is_decorated {
+            return Some(line);
+        }
+        let capacity = self.prefix.len() + layout.indent.len() + line.len() + self.suffix.len();
+        let mut decorated = String::with_capacity(capacity);
+        self.decorate_into(&line, &layout, &mut decorated);
+        Some(Cow::Owned(decorated))
+    }
+
+    // Breaks off the next line, and returns it with the layout of its
+    // decoration.
+    fn read_raw_line(&mut self) -> Option<(Cow<'a, str>, Layout<'a>)> {
+        if !self.has_next {
+            return None;
+        }
+
+        let (mut indent, indent_width, auto_indent_width, mut limit) = self.start_line();
+        let auto_indent_range = self.auto_indent_range.clone();
+
+        let hang = self.left_hang_width(&indent);
+        limit += hang;
+
+        let mut line = self.break_line(limit);
+        if hang > 0 {
+            match line.chars().next() {
+                Some(ch) if self.hanging.hangs_at_start(ch) => {
+                    indent = Cow::Owned(String::from(&indent[..indent.len() - hang]));
+                }
+                _ => limit -= hang,
+            }
+        }
+        if self.overflow_error.is_some() && line.is_empty() {
+            return None;
+        }
+        if self.max_lines > 0 && self.line_no >= self.max_lines {
+            if self.has_next || self.text_follows {
+                line = Cow::Owned(self.truncate_line(&line, limit));
+            }
+            self.has_next = false;
+        }
+        let limit = limit + auto_indent_width;
+        if self.ansi_policy == AnsiPolicy::Reapply {
+            line = self.reapply_ansi(line);
+        }
+        let is_continued = !self.continuation.is_empty()
+            && !line.is_empty()
+            && self.has_next
+            && !self.is_hard_break;
+
+        #[cfg(feature = "bidi")]
+        let line = match self.bidi && line.chars().any(|c| strong_rtl(c) == Some(true)) {
+            true => Cow::Owned(reorder_line(&line, self.bidi_rtl)),
+            false => line,
+        };
+
+        let is_decorated = (!line.is_empty()
+            && (!indent.is_empty() || auto_indent_width > 0 || self.align != Align::Left))
+            || !self.suffix.is_empty()
+            || !self.prefix.is_empty()
+            || self.gutter_width > 0
+            || is_continued;
+        if !line.is_empty() {
+            self.line_indent = indent.clone();
+        }
+        let layout = Layout {
+            indent,
+            indent_width,
+            auto_indent_width,
+            auto_indent_range,
+            limit,
+            is_continued,
+            is_decorated,
+        };
+        Some((line, layout))
+    }
+
+    // Writes the specified line with its gutter, prefix, padding, indentation
+    // and suffix into the specified buffer from the left to the right, so that
+    // no bytes in the buffer are shifted.
+    fn decorate_into(&self, line: &str, layout: &Layout, buf: &mut String) {
+        if self.gutter_width > 0 {
+            buf.push_str(&self.gutter_string());
+        }
+        buf.push_str(self.prefix);
+
+        let mut width = 0;
+        if !line.is_empty() {
+            let (ws, pad) = match layout.auto_indent_width {
+                0 => ("", 0),
+                w => {
+                    let ws = &self.text[layout.auto_indent_range.clone()];
+                    if self.tab_width > 0 && ws.contains('\t') {
+                        ("", w)
+                    } else {
+                        (ws, self.auto_indent_pad)
+                    }
+                }
+            };
+            width =
+                self.text_width(&layout.indent) + self.text_width(ws) + pad + self.text_width(line);
+
+            if self.align != Align::Left {
+                let space = (layout.limit + layout.indent_width).saturating_sub(width);
+                let align_pad = match self.align {
+                    Align::Center => space / 2,
+                    _ => space,
+                };
+                push_spaces(buf, align_pad);
+                width += align_pad;
+            }
+            buf.push_str(&layout.indent);
+            buf.push_str(ws);
+            push_spaces(buf, pad);
+            buf.push_str(line);
+            // a continuation marker is put in the room reserved for it, so
+            // that it does not move the aligned text.
+            if layout.is_continued {
+                buf.push_str(self.continuation);
+                width += self.continuation_width;
+            }
+        }
+        if !self.suffix.is_empty() {
+            let limit = layout.limit + layout.indent_width + self.continuation_width;
+            push_spaces(buf, limit.saturating_sub(width));
+            buf.push_str(self.suffix);
+        }
+    }
+
+    // Advances this iterator by a line without decorating the line, and
+    // returns false if there is no more line.
+    pub(crate) fn skip_line(&mut self) -> bool {
+        if let Some(peeked) = self.peeked.take() {
+            return peeked.line.is_some();
+        }
+        if !self.has_next {
+            return false;
+        }
+        let (indent, _, _, limit) = self.start_line();
+        let hang = self.left_hang_width(&indent);
+        let line = self.break_line(limit + hang);
+        if self.max_lines > 0 && self.line_no >= self.max_lines {
+            self.has_next = false;
+        }
+        if self.ansi_policy == AnsiPolicy::Reapply {
+            self.ansi_state.update(&line);
+        }
+        !(self.overflow_error.is_some() && line.is_empty())
+    }
+
+    // Truncates the specified line so that the placeholder fits within the
+    // specified width, and puts the placeholder at the end of it.
+    fn truncate_line(&self, line: &str, limit: usize) -> String {
+        let room = limit.saturating_sub(self.placeholder_width);
+        let mut width = 0;
+        let mut end = 0;
+        for (i, ch) in line.char_indices() {
+            width += self.char_width(ch);
+            if width > room {
+                break;
+            }
+            end = i + ch.len_utf8();
+        }
+        let mut truncated = String::from(line[..end].trim_end());
+        truncated.push_str(self.placeholder);
+        truncated
+    }
+}
+
+impl<'a> Iterator for LineIter<'a> {
+    type Item = Cow<'a, str>;
+
+    /// Returns an Option of a line string.
+    /// If there is a line string to be printed, this method returns a
+    /// `Some(Cow<str>)`, otherwise returns `None.`
+    /// The line string borrows the given text if it is a contiguous part of
+    /// the text and no indentation, prefix, suffix or padding is added to it.
+    ///
+    /// ```rust
+    ///     use linebreak::LineIter;
+    ///
+    ///     let text = "The Rust programming language helps you write faster, \
+    ///         more reliable software.";
+    ///     let mut iter = LineIter::new(&text, 30);
+    ///     assert_eq!(iter.next().unwrap(), "The Rust programming language");
+    ///     assert_eq!(iter.next().unwrap(), "helps you write faster, more");
+    ///     assert_eq!(iter.next().unwrap(), "reliable software.");
+    ///     assert_eq!(iter.next().is_none(), true);
+    /// ```
+    fn next(&mut self) -> Option<Cow<'a, str>> {
+        match self.peeked.take() {
+            Some(peeked) => {
+                self.range = peeked.range;
+                self.line_indent = peeked.indent;
+                self.is_forced = peeked.is_forced;
+                self.is_hard_break = peeked.is_hard_break;
+                peeked.line
+            }
+            None => self.read_line(),
+        }
+    }
+}
+
+fn push_spaces(buf: &mut String, n: usize) {
+    buf.extend(core::iter::repeat(' ').take(n));
+}
+
+#[cfg(test)]
+#[allow(clippy::redundant_static_lifetimes, clippy::while_let_on_iterator)]
+mod test_of_line_iter {
+    use super::*;
+    use crate::no_break::NoBreakPair;
+
+    #[test]
+    fn test_empty() {
+        let text = "";
+        let mut iter = LineIter::new(text, 20);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, text);
+
+        let opt = iter.next();
+        assert!(opt.is_none());
+    }
+
+    #[test]
+    fn test_one_char_text() {
+        let text = "a";
+        let mut iter = LineIter::new(text, 20);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, text);
+
+        let opt = iter.next();
+        assert!(opt.is_none());
+    }
+
+    #[test]
+    fn test_less_than_line_width() {
+        let text = "1234567890123456789";
+        let mut iter = LineIter::new(text, 20);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, text);
+
+        let opt = iter.next();
+        assert!(opt.is_none());
+    }
+
+    #[test]
+    fn test_equal_to_line_width() {
+        let text = "12345678901234567890";
+        let mut iter = LineIter::new(text, 20);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, text);
+
+        let opt = iter.next();
+        assert!(opt.is_none());
+    }
+
+    #[test]
+    fn test_break_at_line_break_opportunity() {
+        let text = "1234567890 abcdefghij";
+        let mut iter = LineIter::new(text, 20);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, text[0..10]);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, text[11..21]);
+
+        let opt = iter.next();
+        assert!(opt.is_none());
+    }
+
+    #[test]
+    fn test_remove_heading_space_of_each_line() {
+        let text = "12345678901234567890   abcdefghij";
+        let mut iter = LineIter::new(text, 20);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, text[0..20]);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, text[23..]);
+
+        let opt = iter.next();
+        assert!(opt.is_none());
+    }
+
+    #[test]
+    fn test_remove_tailing_space_of_each_line() {
+        let text = "12345678901234567      abcdefghij";
+        let mut iter = LineIter::new(text, 20);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, text[0..17]);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, text[23..]);
+
+        let opt = iter.next();
+        assert!(opt.is_none());
+    }
+
+    #[test]
+    fn test_remove_spaces_of_all_space_line() {
+        let text = "       ";
+        let mut iter = LineIter::new(text, 10);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "");
+
+        let opt = iter.next();
+        assert!(opt.is_none());
+    }
+
+    #[test]
+    fn test_remove_there_is_no_line_break_opportunity() {
+        let text = "12345678901234567890abcdefghij";
+        let mut iter = LineIter::new(text, 20);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, text[0..20]);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, text[20..]);
+
+        let opt = iter.next();
+        assert!(opt.is_none());
+    }
+
+    #[test]
+    fn test_set_indent() {
+        let text = "12345678901234567890abcdefghij";
+        let mut iter = LineIter::new(text, 10);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, text[0..10]);
+
+        iter.set_indent("   ");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "   ".to_string() + &text[10..17]);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "   ".to_string() + &text[17..24]);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "   ".to_string() + &text[24..]);
+
+        let opt = iter.next();
+        assert!(opt.is_none());
+    }
+
+    #[test]
+    fn test_break_position_after_indent_width_is_increased() {
+        let line_width = 30;
+        let indent = " ".repeat(7);
+        let indent_width = crate::text_width(&indent);
+        let text = "aaaaa ".to_string()
+            + &"b".repeat(line_width - indent_width)
+            + &"c".repeat(line_width - indent_width)
+            + "ddd";
+
+        let mut iter = LineIter::new(&text, line_width);
+        let line = iter.next().unwrap();
+        assert_eq!(line, "aaaaa");
+        assert_eq!(crate::text_width(&line), 5);
+
+        iter.set_indent(&indent);
+
+        let line = iter.next().unwrap();
+        assert_eq!(line, " ".repeat(7) + &"b".repeat(line_width - indent_width));
+        assert_eq!(crate::text_width(&line), line_width);
+
+        let line = iter.next().unwrap();
+        assert_eq!(line, " ".repeat(7) + &"c".repeat(line_width - indent_width));
+        assert_eq!(crate::text_width(&line), line_width);
+
+        let line = iter.next().unwrap();
+        assert_eq!(line, " ".repeat(7) + "ddd");
+        assert_eq!(crate::text_width(&line), 10);
+
+        let line = iter.next();
+        assert_eq!(line, None);
+    }
+
+    #[test]
+    fn test_set_width() {
+        let mut iter = LineIter::new("abc defghij", 8);
+        assert_eq!(iter.next().unwrap(), "abc");
+        iter.set_width(3);
+        assert_eq!(iter.next().unwrap(), "def");
+        assert_eq!(iter.next().unwrap(), "ghi");
+        assert_eq!(iter.next().unwrap(), "j");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new("abcdefghijklmnopqrstuvwxyz", 5);
+        assert_eq!(iter.next().unwrap(), "abcde");
+        iter.set_width(15);
+        assert_eq!(iter.next().unwrap(), "fghijklmnopqrst");
+        iter.set_width(2);
+        iter.set_indent(">");
+        assert_eq!(iter.next().unwrap(), ">u");
+        assert_eq!(iter.next().unwrap(), ">v");
+        iter.set_width(10);
+        assert_eq!(iter.next().unwrap(), ">wxyz");
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_exactly_full_line_before_line_break() {
+        let iter = LineIter::new("aあ\nb", 2);
+        assert_eq!(iter.collect::<Vec<_>>(), ["a", "あ", "b"]);
+
+        let iter = LineIter::new("漢字、\nx", 4);
+        assert_eq!(iter.collect::<Vec<_>>(), ["漢", "字、", "x"]);
+
+        let iter = LineIter::new("漢字、\r\n\r\nx", 4);
+        assert_eq!(iter.collect::<Vec<_>>(), ["漢", "字、", "", "x"]);
+
+        let iter = LineIter::new("aあ\n", 2);
+        assert_eq!(iter.collect::<Vec<_>>(), ["a", "あ", ""]);
+
+        let mut iter = LineIter::new("aあ\r\nb", 2);
+        iter.set_strict_line_breaks(true);
+        assert_eq!(iter.collect::<Vec<_>>(), ["a", "あ", "", "b"]);
+
+        let mut iter = LineIter::new("aあ\nb", 2);
+        iter.set_paragraph_mode(true);
+        assert_eq!(iter.collect::<Vec<_>>(), ["a", "あ", "b"]);
+    }
+
+    #[test]
+    fn test_break_position_if_indent_contains_full_width_chars() {
+        let line_width = 30;
+        let indent = "__ああ__".to_string(); // width is 8.
+        let indent_width = crate::text_width(&indent);
+        let text = "aaaaa ".to_string()
+            + &"b".repeat(line_width - indent_width)
+            + &"c".repeat(line_width - indent_width)
+            + "ddd";
+
+        let mut iter = LineIter::new(&text, line_width);
+        let line = iter.next().unwrap();
+        assert_eq!(line, "aaaaa");
+        assert_eq!(crate::text_width(&line), 5);
+
+        iter.set_indent(&indent);
+
+        let line = iter.next().unwrap();
+        assert_eq!(
+            line,
+            "__ああ__".to_string() + &"b".repeat(line_width - indent_width)
+        );
+        assert_eq!(crate::text_width(&line), line_width);
+
+        let line = iter.next().unwrap();
+        assert_eq!(
+            line,
+            "__ああ__".to_string() + &"c".repeat(line_width - indent_width)
+        );
+        assert_eq!(crate::text_width(&line), line_width);
+
+        let line = iter.next().unwrap();
+        assert_eq!(line, "__ああ__ddd");
+        assert_eq!(crate::text_width(&line), 11);
+
+        let line = iter.next();
+        assert_eq!(line, None);
+    }
+
+    #[test]
+    fn test_init() {
+        let text = "12345678901234567890";
+        let mut iter = LineIter::new(text, 12);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, text[0..12]);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, text[12..]);
+
+        let opt = iter.next();
+        assert!(opt.is_none());
+
+        let text = "abcdefghijklmnopqrstuvwxyz";
+        iter.init(text);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, text[0..12]);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, text[12..24]);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, text[24..]);
+
+        let opt = iter.next();
+        assert!(opt.is_none());
+    }
+
+    // This text is quoted from https://go.dev/doc/
+    const LONG_TEXT: &'static str = r#"The Go programming language is an open source project to make programmers more productive.
+
+Go is expressive, concise, clean, and efficient. Its concurrency mechanisms make it easy to write programs that get the most out of multicore and networked machines, while its novel type system enables flexible and modular program construction. Go compiles quickly to machine code yet has the convenience of garbage collection and the power of run-time reflection. It's a fast, statically typed, compiled language that feels like a dynamically typed, interpreted language.    "#;
+
+    #[test]
+    fn test_try_long_text() {
+        let mut iter = LineIter::new(LONG_TEXT, 20);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "The Go programming");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "language is an open");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "source project to");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "make programmers");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "more productive.");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "Go is expressive,");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "concise, clean, and");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "efficient. Its");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "concurrency");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "mechanisms make it");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "easy to write");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "programs that get");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "the most out of");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "multicore and");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "networked machines,");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "while its novel type");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "system enables");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "flexible and modular");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "program");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "construction. Go");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "compiles quickly to");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "machine code yet has");
 
-        let mut line = self.buffer.to_string_trimmed_end();
-        self.buffer.clear();
+        let s = iter.next().unwrap();
+        assert_eq!(s, "the convenience of");
 
-        if !line.is_empty() {
-            line.insert_str(0, self.indent);
+        let s = iter.next().unwrap();
+        assert_eq!(s, "garbage collection");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "and the power of");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "run-time reflection.");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "It's a fast,");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "statically typed,");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "compiled language");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "that feels like a");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "dynamically typed,");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "interpreted");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "language.");
+
+        let opt = iter.next();
+        assert!(opt.is_none());
+    }
+
+    #[test]
+    fn test_print_long_text() {
+        let mut iter = LineIter::new(LONG_TEXT, 20);
+
+        while let Some(s) = iter.next() {
+            println!("{}", s);
         }
-        self.has_next = false;
-        return Some(line);
     }
-}
 
-#[cfg(test)]
-mod test_of_line_iter {
-    use super::*;
+    #[test]
+    fn test_set_indent_to_long_text() {
+        let mut iter = LineIter::new(LONG_TEXT, 40);
+
+        if let Some(s) = iter.next() {
+            println!("{}", s);
+        }
+
+        let indent = " ".repeat(8);
+        iter.set_indent(&indent);
+
+        while let Some(s) = iter.next() {
+            println!("{}", s);
+        }
+    }
 
     #[test]
-    fn test_empty() {
-        let text = "";
-        let mut iter = LineIter::new(text, 20);
+    fn test_text_contains_non_print_char() {
+        let text = "abcdefg\u{0002}hijklmn";
+        let mut iter = LineIter::new(text, 10);
 
         let s = iter.next().unwrap();
-        assert_eq!(s, text);
+        assert_eq!(s, "abcdefghij");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "klmn");
 
         let opt = iter.next();
         assert!(opt.is_none());
     }
 
     #[test]
-    fn test_one_char_text() {
-        let text = "a";
+    fn test_letter_width_of_east_asian_wide_letter() {
+        let text = "東アジアの全角文字は２文字分の幅をとります。";
         let mut iter = LineIter::new(text, 20);
 
         let s = iter.next().unwrap();
-        assert_eq!(s, text);
+        assert_eq!(s, "東アジアの全角文字は");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "２文字分の幅をとりま");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "す。");
 
         let opt = iter.next();
         assert!(opt.is_none());
     }
 
     #[test]
-    fn test_less_than_line_width() {
-        let text = "1234567890123456789";
-        let mut iter = LineIter::new(text, 20);
+    fn test_line_break_of_east_asian_wide_letter() {
+        let text =
+            "東アジアの全角文字は基本的に、文字の前後どちらに行の終わりが来ても改行が行われます。";
+        let mut iter = LineIter::new(text, 28);
 
         let s = iter.next().unwrap();
-        assert_eq!(s, text);
+        assert_eq!(s, "東アジアの全角文字は基本的");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "に、文字の前後どちらに行の終");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "わりが来ても改行が行われま");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "す。");
 
         let opt = iter.next();
         assert!(opt.is_none());
     }
 
     #[test]
-    fn test_equal_to_line_width() {
-        let text = "12345678901234567890";
-        let mut iter = LineIter::new(text, 20);
+    fn test_prohibitions_of_line_break_of_japanese_start() {
+        let text = "句読点は、行頭に置くことは禁止である。";
+        let mut iter = LineIter::new(text, 8);
 
         let s = iter.next().unwrap();
-        assert_eq!(s, text);
+        assert_eq!(s, "句読点");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "は、行頭");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "に置くこ");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "とは禁止");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "である。");
 
         let opt = iter.next();
         assert!(opt.is_none());
     }
 
     #[test]
-    fn test_break_at_line_break_opportunity() {
-        let text = "1234567890 abcdefghij";
-        let mut iter = LineIter::new(text, 20);
+    fn test_prohibitions_of_line_break_of_japanese_end() {
+        let text = "開き括弧は「行末に置く」ことは禁止である。";
+        let mut iter = LineIter::new(text, 12);
 
         let s = iter.next().unwrap();
-        assert_eq!(s, text[0..10]);
+        assert_eq!(s, "開き括弧は");
 
         let s = iter.next().unwrap();
-        assert_eq!(s, text[11..21]);
+        assert_eq!(s, "「行末に置");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "く」ことは禁");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "止である。");
 
         let opt = iter.next();
         assert!(opt.is_none());
     }
 
     #[test]
-    fn test_remove_heading_space_of_each_line() {
-        let text = "12345678901234567890   abcdefghij";
-        let mut iter = LineIter::new(text, 20);
+    fn test_prohibitions_of_line_break_of_english() {
+        let text = "abc def ghi(jkl mn opq rst uvw xyz)";
+        let mut iter = LineIter::new(text, 11);
 
         let s = iter.next().unwrap();
-        assert_eq!(s, text[0..20]);
+        assert_eq!(s, "abc def ghi");
 
         let s = iter.next().unwrap();
-        assert_eq!(s, text[23..]);
+        assert_eq!(s, "(jkl mn opq");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "rst uvw");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "xyz)");
 
         let opt = iter.next();
         assert!(opt.is_none());
     }
 
     #[test]
-    fn test_remove_tailing_space_of_each_line() {
-        let text = "12345678901234567      abcdefghij";
-        let mut iter = LineIter::new(text, 20);
+    fn test_prohibitions_of_line_break_of_english_quots() {
+        let text = r#"abc def " ghi j " kl mno pq" rst uvw" xyz"#;
+        let mut iter = LineIter::new(text, 9);
 
         let s = iter.next().unwrap();
-        assert_eq!(s, text[0..17]);
+        assert_eq!(s, "abc def");
 
         let s = iter.next().unwrap();
-        assert_eq!(s, text[23..]);
+        assert_eq!(s, "\" ghi j \"");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "kl mno pq");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "\" rst");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "uvw\" xyz");
 
         let opt = iter.next();
         assert!(opt.is_none());
     }
 
     #[test]
-    fn test_remove_spaces_of_all_space_line() {
-        let text = "       ";
-        let mut iter = LineIter::new(text, 10);
+    fn test_prohibitions_of_line_break_of_english_mixed_quots() {
+        let text = r#"abc def " ghi j ' kl mno pq' rst uvw" xyz"#;
+        let mut iter = LineIter::new(text, 9);
 
         let s = iter.next().unwrap();
-        assert_eq!(s, "");
+        assert_eq!(s, "abc def");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "\" ghi j");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "' kl mno");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "pq' rst");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "uvw\" xyz");
 
         let opt = iter.next();
         assert!(opt.is_none());
+
+        iter.init(text);
+
+        while let Some(s) = iter.next() {
+            println!("{}", s);
+        }
     }
 
     #[test]
-    fn test_remove_there_is_no_line_break_opportunity() {
-        let text = "12345678901234567890abcdefghij";
-        let mut iter = LineIter::new(text, 20);
+    fn test_print_japanese() {
+        let text = "".to_string()
+            + "私はその人を常に先生と呼んでいた。だからここでもただ先生と書く"
+            + "だけで本名は打ち明けない。これは世間を憚かる遠慮というよりも、"
+            + "その方が私にとって自然だからである。私はその人の記憶を呼び起す"
+            + "ごとに、すぐ「先生」といいたくなる。筆を執っても心持は同じ事で"
+            + "ある。よそよそしい頭文字などはとても使う気にならない。\n"
+            + "（夏目漱石「こころ」から引用）";
+
+        let mut iter = LineIter::new(&text, 50);
+
+        while let Some(s) = iter.next() {
+            println!("{}", s);
+        }
+    }
+
+    #[test]
+    fn test_break_mode_uax14_breaks_after_hyphens() {
+        let text = "state-of-the-art design";
+        let mut iter = LineIter::new(text, 10);
+        iter.set_break_mode(BreakMode::Uax14);
 
         let s = iter.next().unwrap();
-        assert_eq!(s, text[0..20]);
+        assert_eq!(s, "state-of-");
 
         let s = iter.next().unwrap();
-        assert_eq!(s, text[20..]);
+        assert_eq!(s, "the-art");
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "design");
 
         let opt = iter.next();
         assert!(opt.is_none());
     }
 
     #[test]
-    fn test_set_indent() {
-        let text = "12345678901234567890abcdefghij";
+    fn test_set_break_on_hyphen() {
+        let text = "state-of-the-art design";
         let mut iter = LineIter::new(text, 10);
+        assert_eq!(iter.next().unwrap(), "state-of-t");
+        assert_eq!(iter.next().unwrap(), "he-art");
+        assert_eq!(iter.next().unwrap(), "design");
+        assert!(iter.next().is_none());
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, text[0..10]);
+        let mut iter = LineIter::new(text, 10);
+        iter.set_break_on_hyphen(true);
+        assert_eq!(iter.next().unwrap(), "state-of-");
+        assert_eq!(iter.next().unwrap(), "the-art");
+        assert_eq!(iter.next().unwrap(), "design");
+        assert!(iter.next().is_none());
+    }
 
-        iter.set_indent("   ");
+    #[test]
+    fn test_set_break_on_hyphen_ignores_hyphens_not_in_word() {
+        let text = "abc -def ghi- jkl --mno";
+        let mut iter = LineIter::new(text, 6);
+        iter.set_break_on_hyphen(true);
+        assert_eq!(iter.next().unwrap(), "abc");
+        assert_eq!(iter.next().unwrap(), "-def");
+        assert_eq!(iter.next().unwrap(), "ghi-");
+        assert_eq!(iter.next().unwrap(), "jkl");
+        assert_eq!(iter.next().unwrap(), "--mno");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(10).break_on_hyphen(true);
+        let mut iter = LineIter::with_options("see my-file.txt", &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "see my-");
+        assert_eq!(iter.next().unwrap(), "file.txt");
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_set_quote_aware() {
+        let text = "echo \"abc'def\" ghi 'jkl";
+        let mut iter = LineIter::new(text, 6);
+        assert_eq!(iter.next().unwrap(), "echo");
+        assert_eq!(iter.next().unwrap(), "\"a");
+        assert_eq!(iter.next().unwrap(), "bc'def");
+        assert_eq!(iter.next().unwrap(), "\" ghi");
+        assert_eq!(iter.next().unwrap(), "'jkl");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new(text, 6);
+        iter.set_quote_aware(false);
+        assert_eq!(iter.next().unwrap(), "echo");
+        assert_eq!(iter.next().unwrap(), "\"abc'd");
+        assert_eq!(iter.next().unwrap(), "ef\"");
+        assert_eq!(iter.next().unwrap(), "ghi");
+        assert_eq!(iter.next().unwrap(), "'jkl");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(10).quote_aware(false);
+        let iter = LineIter::with_options("x=\"a b\" y='c", &opts).unwrap();
+        assert_eq!(iter.collect::<Vec<_>>(), ["x=\"a b\"", "y='c"]);
+    }
+
+    #[test]
+    fn test_break_korean_at_spaces() {
+        let text = "대한민국의 수도는 서울입니다.";
+        let mut iter = LineIter::new(text, 12);
+        assert_eq!(iter.next().unwrap(), "대한민국의");
+        assert_eq!(iter.next().unwrap(), "수도는");
+        assert_eq!(iter.next().unwrap(), "서울입니다.");
+        assert!(iter.next().is_none());
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "   ".to_string() + &text[10..17]);
+        let mut iter = LineIter::new(text, 12);
+        iter.set_break_hangul(true);
+        assert_eq!(iter.next().unwrap(), "대한민국의");
+        assert_eq!(iter.next().unwrap(), "수도는 서울");
+        assert_eq!(iter.next().unwrap(), "입니다.");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(12).break_hangul(true);
+        let iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(
+            iter.collect::<Vec<_>>(),
+            ["대한민국의", "수도는 서울", "입니다."]
+        );
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "   ".to_string() + &text[17..24]);
+    #[test]
+    fn test_set_url_break_normal() {
+        let text = "see https://example.com/a/b";
+        let mut iter = LineIter::new(text, 16);
+        assert_eq!(iter.next().unwrap(), "see https://");
+        assert_eq!(iter.next().unwrap(), "example.com/a/b");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "   ".to_string() + &text[24..]);
+    #[test]
+    fn test_set_url_break_keep() {
+        let text = "see https://example.com/a/b now";
+        let mut iter = LineIter::new(text, 30);
+        iter.set_url_break(UrlBreak::Keep);
+        assert_eq!(iter.next().unwrap(), "see https://example.com/a/b");
+        assert_eq!(iter.next().unwrap(), "now");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new(text, 24);
+        iter.set_url_break(UrlBreak::Keep);
+        assert_eq!(iter.next().unwrap(), "see");
+        assert_eq!(iter.next().unwrap(), "https://example.com/a/b");
+        assert_eq!(iter.next().unwrap(), "now");
+        assert!(iter.next().is_none());
+
+        let text = "https://example.com/a/b
+(/usr/local/bin).";
+        let mut iter = LineIter::new(text, 10);
+        iter.set_url_break(UrlBreak::Keep);
+        assert_eq!(iter.next().unwrap(), "https://example.com/a/b");
+        assert_eq!(iter.next().unwrap(), "(");
+        assert_eq!(iter.next().unwrap(), "/usr/local/bin).");
+        assert!(iter.next().is_none());
+    }
 
-        let opt = iter.next();
-        assert!(opt.is_none());
+    #[test]
+    fn test_set_url_break_at_delimiters() {
+        let text = "see https://example.com/a?b=c&d=e";
+        let mut iter = LineIter::new(text, 10);
+        iter.set_url_break(UrlBreak::AtDelimiters);
+        assert_eq!(iter.next().unwrap(), "see");
+        assert_eq!(iter.next().unwrap(), "https://ex");
+        assert_eq!(iter.next().unwrap(), "ample.com/");
+        assert_eq!(iter.next().unwrap(), "a?b=c&d=e");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(12).url_break(UrlBreak::AtDelimiters);
+        let mut iter = LineIter::with_options("in ./src/line_iter.rs", &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "in ./src/");
+        assert_eq!(iter.next().unwrap(), "line_iter.rs");
+        assert!(iter.next().is_none());
     }
 
     #[test]
-    fn test_break_position_after_indent_width_is_increased() {
-        let line_width = 30;
-        let indent = " ".repeat(7);
-        let indent_width = crate::text_width(&indent);
-        let text = "aaaaa ".to_string()
-            + &"b".repeat(line_width - indent_width)
-            + &"c".repeat(line_width - indent_width)
-            + "ddd";
+    fn test_set_overflow_policy_overflow() {
+        let text = "abc defghijklmn, opq rstuvwxyz";
+        let mut iter = LineIter::new(text, 6);
+        iter.set_overflow_policy(OverflowPolicy::Overflow);
+        assert_eq!(iter.next().unwrap(), "abc");
+        assert_eq!(iter.next().unwrap(), "defghijklmn,");
+        assert_eq!(iter.next().unwrap(), "opq");
+        assert_eq!(iter.next().unwrap(), "rstuvwxyz");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(6).overflow_policy(OverflowPolicy::Overflow);
+        let mut iter = LineIter::with_options(
+            "あいうえお
+かき",
+            &opts,
+        )
+        .unwrap();
+        assert_eq!(iter.next().unwrap(), "あいう");
+        assert_eq!(iter.next().unwrap(), "えお");
+        assert_eq!(iter.next().unwrap(), "かき");
+        assert!(iter.next().is_none());
+    }
 
-        let mut iter = LineIter::new(&text, line_width);
-        let line = iter.next().unwrap();
-        assert_eq!(line, "aaaaa");
-        assert_eq!(crate::text_width(&line), 5);
+    struct Splitter<F: Fn(&str, usize) -> Option<usize>>(F);
 
-        iter.set_indent(&indent);
+    impl<F: Fn(&str, usize) -> Option<usize>> Hyphenator for Splitter<F> {
+        fn hyphenate(&self, _word: &str) -> Vec<usize> {
+            Vec::new()
+        }
 
-        let line = iter.next().unwrap();
-        assert_eq!(line, " ".repeat(7) + &"b".repeat(line_width - indent_width));
-        assert_eq!(crate::text_width(&line), line_width);
+        fn split(&self, word: &str, width: usize) -> Option<usize> {
+            (self.0)(word, width)
+        }
+    }
 
-        let line = iter.next().unwrap();
-        assert_eq!(line, " ".repeat(7) + &"c".repeat(line_width - indent_width));
-        assert_eq!(crate::text_width(&line), line_width);
+    #[test]
+    fn test_set_long_word_policy_split() {
+        let splitter = Splitter(|word: &str, _: usize| word.find('_').map(|i| i + 1));
+        let mut iter = LineIter::new("abc long_identifier_name", 10);
+        iter.set_hyphenator(&splitter);
+        iter.set_long_word_policy(LongWordPolicy::Split);
+        assert_eq!(iter.next().unwrap(), "abc");
+        assert_eq!(iter.next().unwrap(), "long_");
+        assert_eq!(iter.next().unwrap(), "identifier");
+        assert_eq!(iter.next().unwrap(), "_name");
+        assert!(iter.next().is_none());
+
+        // falls back to breaking at the end of the line for invalid positions.
+        let splitter = Splitter(|_: &str, _: usize| Some(20));
+        let mut iter = LineIter::new("abcdefghijkl", 5);
+        iter.set_hyphenator(&splitter);
+        iter.set_long_word_policy(LongWordPolicy::Split);
+        assert_eq!(iter.next().unwrap(), "abcde");
+        assert_eq!(iter.next().unwrap(), "fghij");
+        assert_eq!(iter.next().unwrap(), "kl");
+        assert!(iter.next().is_none());
+
+        // falls back to breaking at the end of the line without a hyphenator.
+        let opts = Options::new(5).long_word_policy(LongWordPolicy::Split);
+        let iter = LineIter::with_options("abcdefghijkl", &opts).unwrap();
+        assert_eq!(iter.collect::<Vec<_>>(), ["abcde", "fghij", "kl"]);
+    }
 
-        let line = iter.next().unwrap();
-        assert_eq!(line, " ".repeat(7) + "ddd");
-        assert_eq!(crate::text_width(&line), 10);
+    #[test]
+    fn test_set_long_word_policy_identifier() {
+        let mut iter = LineIter::new("at parseHttpResponseHeaderValue2", 12);
+        iter.set_long_word_policy(LongWordPolicy::Identifier);
+        assert_eq!(iter.next().unwrap(), "at");
+        assert_eq!(iter.next().unwrap(), "parseHttp");
+        assert_eq!(iter.next().unwrap(), "Response");
+        assert_eq!(iter.next().unwrap(), "HeaderValue2");
+        assert!(iter.next().is_none());
+
+        iter.init("see very_long_snake_case_identifier_name");
+        assert_eq!(iter.next().unwrap(), "see");
+        assert_eq!(iter.next().unwrap(), "very_long_");
+        assert_eq!(iter.next().unwrap(), "snake_case_");
+        assert_eq!(iter.next().unwrap(), "identifier_");
+        assert_eq!(iter.next().unwrap(), "name");
+        assert!(iter.next().is_none());
+
+        // falls back to breaking at the end of the line without boundaries.
+        let mut iter = LineIter::new("x abcdefghijklmnop", 6);
+        iter.set_long_word_policy(LongWordPolicy::Identifier);
+        assert_eq!(iter.next().unwrap(), "x");
+        assert_eq!(iter.next().unwrap(), "abcdef");
+        assert_eq!(iter.next().unwrap(), "ghijkl");
+        assert_eq!(iter.next().unwrap(), "mnop");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(8).long_word_policy(LongWordPolicy::Identifier);
+        let mut iter = LineIter::with_options("getUserName", &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "getUser");
+        assert_eq!(iter.next().unwrap(), "Name");
+        assert!(iter.next().is_none());
+    }
 
-        let line = iter.next();
-        assert_eq!(line, None);
+    #[test]
+    fn test_next_with_range() {
+        let text = "句読点は、行頭に置く。\nabc";
+        let mut iter = LineIter::new(text, 8);
+        let (line, range) = iter.next_with_range().unwrap();
+        assert_eq!(line, "句読点");
+        assert_eq!(&text[range], "句読点");
+        let (line, range) = iter.next_with_range().unwrap();
+        assert_eq!(line, "は、行頭");
+        assert_eq!(&text[range], "は、行頭");
+        let (line, range) = iter.next_with_range().unwrap();
+        assert_eq!(line, "に置く。");
+        assert_eq!(&text[range], "に置く。");
+        let (line, range) = iter.next_with_range().unwrap();
+        assert_eq!(line, "abc");
+        assert_eq!(range, 34..37);
+        assert!(iter.next_with_range().is_none());
     }
 
     #[test]
-    fn test_break_position_if_indent_contains_full_width_chars() {
-        let line_width = 30;
-        let indent = "__ああ__".to_string(); // width is 8.
-        let indent_width = crate::text_width(&indent);
-        let text = "aaaaa ".to_string()
-            + &"b".repeat(line_width - indent_width)
-            + &"c".repeat(line_width - indent_width)
-            + "ddd";
+    fn test_next_with_range_of_reflowed_lines() {
+        let text = "abc\n  def\tghi jkl\n";
+        let opts = Options::new(12).paragraph_mode(true).tab_width(4);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.next_with_range(), Some(("abc def ghi".into(), 0..13)));
+        assert_eq!(iter.next_with_range(), Some(("jkl".into(), 14..17)));
+        assert_eq!(iter.next_with_range(), Some(("".into(), 18..18)));
+        assert!(iter.next_with_range().is_none());
+
+        iter.init("abcdefgh");
+        iter.set_width(5);
+        assert_eq!(iter.next_with_range(), Some(("abcde".into(), 0..5)));
+        assert_eq!(iter.next_with_range(), Some(("fgh".into(), 5..8)));
+        assert!(iter.next_with_range().is_none());
+    }
 
-        let mut iter = LineIter::new(&text, line_width);
-        let line = iter.next().unwrap();
-        assert_eq!(line, "aaaaa");
-        assert_eq!(crate::text_width(&line), 5);
+    #[test]
+    fn test_next_line() {
+        let opts = Options::new(10).first_indent("* ").indent("  ").suffix("|");
+        let mut iter = LineIter::with_options("あいうえお\n\nabc", &opts).unwrap();
+
+        let line = iter.next_line().unwrap();
+        assert_eq!(line.text, "* あいう |");
+        assert_eq!(line.width, 10);
+        assert_eq!(line.range, 0..9);
+        assert_eq!(line.indent, "* ");
+        assert!(!line.is_forced);
+        assert!(!line.is_hard_break);
+
+        let line = iter.next_line().unwrap();
+        assert_eq!(line.text, "  えお   |");
+        assert_eq!(line.indent, "  ");
+        assert!(line.is_hard_break);
+
+        let line = iter.next_line().unwrap();
+        assert_eq!(line.text, "         |");
+        assert_eq!(line.range, 16..16);
+        assert_eq!(line.indent, "");
+        assert!(line.is_hard_break);
+
+        let line = iter.next_line().unwrap();
+        assert_eq!(line.text, "* abc    |");
+        assert!(!line.is_forced);
+        assert!(!line.is_hard_break);
+
+        assert!(iter.next_line().is_none());
+    }
 
-        iter.set_indent(&indent);
+    #[test]
+    fn test_lines_borrow_text() {
+        let text = "abc def\tghi\njkl";
+        let mut iter = LineIter::new(text, 8);
+        assert!(matches!(iter.next(), Some(Cow::Borrowed("abc def"))));
+        assert!(matches!(iter.next(), Some(Cow::Borrowed("ghi"))));
+        assert!(matches!(iter.next(), Some(Cow::Borrowed("jkl"))));
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(8).tab_width(4);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert!(matches!(iter.next(), Some(Cow::Borrowed("abc def"))));
+        iter.set_indent(" ");
+        assert!(matches!(iter.next(), Some(Cow::Owned(s)) if s == " ghi"));
+        iter.set_indent("");
+        assert!(matches!(iter.next(), Some(Cow::Borrowed("jkl"))));
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::with_options("ab\tc d", &opts).unwrap();
+        assert!(matches!(iter.next(), Some(Cow::Owned(s)) if s == "ab  c d"));
+        assert!(iter.next().is_none());
+    }
 
-        let line = iter.next().unwrap();
-        assert_eq!(
-            line,
-            "__ああ__".to_string() + &"b".repeat(line_width - indent_width)
-        );
-        assert_eq!(crate::text_width(&line), line_width);
+    #[test]
+    fn test_width_of_emoji() {
+        let text = "ab \u{2764}\u{FE0F} cd 😀😀 ef";
+        let mut iter = LineIter::new(text, 4);
+        assert_eq!(iter.next().unwrap(), "ab");
+        assert_eq!(iter.next().unwrap(), "\u{2764}\u{FE0F}");
+        assert_eq!(iter.next().unwrap(), "cd");
+        assert_eq!(iter.next().unwrap(), "😀😀");
+        assert_eq!(iter.next().unwrap(), "ef");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new(text, 5);
+        assert_eq!(iter.next().unwrap(), "ab \u{2764}\u{FE0F}");
+        assert_eq!(iter.next().unwrap(), "cd 😀");
+        assert_eq!(iter.next().unwrap(), "😀 ef");
+        assert!(iter.next().is_none());
+    }
 
-        let line = iter.next().unwrap();
-        assert_eq!(
-            line,
-            "__ああ__".to_string() + &"c".repeat(line_width - indent_width)
-        );
-        assert_eq!(crate::text_width(&line), line_width);
+    #[test]
+    fn test_regional_indicator_pairs() {
+        let text = "ab🇯🇵🇺🇸🇫 cd";
+        let mut iter = LineIter::new(text, 5);
+        assert_eq!(iter.next().unwrap(), "ab🇯🇵");
+        assert_eq!(iter.next().unwrap(), "🇺🇸🇫");
+        assert_eq!(iter.next().unwrap(), "cd");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new(text, 3);
+        assert_eq!(iter.next().unwrap(), "ab");
+        assert_eq!(iter.next().unwrap(), "🇯🇵");
+        assert_eq!(iter.next().unwrap(), "🇺🇸");
+        assert_eq!(iter.next().unwrap(), "🇫");
+        assert_eq!(iter.next().unwrap(), "cd");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new(text, 5);
+        iter.set_break_mode(BreakMode::Uax14);
+        assert_eq!(iter.next().unwrap(), "ab🇯🇵");
+        assert_eq!(iter.next().unwrap(), "🇺🇸🇫");
+        assert_eq!(iter.next().unwrap(), "cd");
+        assert!(iter.next().is_none());
+    }
 
-        let line = iter.next().unwrap();
-        assert_eq!(line, "__ああ__ddd");
-        assert_eq!(crate::text_width(&line), 11);
+    #[test]
+    fn test_keep_zero_width_chars() {
+        let text = "ne\u{0301}e\u{0301} a\u{20DD}b\u{0300}c";
+        let mut iter = LineIter::new(text, 3);
+        assert!(matches!(
+            iter.next(),
+            Some(Cow::Borrowed("ne\u{0301}e\u{0301}"))
+        ));
+        assert!(matches!(
+            iter.next(),
+            Some(Cow::Borrowed("a\u{20DD}b\u{0300}c"))
+        ));
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(3).keep_zero_width(false);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "nee");
+        assert_eq!(iter.next().unwrap(), "abc");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new("\u{2764}\u{FE0F}\u{FE0F}", 3);
+        assert_eq!(iter.next().unwrap(), "\u{2764}\u{FE0F}\u{FE0F}");
+        assert!(iter.next().is_none());
+    }
 
-        let line = iter.next();
-        assert_eq!(line, None);
+    #[test]
+    fn test_crlf_as_one_line_break() {
+        let text = "abc\r\ndef\rghi\n\r\njkl";
+        let mut iter = LineIter::new(text, 10);
+        assert_eq!(iter.next_with_range(), Some(("abc".into(), 0..3)));
+        assert_eq!(iter.next_with_range(), Some(("def".into(), 5..8)));
+        assert_eq!(iter.next_with_range(), Some(("ghi".into(), 9..12)));
+        assert_eq!(iter.next().unwrap(), "");
+        assert_eq!(iter.next_with_range(), Some(("jkl".into(), 15..18)));
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(10).strict_line_breaks(true);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "abc");
+        assert_eq!(iter.next().unwrap(), "");
+        assert_eq!(iter.next().unwrap(), "def");
+        assert_eq!(iter.next().unwrap(), "ghi");
+        assert_eq!(iter.next().unwrap(), "");
+        assert_eq!(iter.next().unwrap(), "");
+        assert_eq!(iter.next().unwrap(), "jkl");
+        assert!(iter.next().is_none());
     }
 
     #[test]
-    fn test_init() {
-        let text = "12345678901234567890";
-        let mut iter = LineIter::new(text, 12);
+    fn test_crlf_in_paragraph_mode() {
+        let mut iter = LineIter::new("abc\r\ndef\r\n\r\nghi", 10);
+        iter.set_paragraph_mode(true);
+        assert_eq!(iter.next().unwrap(), "abc def");
+        assert_eq!(iter.next().unwrap(), "");
+        assert_eq!(iter.next().unwrap(), "ghi");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, text[0..12]);
+    #[test]
+    fn test_line_and_paragraph_separators() {
+        let text = "abc\u{2028}def\u{2029}ghi";
+        let mut iter = LineIter::new(text, 10);
+        iter.set_first_indent("* ");
+        iter.set_indent("  ");
+        let line = iter.next_line().unwrap();
+        assert_eq!(line.text, "* abc");
+        assert!(line.is_hard_break);
+        assert_eq!(iter.next().unwrap(), "  def");
+        assert_eq!(iter.next().unwrap(), "* ghi");
+        assert!(iter.next().is_none());
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, text[12..]);
+        let mut iter = LineIter::new(text, 10);
+        iter.set_break_mode(BreakMode::Uax14);
+        assert_eq!(iter.next().unwrap(), "abc");
+        assert_eq!(iter.next().unwrap(), "def");
+        assert_eq!(iter.next().unwrap(), "ghi");
+        assert!(iter.next().is_none());
+    }
 
-        let opt = iter.next();
-        assert!(opt.is_none());
+    #[test]
+    fn test_paragraph_separator_in_paragraph_mode() {
+        let text = "abc\ndef\u{2028}ghi\u{2029}jkl\nmno";
+        let mut iter = LineIter::new(text, 10);
+        iter.set_paragraph_mode(true);
+        assert_eq!(iter.next().unwrap(), "abc def");
+        assert_eq!(iter.next().unwrap(), "ghi");
+        assert_eq!(iter.next_with_range(), Some(("".into(), 13..13)));
+        assert_eq!(iter.next().unwrap(), "jkl mno");
+        assert!(iter.next().is_none());
+    }
 
-        let text = "abcdefghijklmnopqrstuvwxyz";
-        iter.init(text);
+    #[test]
+    fn test_form_feed() {
+        let text = "abc\u{000C}def\r\n\u{000C}\r\nghi";
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, text[0..12]);
+        let mut iter = LineIter::new(text, 10);
+        assert_eq!(iter.next().unwrap(), "abcdef");
+        assert_eq!(iter.next().unwrap(), "");
+        assert_eq!(iter.next().unwrap(), "ghi");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(10).form_feed(FormFeed::Emit);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.next_with_range(), Some(("abc".into(), 0..3)));
+        assert_eq!(iter.next_with_range(), Some(("\u{000C}".into(), 3..4)));
+        assert_eq!(iter.next_with_range(), Some(("def".into(), 4..7)));
+        assert_eq!(iter.next_with_range(), Some(("\u{000C}".into(), 9..10)));
+        assert_eq!(iter.next_with_range(), Some(("ghi".into(), 12..15)));
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new("\u{000C}abc\u{000C}def", 10);
+        iter.set_form_feed(FormFeed::Break);
+        iter.set_first_indent("* ");
+        assert_eq!(iter.next().unwrap(), "* abc");
+        assert_eq!(iter.next().unwrap(), "* def");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, text[12..24]);
+    #[test]
+    fn test_preserve_leading_whitespace() {
+        let text = "  abc def\n\n    ghi\n \njkl";
+        let opts = Options::new(8).preserve_leading_whitespace(true);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert!(matches!(iter.next(), Some(Cow::Borrowed("  abc"))));
+        assert_eq!(iter.next().unwrap(), "def");
+        assert_eq!(iter.next().unwrap(), "");
+        assert_eq!(iter.next_with_range(), Some(("    ghi".into(), 11..18)));
+        assert_eq!(iter.next().unwrap(), "");
+        assert_eq!(iter.next().unwrap(), "jkl");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(10)
+            .preserve_leading_whitespace(true)
+            .tab_width(4)
+            .indent("> ");
+        let mut iter = LineIter::with_options("abc\n\tdef", &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "> abc");
+        assert_eq!(iter.next().unwrap(), ">     def");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, text[24..]);
+    #[test]
+    fn test_auto_indent() {
+        let text = "abc def ghi\n\tjkl mno pqr\n  stu";
+        let opts = Options::new(10).tab_width(4).auto_indent(true);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert!(matches!(iter.next(), Some(Cow::Borrowed("abc def"))));
+        assert_eq!(iter.next().unwrap(), "ghi");
+        assert_eq!(iter.next().unwrap(), "    jkl");
+        assert_eq!(iter.next().unwrap(), "    mno");
+        assert_eq!(iter.next().unwrap(), "    pqr");
+        assert_eq!(iter.next().unwrap(), "  stu");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(12)
+            .indent("> ")
+            .auto_indent(true)
+            .auto_indent_hang(1)
+            .paragraph_mode(true);
+        let text = "  abc def\nghi jkl\n\n       mno pqr stu";
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), ">   abc def");
+        assert_eq!(iter.next().unwrap(), ">    ghi jkl");
+        assert_eq!(iter.next().unwrap(), "");
+        assert_eq!(iter.next().unwrap(), ">        mno");
+        assert_eq!(iter.next().unwrap(), "> pqr stu");
+        assert!(iter.next().is_none());
+    }
 
-        let opt = iter.next();
-        assert!(opt.is_none());
+    #[test]
+    fn test_list_items() {
+        let text = "intro text here\n- abc def ghi jkl\n10) mno pqr stu\nvwx";
+        let opts = Options::new(10).list_items(true);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "intro text");
+        assert_eq!(iter.next().unwrap(), "here");
+        assert_eq!(iter.next().unwrap(), "- abc def");
+        assert_eq!(iter.next().unwrap(), "  ghi jkl");
+        assert_eq!(iter.next().unwrap(), "10) mno");
+        assert_eq!(iter.next().unwrap(), "    pqr");
+        assert_eq!(iter.next().unwrap(), "    stu");
+        assert_eq!(iter.next().unwrap(), "vwx");
+        assert!(iter.next().is_none());
+
+        let text = "* abc def\nghi jkl\n\n* mno";
+        let opts = Options::new(8)
+            .list_items(true)
+            .paragraph_mode(true)
+            .auto_indent_hang(1);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "* abc");
+        assert_eq!(iter.next().unwrap(), "   def");
+        assert_eq!(iter.next().unwrap(), "   ghi");
+        assert_eq!(iter.next().unwrap(), "   jkl");
+        assert_eq!(iter.next().unwrap(), "");
+        assert_eq!(iter.next().unwrap(), "* mno");
+        assert!(iter.next().is_none());
     }
 
-    // This text is quoted from https://go.dev/doc/
-    const LONG_TEXT: &'static str = r#"The Go programming language is an open source project to make programmers more productive.
+    #[cfg(feature = "bidi")]
+    #[test]
+    fn test_bidi() {
+        let text = "abc de \u{05D0}\u{05D1} \u{05D2}\u{05D3} fg\n\u{05D4} 12 \u{05D5}";
+        let opts = Options::new(10).bidi(true);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "abc de");
+        assert_eq!(iter.next().unwrap(), "\u{05D3}\u{05D2} \u{05D1}\u{05D0} fg");
+        assert_eq!(iter.next().unwrap(), "\u{05D5} 12 \u{05D4}");
+        assert!(iter.next().is_none());
 
-Go is expressive, concise, clean, and efficient. Its concurrency mechanisms make it easy to write programs that get the most out of multicore and networked machines, while its novel type system enables flexible and modular program construction. Go compiles quickly to machine code yet has the convenience of garbage collection and the power of run-time reflection. It's a fast, statically typed, compiled language that feels like a dynamically typed, interpreted language.    "#;
+        let mut iter = LineIter::new(text, 10);
+        assert_eq!(iter.next().unwrap(), "abc de \u{05D0}\u{05D1}");
+        assert_eq!(iter.next().unwrap(), "\u{05D2}\u{05D3} fg");
+        assert_eq!(iter.next().unwrap(), "\u{05D4} 12 \u{05D5}");
+        assert!(iter.next().is_none());
+    }
 
+    #[cfg(feature = "bidi")]
     #[test]
-    fn test_try_long_text() {
-        let mut iter = LineIter::new(LONG_TEXT, 20);
+    fn test_bidi_breaks_run_wider_than_line() {
+        let text = "x \u{05D0}\u{05D1} \u{05D2}\u{05D3} \u{05D4}\u{05D5}";
+        let mut iter = LineIter::new(text, 5);
+        iter.set_bidi(true);
+        assert_eq!(iter.next().unwrap(), "x \u{05D1}\u{05D0}");
+        assert_eq!(iter.next().unwrap(), "\u{05D5}\u{05D4} \u{05D3}\u{05D2}");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "The Go programming");
+    #[test]
+    fn test_width_provider() {
+        // counts ambiguous characters as narrow.
+        let provider = |ch: char| match ch {
+            '\u{00B1}' => 1,
+            _ => crate::char_width(ch),
+        };
+        let text = "a\u{00B1}b c\u{00B1}d e";
+        let mut iter = LineIter::new(text, 9);
+        iter.set_prefix("\u{00B1} ");
+        iter.set_width_provider(&provider);
+        assert_eq!(iter.next().unwrap(), "\u{00B1} a\u{00B1}b c\u{00B1}d");
+        assert_eq!(iter.next().unwrap(), "\u{00B1} e");
+        assert!(iter.next().is_none());
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "language is an open");
+        let mut iter = LineIter::new(text, 9);
+        iter.set_prefix("\u{00B1} ");
+        assert_eq!(iter.next().unwrap(), "\u{00B1} a\u{00B1}b");
+        assert_eq!(iter.next().unwrap(), "\u{00B1} c\u{00B1}d e");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(9).prefix("\u{00B1} ").width_provider(provider);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "\u{00B1} a\u{00B1}b c\u{00B1}d");
+        assert_eq!(iter.next().unwrap(), "\u{00B1} e");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "source project to");
+    #[test]
+    fn test_indent_wider_than_line_width() {
+        let mut iter = LineIter::new("ab c", 4);
+        iter.set_indent("    ");
+        assert_eq!(iter.next().unwrap(), "    a");
+        assert_eq!(iter.next().unwrap(), "    b");
+        assert_eq!(iter.next().unwrap(), "    c");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new("ab", 4);
+        iter.set_prefix("| ");
+        iter.set_suffix(" |");
+        iter.set_indent("      ");
+        assert_eq!(iter.next().unwrap(), "|       a |");
+        assert_eq!(iter.next().unwrap(), "|       b |");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "make programmers");
+    #[test]
+    fn test_zero_width() {
+        let mut iter = LineIter::new("ab", 0);
+        assert_eq!(iter.next().unwrap(), "a");
+        assert_eq!(iter.next().unwrap(), "b");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "more productive.");
+    #[test]
+    fn test_overflow_policy_overflow() {
+        let opts = Options::new(6).overflow_policy(OverflowPolicy::Overflow);
+        let mut iter = LineIter::with_options("abc defghijkl mn", &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "abc");
+        assert_eq!(iter.next().unwrap(), "defghijkl");
+        assert_eq!(iter.next().unwrap(), "mn");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "");
+    #[test]
+    fn test_overflow_policy_error() {
+        let opts = Options::new(6).overflow_policy(OverflowPolicy::Error);
+        let mut iter = LineIter::with_options("abc de\nfghijklmn", &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "abc de");
+        assert!(iter.overflow_error().is_none());
+        assert!(iter.next().is_none());
+        assert_eq!(
+            iter.overflow_error(),
+            Some(OverflowError {
+                offset: 7,
+                width: 6
+            })
+        );
+        assert!(iter.next().is_none());
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "Go is expressive,");
+        iter.init("abc");
+        assert!(iter.overflow_error().is_none());
+        assert_eq!(iter.next().unwrap(), "abc");
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "concise, clean, and");
+    #[test]
+    fn test_overflow_policy_error_for_wide_char() {
+        let mut iter = LineIter::new("a \u{3042}", 1);
+        iter.set_overflow_policy(OverflowPolicy::Error);
+        assert_eq!(iter.next().unwrap(), "a");
+        assert!(iter.next().is_none());
+        assert_eq!(
+            iter.overflow_error(),
+            Some(OverflowError {
+                offset: 2,
+                width: 1
+            })
+        );
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "efficient. Its");
+        let mut iter = LineIter::new("a \u{3042}", 1);
+        assert_eq!(iter.next().unwrap(), "a");
+        assert_eq!(iter.next().unwrap(), "\u{3042}");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "concurrency");
+    #[test]
+    fn test_overflow_policy_error_for_url() {
+        let opts = Options::new(10)
+            .url_break(UrlBreak::Keep)
+            .overflow_policy(OverflowPolicy::Error);
+        let mut iter = LineIter::with_options("see https://example.com ok", &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "see");
+        assert!(iter.next().is_none());
+        assert_eq!(
+            iter.overflow_error(),
+            Some(OverflowError {
+                offset: 4,
+                width: 10
+            })
+        );
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "mechanisms make it");
+    #[test]
+    fn test_set_line_numbers() {
+        let mut iter = LineIter::new("abc def ghi jkl mno pqr stu vwx yz", 6);
+        iter.set_line_numbers(1);
+        iter.set_prefix("|");
+        let lines: Vec<_> = iter.by_ref().collect();
+        assert_eq!(
+            lines,
+            [
+                "1 |abc", "2 |def", "3 |ghi", "4 |jkl", "5 |mno", "6 |pqr", "7 |stu", "8 |vwx",
+                "9 |yz"
+            ]
+        );
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "easy to write");
+        iter.init("abc\n\nd");
+        iter.set_line_numbers(3);
+        assert_eq!(iter.next().unwrap(), "  1 |a");
+        assert_eq!(iter.next().unwrap(), "  2 |b");
+        assert_eq!(iter.next().unwrap(), "  3 |c");
+        assert_eq!(iter.next().unwrap(), "  4 |");
+        assert_eq!(iter.next().unwrap(), "  5 |d");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "programs that get");
+    #[test]
+    fn test_set_gutter() {
+        let gutter = |n: usize| {
+            if n % 2 == 0 {
+                "#".to_string()
+            } else {
+                "++".to_string()
+            }
+        };
+        let mut iter = LineIter::new("abc def ghi", 5);
+        iter.set_line_numbers(4);
+        iter.set_gutter(2, &gutter);
+        assert_eq!(iter.next().unwrap(), "++abc");
+        assert_eq!(iter.next().unwrap(), "# def");
+        assert_eq!(iter.next().unwrap(), "++ghi");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "the most out of");
+    #[test]
+    fn test_set_max_lines() {
+        let text = "abc def ghi jkl mno";
+        let mut iter = LineIter::new(text, 7);
+        iter.set_max_lines(2);
+        assert_eq!(iter.next().unwrap(), "abc def");
+        assert_eq!(iter.next().unwrap(), "ghi j…");
+        assert!(iter.next().is_none());
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "multicore and");
+        iter.init(text);
+        iter.set_max_lines(3);
+        assert_eq!(iter.next().unwrap(), "abc def");
+        assert_eq!(iter.next().unwrap(), "ghi jkl");
+        assert_eq!(iter.next().unwrap(), "mno");
+        assert!(iter.next().is_none());
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "networked machines,");
+        iter.init(text);
+        iter.set_max_lines(1);
+        iter.set_placeholder("...");
+        iter.set_indent("> ");
+        assert_eq!(iter.next().unwrap(), "> ab...");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "while its novel type");
+    #[test]
+    fn test_set_max_lines_with_wide_chars() {
+        let mut iter = LineIter::new("句読点は、行頭に置くことは禁止である。", 8);
+        iter.set_max_lines(2);
+        assert_eq!(iter.next().unwrap(), "句読点");
+        assert_eq!(iter.next().unwrap(), "は、行…");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "system enables");
+    #[test]
+    fn test_peek() {
+        let mut iter = LineIter::new("abc defghijkl\nmno", 6);
+        iter.set_indent("> ");
+        assert_eq!(iter.peek().unwrap(), "> abc");
+        let line = iter.next_line().unwrap();
+        assert_eq!(line.text, "> abc");
+        assert_eq!(line.range, 0..3);
+
+        assert_eq!(iter.peek().unwrap(), "> defg");
+        iter.set_indent("");
+        let line = iter.next_line().unwrap();
+        assert_eq!(line.text, "> defg");
+        assert_eq!(line.range, 4..8);
+        assert_eq!(line.indent, "> ");
+        assert!(line.is_forced);
+
+        assert_eq!(iter.peek().unwrap(), "hijkl");
+        assert_eq!(iter.next_with_range(), Some(("hijkl".into(), 8..13)));
+        assert_eq!(iter.peek().unwrap(), "mno");
+        assert_eq!(iter.next().unwrap(), "mno");
+        assert!(iter.peek().is_none());
+        assert!(iter.next().is_none());
+
+        iter.init("pqr");
+        assert_eq!(iter.peek().unwrap(), "pqr");
+        iter.init("stu");
+        assert_eq!(iter.next().unwrap(), "stu");
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "flexible and modular");
+    #[test]
+    fn test_set_owned_indents() {
+        let mut iter = LineIter::new("abc def\nghi jkl", 6);
+        {
+            let first = String::from("* ");
+            iter.set_first_indent(first);
+            iter.set_indent(" ".repeat(2));
+        }
+        let line = iter.next_line().unwrap();
+        assert_eq!(line.text, "* abc");
+        assert_eq!(line.indent, "* ");
+        assert_eq!(iter.next().unwrap(), "  def");
+        assert_eq!(iter.next().unwrap(), "* ghi");
+        assert_eq!(iter.next().unwrap(), "  jkl");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "program");
+    #[test]
+    fn test_getters() {
+        let text = "abc def ghi\n\njkl";
+        let mut iter = LineIter::new(text, 8);
+        assert_eq!(iter.width(), 8);
+        assert_eq!(iter.indent(), "");
+        assert_eq!(iter.indent_width(), 0);
+        iter.set_indent("ああ");
+        assert_eq!(iter.indent(), "ああ");
+        assert_eq!(iter.indent_width(), 4);
+        iter.set_width(10);
+        assert_eq!(iter.width(), 10);
+
+        assert!(!iter.is_exhausted());
+        assert_eq!(iter.next().unwrap(), "ああabc");
+        assert_eq!(iter.consumed_bytes(), 4);
+        assert_eq!(iter.peek().unwrap(), "ああdef");
+        assert_eq!(iter.consumed_bytes(), 4);
+        assert_eq!(iter.next().unwrap(), "ああdef");
+        assert_eq!(iter.consumed_bytes(), 8);
+        assert_eq!(iter.next().unwrap(), "ああghi");
+        assert_eq!(iter.next().unwrap(), "");
+        assert_eq!(iter.consumed_bytes(), 13);
+        assert!(iter.peek().is_some());
+        assert!(!iter.is_exhausted());
+        assert_eq!(iter.next().unwrap(), "ああjkl");
+        assert!(iter.is_exhausted());
+        assert_eq!(iter.consumed_bytes(), text.len());
+        assert!(iter.peek().is_none());
+        assert!(iter.is_exhausted());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "construction. Go");
+    #[test]
+    fn test_skip_lines() {
+        let text = "abc def\n\nghi jkl mno\npqr";
+        let lines: Vec<_> = LineIter::new(text, 7).collect();
+        for n in 0..=lines.len() + 1 {
+            let mut iter = LineIter::new(text, 9);
+            iter.set_line_numbers(1);
+            assert_eq!(iter.skip_lines(n), n.min(lines.len()));
+            match lines.get(n) {
+                Some(line) => assert_eq!(iter.next().unwrap(), format!("{} {}", n + 1, line)),
+                None => assert!(iter.next().is_none()),
+            }
+        }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "compiles quickly to");
+        let mut iter = LineIter::new(text, 7);
+        assert_eq!(iter.peek().unwrap(), "abc def");
+        assert_eq!(iter.skip_lines(2), 2);
+        assert_eq!(iter.next().unwrap(), "ghi jkl");
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "machine code yet has");
+    #[test]
+    fn test_set_width_override() {
+        let mut iter = LineIter::new("\u{F101}\u{F101}\u{F101} abc", 4);
+        iter.set_width_override('\u{F101}', 1);
+        assert_eq!(iter.next().unwrap(), "\u{F101}\u{F101}\u{F101}");
+        assert_eq!(iter.next().unwrap(), "abc");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new("a\u{F101}b", 8);
+        iter.set_non_print_policy(NonPrintPolicy::EscapeUnicode);
+        iter.set_width_override('\u{F101}', 1);
+        assert_eq!(iter.next().unwrap(), "a\u{F101}b");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new("xx y", 4);
+        iter.set_width_override('x', 2);
+        assert_eq!(iter.next().unwrap(), "xx");
+        assert_eq!(iter.next().unwrap(), "y");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new("abc def", 8);
+        iter.set_prefix("\u{F101}");
+        iter.set_width_override('\u{F101}', 1);
+        assert_eq!(iter.next().unwrap(), "\u{F101}abc def");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "the convenience of");
+    #[test]
+    fn test_set_cjk() {
+        let mut iter = LineIter::new("abc ─ def", 6);
+        iter.set_prefix("○");
+        assert_eq!(iter.next().unwrap(), "○abc");
+        assert_eq!(iter.next().unwrap(), "○─");
+        assert_eq!(iter.next().unwrap(), "○def");
+        assert!(iter.next().is_none());
+
+        iter.init("abc ─ def");
+        iter.set_cjk(false);
+        assert_eq!(iter.next().unwrap(), "○abc ─");
+        assert_eq!(iter.next().unwrap(), "○def");
+        assert!(iter.next().is_none());
+
+        iter.set_cjk(true);
+        iter.init("abc ─ def");
+        assert_eq!(iter.next().unwrap(), "○abc");
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "garbage collection");
+    #[test]
+    fn test_typographic_quotes() {
+        let mut iter = LineIter::new("He said “Hello, world” and ‘bye’.", 11);
+        assert_eq!(iter.next().unwrap(), "He said");
+        assert_eq!(iter.next().unwrap(), "“Hello,");
+        assert_eq!(iter.next().unwrap(), "world” and");
+        assert_eq!(iter.next().unwrap(), "‘bye’.");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new("«Bonjour» ‹toi›", 9);
+        assert_eq!(iter.next().unwrap(), "«Bonjour»");
+        assert_eq!(iter.next().unwrap(), "‹toi›");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new("「あい」「うえ」", 8);
+        assert_eq!(iter.next().unwrap(), "「あい」");
+        assert_eq!(iter.next().unwrap(), "「うえ」");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "and the power of");
+    #[test]
+    fn test_add_quote_pair() {
+        let text = "abc |def ghi| jk";
+        let mut iter = LineIter::new(text, 7);
+        iter.add_quote_pair('|', '|');
+        assert_eq!(iter.next().unwrap(), "abc");
+        assert_eq!(iter.next().unwrap(), "|def");
+        assert_eq!(iter.next().unwrap(), "ghi| jk");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(8).quote_pair('⸢', '⸣');
+        let mut iter = LineIter::with_options("abcdef ⸢gh⸣", &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "abcdef");
+        assert_eq!(iter.next().unwrap(), "⸢gh⸣");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "run-time reflection.");
+    #[test]
+    fn test_apostrophes_in_contractions() {
+        let text = "don't say 'yes' or it's \"no\" ok";
+        let mut iter = LineIter::new(text, 8);
+        assert_eq!(iter.next().unwrap(), "don't");
+        assert_eq!(iter.next().unwrap(), "say");
+        assert_eq!(iter.next().unwrap(), "'yes' or");
+        assert_eq!(iter.next().unwrap(), "it's");
+        assert_eq!(iter.next().unwrap(), "\"no\" ok");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new("I can’t stop", 7);
+        assert_eq!(iter.next().unwrap(), "I");
+        assert_eq!(iter.next().unwrap(), "can’t");
+        assert_eq!(iter.next().unwrap(), "stop");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "It's a fast,");
+    #[test]
+    fn test_kinsoku_level() {
+        let text = "ミュージック";
+        let mut iter = LineIter::new(text, 4);
+        assert_eq!(iter.next().unwrap(), "ミュ");
+        assert_eq!(iter.next().unwrap(), "ー");
+        assert_eq!(iter.next().unwrap(), "ジッ");
+        assert_eq!(iter.next().unwrap(), "ク");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new(text, 4);
+        iter.set_kinsoku_level(KinsokuLevel::Normal);
+        assert_eq!(iter.next().unwrap(), "ミュ");
+        assert_eq!(iter.next().unwrap(), "ージ");
+        assert_eq!(iter.next().unwrap(), "ック");
+        assert!(iter.next().is_none());
+
+        let text = "東京々々ゝあいう";
+        let mut iter = LineIter::new(text, 4);
+        iter.set_kinsoku_level(KinsokuLevel::Normal);
+        assert_eq!(iter.next().unwrap(), "東");
+        assert_eq!(iter.next().unwrap(), "京々");
+        assert_eq!(iter.next().unwrap(), "々ゝ");
+        assert_eq!(iter.next().unwrap(), "あい");
+        assert_eq!(iter.next().unwrap(), "う");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(4).kinsoku_level(KinsokuLevel::Loose);
+        let iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.collect::<Vec<_>>(), ["東京", "々々", "ゝあ", "いう"]);
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "statically typed,");
+    #[test]
+    fn test_burasage() {
+        let text = "あいう、えおか。きく";
+        let mut iter = LineIter::new(text, 6);
+        iter.set_burasage(true);
+        assert_eq!(iter.next().unwrap(), "あいう、");
+        assert_eq!(iter.next().unwrap(), "えおか。");
+        assert_eq!(iter.next().unwrap(), "きく");
+        assert!(iter.next().is_none());
+
+        // only one character hangs.
+        let mut iter = LineIter::new("あいう。」えお", 6);
+        iter.set_burasage(true);
+        assert_eq!(iter.next().unwrap(), "あい");
+        assert_eq!(iter.next().unwrap(), "う。」");
+        assert_eq!(iter.next().unwrap(), "えお");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(6).burasage(true).align(Align::Right);
+        let iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.collect::<Vec<_>>(), ["あいう、", "えおか。", "  きく"]);
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "compiled language");
+    #[test]
+    fn test_hanging_punctuation() {
+        let text = "abc def. ghi, jkl-mno";
+        let mut iter = LineIter::new(text, 7);
+        assert_eq!(iter.next().unwrap(), "abc");
+        assert_eq!(iter.next().unwrap(), "def.");
+        assert_eq!(iter.next().unwrap(), "ghi,");
+        assert_eq!(iter.next().unwrap(), "jkl-mno");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new(text, 7);
+        iter.set_hanging_punctuation(HangingPunctuation::ALL);
+        assert_eq!(iter.next().unwrap(), "abc def.");
+        assert_eq!(iter.next().unwrap(), "ghi,");
+        assert_eq!(iter.next().unwrap(), "jkl-mno");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new("abc dee-fg", 7);
+        iter.set_break_on_hyphen(true);
+        iter.set_hanging_punctuation(HangingPunctuation {
+            hyphens: true,
+            ..Default::default()
+        });
+        assert_eq!(iter.next().unwrap(), "abc dee-");
+        assert_eq!(iter.next().unwrap(), "fg");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "that feels like a");
+    #[test]
+    fn test_hanging_opening_quotes() {
+        let text = "abc “def” ghi";
+        let opts = Options::new(8)
+            .indent("  ")
+            .hanging_punctuation(HangingPunctuation {
+                opening_quotes: true,
+                ..Default::default()
+            });
+        let iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.collect::<Vec<_>>(), ["  abc", "“def”", "  ghi"]);
+
+        // the quote does not hang without trailing spaces in the indentation.
+        let opts = Options::new(8)
+            .indent("> ")
+            .hanging_punctuation(HangingPunctuation::ALL);
+        let iter = LineIter::with_options("abc \"def\" ghi", &opts).unwrap();
+        assert_eq!(iter.collect::<Vec<_>>(), ["> abc", ">\"def\"", "> ghi"]);
+
+        let opts = Options::new(8)
+            .indent(">>")
+            .hanging_punctuation(HangingPunctuation::ALL);
+        let iter = LineIter::with_options("abc \"def\" ghi", &opts).unwrap();
+        assert_eq!(iter.collect::<Vec<_>>(), [">>abc", ">>\"def\"", ">>ghi"]);
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "dynamically typed,");
+    #[test]
+    fn test_no_break_rules() {
+        let text = "The limit is 50 % of § 12 and v Praze.";
+        let mut iter = LineIter::new(text, 16);
+        assert_eq!(iter.next().unwrap(), "The limit is 50");
+        assert_eq!(iter.next().unwrap(), "% of § 12 and v");
+        assert_eq!(iter.next().unwrap(), "Praze.");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new(text, 16);
+        iter.add_no_break_rule(&NoBreakPair::NumberUnit);
+        iter.add_no_break_rule(&NoBreakPair::SectionSign);
+        iter.add_no_break_rule(&NoBreakPair::SingleLetterWord);
+        assert_eq!(iter.next().unwrap(), "The limit is");
+        assert_eq!(iter.next().unwrap(), "50 % of § 12");
+        assert_eq!(iter.next().unwrap(), "and v Praze.");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(8)
+            .no_break_rule(NoBreakPair::NumberUnit)
+            .no_break_rule(|before: &str, _: &str| before == "No.");
+        let iter = LineIter::with_options("See No. 5 at 10 km", &opts).unwrap();
+        assert_eq!(iter.collect::<Vec<_>>(), ["See", "No. 5 at", "10 km"]);
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "interpreted");
+    #[test]
+    fn test_protected_spans() {
+        let text = "Type `git commit -m` and ⟦keep this⟧ text.";
+        let mut iter = LineIter::new(text, 16);
+        assert_eq!(iter.next().unwrap(), "Type `git commit");
+        assert_eq!(iter.next().unwrap(), "-m` and ⟦keep");
+        assert_eq!(iter.next().unwrap(), "this⟧ text.");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new(text, 16);
+        iter.add_protected_delimiters("`", "`");
+        assert_eq!(iter.next().unwrap(), "Type");
+        assert_eq!(iter.next().unwrap(), "`git commit -m`");
+        assert_eq!(iter.next().unwrap(), "and ⟦keep this⟧");
+        assert_eq!(iter.next().unwrap(), "text.");
+        assert!(iter.next().is_none());
+
+        // the spans are found again in the text given to `init`.
+        iter.init("`a b` `c d`");
+        iter.set_width(5);
+        assert_eq!(iter.next().unwrap(), "`a b`");
+        assert_eq!(iter.next().unwrap(), "`c d`");
+        assert!(iter.next().is_none());
+
+        // a span wider than the line is broken.
+        iter.init("x `a b c d e f`");
+        assert_eq!(iter.next().unwrap(), "x `a");
+        assert_eq!(iter.next().unwrap(), "b c d");
+        assert_eq!(iter.next().unwrap(), "e f`");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(16).protected_delimiters("⟦", "⟧");
+        let iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(
+            iter.collect::<Vec<_>>(),
+            ["Type `git commit", "-m` and", "⟦keep this⟧", "text."]
+        );
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "language.");
+        let mut iter = LineIter::new("ab cd ef gh", 5);
+        iter.add_protected_range(3..8);
+        assert_eq!(iter.collect::<Vec<_>>(), ["ab", "cd ef", "gh"]);
+    }
 
-        let opt = iter.next();
-        assert!(opt.is_none());
+    #[test]
+    fn test_zero_width_space() {
+        let text = "abc\u{200B}def\u{200B}ghi";
+        let mut iter = LineIter::new(text, 7);
+        assert_eq!(iter.next().unwrap(), "abcdef");
+        assert_eq!(iter.next().unwrap(), "ghi");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new(text, 5);
+        iter.set_non_print_policy(NonPrintPolicy::EscapeUnicode);
+        assert_eq!(iter.next().unwrap(), "abc");
+        assert_eq!(iter.next().unwrap(), "def");
+        assert_eq!(iter.next().unwrap(), "ghi");
+        assert!(iter.next().is_none());
     }
 
     #[test]
-    fn test_print_long_text() {
-        let mut iter = LineIter::new(LONG_TEXT, 20);
+    fn test_word_joiner() {
+        let mut iter = LineIter::new("あい\u{2060}うえ", 4);
+        assert_eq!(iter.next().unwrap(), "あ");
+        assert_eq!(iter.next().unwrap(), "いう");
+        assert_eq!(iter.next().unwrap(), "え");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new("ab 50%\u{2060}(x)", 6);
+        assert_eq!(iter.next().unwrap(), "ab");
+        assert_eq!(iter.next().unwrap(), "50%(x)");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new("ab cd\u{FEFF}ef gh", 4);
+        assert_eq!(iter.next().unwrap(), "ab");
+        assert_eq!(iter.next().unwrap(), "cdef");
+        assert_eq!(iter.next().unwrap(), "gh");
+        assert!(iter.next().is_none());
+    }
 
-        while let Some(s) = iter.next() {
-            println!("{}", s);
-        }
+    #[test]
+    fn test_ideographic_space() {
+        let text = "あい\u{3000}うえ\u{3000}\u{3000}おか";
+        let mut iter = LineIter::new(text, 6);
+        assert_eq!(iter.next().unwrap(), "あい");
+        assert_eq!(iter.next().unwrap(), "うえ");
+        assert_eq!(iter.next().unwrap(), "おか");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(6).ideographic_space(IdeographicSpace::Printable);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "あい\u{3000}");
+        assert_eq!(iter.next().unwrap(), "う");
+        assert_eq!(iter.next().unwrap(), "え\u{3000}\u{3000}");
+        assert_eq!(iter.next().unwrap(), "おか");
+        assert!(iter.next().is_none());
     }
 
     #[test]
-    fn test_set_indent_to_long_text() {
-        let mut iter = LineIter::new(LONG_TEXT, 40);
+    fn test_sentence_slack() {
+        let text = "See e.g. Dr. Smith for details. They know it well.";
+        let mut iter = LineIter::new(text, 20);
+        assert_eq!(iter.next().unwrap(), "See e.g. Dr. Smith");
+        assert_eq!(iter.next().unwrap(), "for details. They");
+        assert_eq!(iter.next().unwrap(), "know it well.");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(20).sentence_slack(8);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "See e.g. Dr. Smith");
+        assert_eq!(iter.next().unwrap(), "for details.");
+        assert_eq!(iter.next().unwrap(), "They know it well.");
+        assert!(iter.next().is_none());
+
+        let text = "今日は雨。明日は晴れるでしょう。";
+        let mut iter = LineIter::new(text, 14);
+        iter.set_sentence_slack(6);
+        assert_eq!(iter.next().unwrap(), "今日は雨。");
+        assert_eq!(iter.next().unwrap(), "明日は晴れるで");
+        assert_eq!(iter.next().unwrap(), "しょう。");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new("Buy e.g. this one now.", 10);
+        iter.add_no_break_rule(&NoBreakPair::Abbreviation);
+        assert_eq!(iter.next().unwrap(), "Buy");
+        assert_eq!(iter.next().unwrap(), "e.g. this");
+        assert_eq!(iter.next().unwrap(), "one now.");
+        assert!(iter.next().is_none());
+    }
 
-        if let Some(s) = iter.next() {
-            println!("{}", s);
-        }
+    #[test]
+    fn test_widow_control() {
+        let text = "aaa bbb ccc ddd\neee fff ggg hhh";
+        let mut iter = LineIter::new(text, 12);
+        iter.set_widow_control(true);
+        assert_eq!(iter.next().unwrap(), "aaa bbb");
+        assert_eq!(iter.next().unwrap(), "ccc ddd");
+        assert_eq!(iter.next().unwrap(), "eee fff");
+        assert_eq!(iter.next().unwrap(), "ggg hhh");
+        assert!(iter.next().is_none());
+
+        let text = "aaa bbb ccc ddd\neee fff ggg hhh\n\niii jjj kkk lll";
+        let opts = Options::new(12).paragraph_mode(true).widow_control(true);
+        let iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(
+            iter.collect::<Vec<_>>(),
+            [
+                "aaa bbb ccc",
+                "ddd eee fff",
+                "ggg hhh",
+                "",
+                "iii jjj",
+                "kkk lll"
+            ]
+        );
 
-        let indent = " ".repeat(8);
-        iter.set_indent(&indent);
+        let mut iter = LineIter::new("x verylongword", 12);
+        iter.set_widow_control(true);
+        assert_eq!(iter.next().unwrap(), "x");
+        assert_eq!(iter.next().unwrap(), "verylongword");
+        assert!(iter.next().is_none());
+    }
 
-        while let Some(s) = iter.next() {
-            println!("{}", s);
+    #[test]
+    fn test_next_into_is_same_as_next() {
+        let text = "abc def ghi\n\n  jkl mno pqr stu vwx\n\tyz";
+        let opts = [
+            Options::new(12),
+            Options::new(12).indent("> ").suffix(" |"),
+            Options::new(12).prefix("# ").align(Align::Center),
+            Options::new(12).first_indent("* ").align(Align::Right),
+            Options::new(12)
+                .auto_indent(true)
+                .tab_width(4)
+                .line_numbers(2),
+        ];
+        for opts in &opts {
+            let expected: Vec<_> = LineIter::with_options(text, opts).unwrap().collect();
+            let mut iter = LineIter::with_options(text, opts).unwrap();
+            let mut buf = String::from("garbage");
+            let mut lines = Vec::new();
+            while iter.next_into(&mut buf) {
+                lines.push(buf.clone());
+            }
+            assert_eq!(lines, expected);
         }
+
+        let mut iter = LineIter::new("abc def", 3);
+        let mut buf = String::new();
+        assert_eq!(iter.peek().unwrap(), "abc");
+        assert!(iter.next_into(&mut buf));
+        assert_eq!(buf, "abc");
+        assert!(iter.next_into(&mut buf));
+        assert_eq!(buf, "def");
+        assert!(!iter.next_into(&mut buf));
     }
 
     #[test]
-    fn test_text_contains_non_print_char() {
-        let text = "abcdefg\u{0002}hijklmn";
-        let mut iter = LineIter::new(text, 10);
+    fn test_continuation_marker() {
+        let text = "abc def ghi jkl\n\nmno pqr";
+        let opts = Options::new(10).continuation_marker("\\").suffix("|");
+        let iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(
+            iter.collect::<Vec<_>>(),
+            ["abc def\\ |", "ghi jkl  |", "         |", "mno pqr  |"]
+        );
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "abcdefghij");
+        let opts = Options::new(10)
+            .continuation_marker(" \\")
+            .align(Align::Right);
+        let iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(
+            iter.collect::<Vec<_>>(),
+            [" abc def \\", " ghi jkl", "", " mno pqr"]
+        );
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "klmn");
+        let mut iter = LineIter::new(text, 10);
+        iter.set_continuation_marker("↩");
+        iter.set_indent("  ");
+        iter.set_max_lines(2);
+        assert_eq!(iter.next().unwrap(), "  abc def↩");
+        assert_eq!(iter.next().unwrap(), "  ghi j…");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(4).indent("  ").continuation_marker("->");
+        assert_eq!(
+            opts.validate(),
+            Err(OptionsError::NoRoomForText {
+                width: 4,
+                decoration_width: 4
+            })
+        );
+    }
 
-        let opt = iter.next();
-        assert!(opt.is_none());
+    #[test]
+    fn test_shell_command() {
+        let text = "docker run --rm -v \"$PWD/my dir\":/work -e NAME=a\\ b \
+            image:latest sh -c 'echo \"hi there\"'\nls -la";
+        let opts = Options::new(24)
+            .shell_command(true)
+            .overflow_policy(OverflowPolicy::Overflow);
+        let iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(
+            iter.collect::<Vec<_>>(),
+            [
+                "docker run --rm -v \\",
+                "  \"$PWD/my dir\":/work \\",
+                "  -e NAME=a\\ b \\",
+                "  image:latest sh -c \\",
+                "  'echo \"hi there\"'",
+                "ls -la",
+            ]
+        );
+
+        let opts = Options::new(24)
+            .shell_command(true)
+            .continuation_marker(" ^");
+        let mut iter = LineIter::with_options("echo aaa bbb ccc ddd eee fff", &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "echo aaa bbb ccc ddd ^");
+        assert_eq!(iter.next().unwrap(), "  eee fff");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new("echo aaa bbb", 8);
+        iter.set_shell_command(true);
+        iter.set_shell_command(false);
+        assert_eq!(iter.next().unwrap(), "echo aaa");
+        assert_eq!(iter.next().unwrap(), "bbb");
+        assert!(iter.next().is_none());
+
+        assert!(Options::new(4).shell_command(true).validate().is_err());
     }
 
     #[test]
-    fn test_letter_width_of_east_asian_wide_letter() {
-        let text = "東アジアの全角文字は２文字分の幅をとります。";
+    fn test_break_mode_code() {
+        let text = "let total = compute_sum(first_value, second_value) + offset * 2;";
+        let mut iter = LineIter::new(text, 24);
+        iter.set_break_mode(BreakMode::Code);
+        assert_eq!(iter.next().unwrap(), "let total = compute_sum(");
+        assert_eq!(iter.next().unwrap(), "first_value,");
+        assert_eq!(iter.next().unwrap(), "second_value) + offset *");
+        assert_eq!(iter.next().unwrap(), "2;");
+        assert!(iter.next().is_none());
+
+        let text = r#"log(level, "a, b and c", count + 1);"#;
         let mut iter = LineIter::new(text, 20);
+        iter.set_break_mode(BreakMode::Code);
+        assert_eq!(iter.next().unwrap(), "log(level,");
+        assert_eq!(iter.next().unwrap(), r#""a, b and c", count"#);
+        assert_eq!(iter.next().unwrap(), "+ 1);");
+        assert!(iter.next().is_none());
+
+        iter.init(r#"if a==b&&c!="x, y"{z}"#);
+        assert_eq!(iter.next().unwrap(), r#"if a==b&&c!="x, y"{"#);
+        assert_eq!(iter.next().unwrap(), "z}");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "東アジアの全角文字は");
+    #[test]
+    fn test_ansi_policy() {
+        let text = "abc \u{1b}[4mdefghijklmnop\u{1b}[0m";
+
+        let mut iter = LineIter::new(text, 5);
+        iter.set_ansi_policy(AnsiPolicy::Keep);
+        assert_eq!(iter.next().unwrap(), "abc");
+        assert_eq!(iter.next().unwrap(), "\u{1b}[4mdefgh");
+        assert_eq!(iter.next().unwrap(), "ijklm");
+        assert_eq!(iter.next().unwrap(), "nop\u{1b}[0m");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(5).ansi_policy(AnsiPolicy::Reapply);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "abc");
+        assert_eq!(iter.next().unwrap(), "\u{1b}[4mdefgh\u{1b}[0m");
+        assert_eq!(iter.next().unwrap(), "\u{1b}[4mijklm\u{1b}[0m");
+        assert_eq!(iter.next().unwrap(), "\u{1b}[4mnop\u{1b}[0m");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "２文字分の幅をとりま");
+    #[test]
+    fn test_ansi_policy_with_decorations() {
+        let text = "\u{1b}[31mred apples\u{1b}[0m";
+        let mut iter = LineIter::new(text, 12);
+        iter.set_ansi_policy(AnsiPolicy::Keep);
+        iter.set_align(Align::Right);
+        let line = iter.next_line().unwrap();
+        assert_eq!(line.text, "  \u{1b}[31mred apples\u{1b}[0m");
+        assert_eq!(line.width, 12);
+        assert!(iter.next().is_none());
+
+        // the attributes are re-applied after the skipped lines.
+        let text = "\u{1b}[1;31mone two three\u{1b}[0m four";
+        let mut iter = LineIter::new(text, 7);
+        iter.set_ansi_policy(AnsiPolicy::Reapply);
+        iter.set_indent("> ");
+        assert_eq!(iter.skip_lines(1), 1);
+        assert_eq!(iter.next().unwrap(), "> \u{1b}[1;31mtwo\u{1b}[0m");
+        assert_eq!(iter.next().unwrap(), "> \u{1b}[1;31mthree\u{1b}[0m");
+        assert_eq!(iter.next().unwrap(), "> four");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "す。");
+    #[test]
+    fn test_ansi_policy_with_hyperlinks() {
+        let open = "\u{1b}]8;;https://example.com\u{1b}\\";
+        let close = "\u{1b}]8;;\u{1b}\\";
+        let text = format!("See {}the online manual{} for details.", open, close);
+
+        let mut iter = LineIter::new(&text, 10);
+        iter.set_ansi_policy(AnsiPolicy::Keep);
+        assert_eq!(iter.next().unwrap(), format!("See {}the", open));
+        assert_eq!(iter.next().unwrap(), "online");
+        assert_eq!(iter.next().unwrap(), format!("manual{} for", close));
+        assert_eq!(iter.next().unwrap(), "details.");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new(&text, 10);
+        iter.set_ansi_policy(AnsiPolicy::Reapply);
+        assert_eq!(iter.next().unwrap(), format!("See {}the{}", open, close));
+        assert_eq!(iter.next().unwrap(), format!("{}online{}", open, close));
+        assert_eq!(iter.next().unwrap(), format!("{}manual{} for", open, close));
+        assert_eq!(iter.next().unwrap(), "details.");
+        assert!(iter.next().is_none());
+    }
 
-        let opt = iter.next();
-        assert!(opt.is_none());
+    #[test]
+    fn test_set_first_line_offset() {
+        let text = "abc def ghi\njkl mno pqr";
+        let mut iter = LineIter::new(text, 8);
+        iter.set_first_line_offset(4);
+        iter.set_first_indent("* ");
+        assert_eq!(iter.next().unwrap(), "* ab");
+        assert_eq!(iter.next().unwrap(), "c def");
+        assert_eq!(iter.next().unwrap(), "ghi");
+        assert_eq!(iter.next().unwrap(), "* jkl");
+        assert_eq!(iter.next().unwrap(), "mno pqr");
+        assert!(iter.next().is_none());
+
+        iter.init(text);
+        iter.set_first_line_offset(20);
+        assert_eq!(iter.next().unwrap(), "* a");
+        assert_eq!(iter.next().unwrap(), "bc def");
     }
 
     #[test]
-    fn test_line_break_of_east_asian_wide_letter() {
-        let text =
-            "東アジアの全角文字は基本的に、文字の前後どちらに行の終わりが来ても改行が行われます。";
-        let mut iter = LineIter::new(text, 28);
+    fn test_set_first_line_width() {
+        let text = "abc def ghi jkl mno pqr stu";
+        let mut iter = LineIter::new(text, 7);
+        iter.set_first_line_width(11);
+        iter.set_prefix("> ");
+        assert_eq!(iter.next().unwrap(), "> abc def");
+        assert_eq!(iter.next().unwrap(), "> ghi");
+        assert_eq!(iter.next().unwrap(), "> jkl");
+        assert_eq!(iter.next().unwrap(), "> mno");
+        assert_eq!(iter.next().unwrap(), "> pqr");
+        assert_eq!(iter.next().unwrap(), "> stu");
+        assert!(iter.next().is_none());
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "東アジアの全角文字は基本的");
+        iter.init(text);
+        iter.set_first_line_width(5);
+        iter.set_first_line_count(3);
+        iter.set_width(15);
+        assert_eq!(iter.next().unwrap(), "> abc");
+        assert_eq!(iter.next().unwrap(), "> def");
+        assert_eq!(iter.next().unwrap(), "> ghi");
+        assert_eq!(iter.next().unwrap(), "> jkl mno pqr");
+        assert_eq!(iter.next().unwrap(), "> stu");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "に、文字の前後どちらに行の終");
+    #[test]
+    fn test_kinsoku_rules() {
+        let text = "abc (def) ghi";
+        let mut iter = LineIter::new(text, 5);
+        assert_eq!(iter.next().unwrap(), "abc");
+        assert_eq!(iter.next().unwrap(), "(def)");
+        assert_eq!(iter.next().unwrap(), "ghi");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new(text, 5);
+        iter.remove_no_break_after('(');
+        iter.add_no_break_after('(');
+        iter.add_no_break_before(')');
+        assert_eq!(iter.next().unwrap(), "abc");
+        assert_eq!(iter.next().unwrap(), "(def)");
+
+        let mut iter = LineIter::new("あいうえ「お」", 10);
+        iter.set_break_mode(BreakMode::Uax14);
+        assert_eq!(iter.next().unwrap(), "あいうえ");
+        assert_eq!(iter.next().unwrap(), "「お」");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new("あいうえ「お」", 10);
+        iter.remove_no_break_after('「');
+        assert_eq!(iter.next().unwrap(), "あいうえ「");
+        assert_eq!(iter.next().unwrap(), "お」");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "わりが来ても改行が行われま");
+    #[test]
+    fn test_non_print_policy() {
+        let text = "a\u{07}b\u{1B}c\u{200E}d";
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "す。");
+        let mut iter = LineIter::new(text, 10);
+        assert_eq!(iter.next().unwrap(), "abcd");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(10).non_print_policy(NonPrintPolicy::Keep);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert!(matches!(iter.next(), Some(Cow::Borrowed(s)) if s == text));
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new(text, 16);
+        iter.set_non_print_policy(NonPrintPolicy::EscapeCaret);
+        assert_eq!(iter.next().unwrap(), "a^Gb^[c\\u{200E}d");
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new(text, 30);
+        iter.set_non_print_policy(NonPrintPolicy::EscapeUnicode);
+        let line = iter.next_line().unwrap();
+        assert_eq!(line.text, "a\\u{0007}b\\u{001B}c\\u{200E}d");
+        assert_eq!(line.width, 28);
+        assert_eq!(line.range, 0..text.len());
+        assert!(iter.next().is_none());
+    }
 
-        let opt = iter.next();
-        assert!(opt.is_none());
+    #[test]
+    fn test_non_print_policy_ends_line_with_escape() {
+        let text = "ab \u{200E}";
+        let mut iter = LineIter::new(text, 11);
+        iter.set_non_print_policy(NonPrintPolicy::EscapeUnicode);
+        assert_eq!(
+            iter.next_with_range(),
+            Some(("ab \\u{200E}".into(), 0..text.len()))
+        );
+        assert!(iter.next().is_none());
+
+        let mut iter = LineIter::new("ab\u{01}\ncd", 10);
+        iter.set_non_print_policy(NonPrintPolicy::EscapeCaret);
+        assert_eq!(iter.next().unwrap(), "ab^A");
+        assert_eq!(iter.next().unwrap(), "cd");
+        assert!(iter.next().is_none());
     }
 
     #[test]
-    fn test_prohibitions_of_line_break_of_japanese_start() {
+    fn test_break_mode_uax14_keeps_prohibitions_of_japanese() {
         let text = "句読点は、行頭に置くことは禁止である。";
         let mut iter = LineIter::new(text, 8);
+        iter.set_break_mode(BreakMode::Uax14);
 
         let s = iter.next().unwrap();
         assert_eq!(s, "句読点");
@@ -757,115 +5541,362 @@ Go is expressive, concise, clean, and efficient. Its concurrency mechanisms make
     }
 
     #[test]
-    fn test_prohibitions_of_line_break_of_japanese_end() {
-        let text = "開き括弧は「行末に置く」ことは禁止である。";
-        let mut iter = LineIter::new(text, 12);
-
-        let s = iter.next().unwrap();
-        assert_eq!(s, "開き括弧は");
-
-        let s = iter.next().unwrap();
-        assert_eq!(s, "「行末に置");
+    fn test_break_mode_uax14_does_not_break_numbers() {
+        let text = "It costs $1,234.56 now";
+        let mut iter = LineIter::new(text, 14);
+        iter.set_break_mode(BreakMode::Uax14);
 
         let s = iter.next().unwrap();
-        assert_eq!(s, "く」ことは禁");
+        assert_eq!(s, "It costs");
 
         let s = iter.next().unwrap();
-        assert_eq!(s, "止である。");
+        assert_eq!(s, "$1,234.56 now");
 
         let opt = iter.next();
         assert!(opt.is_none());
     }
 
     #[test]
-    fn test_prohibitions_of_line_break_of_english() {
-        let text = "abc def ghi(jkl mn opq rst uvw xyz)";
-        let mut iter = LineIter::new(text, 11);
+    fn test_break_mode_uax14_after_init() {
+        let text = "abc def";
+        let mut iter = LineIter::new(text, 5);
+        iter.set_break_mode(BreakMode::Uax14);
+
+        assert_eq!(iter.next().unwrap(), "abc");
+        assert_eq!(iter.next().unwrap(), "def");
+        assert!(iter.next().is_none());
+
+        iter.init("ghi-jkl");
+        assert_eq!(iter.next().unwrap(), "ghi-");
+        assert_eq!(iter.next().unwrap(), "jkl");
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_set_first_indent() {
+        let text = "12345678901234567890abcdefghij";
+        let mut iter = LineIter::new(text, 10);
+        iter.set_first_indent("* ");
+        iter.set_indent("   ");
 
         let s = iter.next().unwrap();
-        assert_eq!(s, "abc def ghi");
+        assert_eq!(s, "* ".to_string() + &text[0..8]);
 
         let s = iter.next().unwrap();
-        assert_eq!(s, "(jkl mn opq");
+        assert_eq!(s, "   ".to_string() + &text[8..15]);
 
         let s = iter.next().unwrap();
-        assert_eq!(s, "rst uvw");
+        assert_eq!(s, "   ".to_string() + &text[15..22]);
 
         let s = iter.next().unwrap();
-        assert_eq!(s, "xyz)");
+        assert_eq!(s, "   ".to_string() + &text[22..29]);
+
+        let s = iter.next().unwrap();
+        assert_eq!(s, "   ".to_string() + &text[29..]);
 
         let opt = iter.next();
         assert!(opt.is_none());
     }
 
     #[test]
-    fn test_prohibitions_of_line_break_of_english_quots() {
-        let text = r#"abc def " ghi j " kl mno pq" rst uvw" xyz"#;
-        let mut iter = LineIter::new(text, 9);
+    fn test_set_first_indent_for_each_paragraph() {
+        let text = "abc def ghi\n\njkl mno";
+        let mut iter = LineIter::new(text, 8);
+        iter.set_first_indent("- ");
+        iter.set_indent("  ");
+
+        assert_eq!(iter.next().unwrap(), "- abc");
+        assert_eq!(iter.next().unwrap(), "  def");
+        assert_eq!(iter.next().unwrap(), "  ghi");
+        assert_eq!(iter.next().unwrap(), "");
+        assert_eq!(iter.next().unwrap(), "- jkl");
+        assert_eq!(iter.next().unwrap(), "  mno");
+        assert!(iter.next().is_none());
+
+        iter.init("pqr stu");
+        assert_eq!(iter.next().unwrap(), "- pqr");
+        assert_eq!(iter.next().unwrap(), "  stu");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "abc def");
+    #[test]
+    fn test_set_first_indent_wider_than_indent() {
+        let text = "abcdefghijklmnop";
+        let mut iter = LineIter::new(text, 8);
+        iter.set_first_indent("(1) ");
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "\" ghi j \"");
+        assert_eq!(iter.next().unwrap(), "(1) abcd");
+        assert_eq!(iter.next().unwrap(), "efghijkl");
+        assert_eq!(iter.next().unwrap(), "mnop");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "kl mno pq");
+    #[test]
+    fn test_set_prefix() {
+        let text = "abc def ghi\n\njkl";
+        let mut iter = LineIter::new(text, 10);
+        iter.set_prefix("// ");
+
+        assert_eq!(iter.next().unwrap(), "// abc def");
+        assert_eq!(iter.next().unwrap(), "// ghi");
+        assert_eq!(iter.next().unwrap(), "// ");
+        assert_eq!(iter.next().unwrap(), "// jkl");
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_set_prefix_and_suffix_with_indent() {
+        let text = "あいうえおかきくけこ";
+        let mut iter = LineIter::new(text, 14);
+        iter.set_prefix("|");
+        iter.set_suffix("|");
+        iter.set_first_indent("");
+        iter.set_indent("  ");
 
         let s = iter.next().unwrap();
-        assert_eq!(s, "\" rst");
+        assert_eq!(s, "|あいうえおか|");
+        assert_eq!(crate::text_width(&s), 14);
 
         let s = iter.next().unwrap();
-        assert_eq!(s, "uvw\" xyz");
+        assert_eq!(s, "|  きくけこ  |");
+        assert_eq!(crate::text_width(&s), 14);
 
-        let opt = iter.next();
-        assert!(opt.is_none());
+        assert!(iter.next().is_none());
     }
 
     #[test]
-    fn test_prohibitions_of_line_break_of_english_mixed_quots() {
-        let text = r#"abc def " ghi j ' kl mno pq' rst uvw" xyz"#;
-        let mut iter = LineIter::new(text, 9);
+    fn test_set_suffix_pads_empty_line() {
+        let text = "abc\n\ndef";
+        let mut iter = LineIter::new(text, 6);
+        iter.set_suffix(" |");
+
+        assert_eq!(iter.next().unwrap(), "abc  |");
+        assert_eq!(iter.next().unwrap(), "     |");
+        assert_eq!(iter.next().unwrap(), "def  |");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "abc def");
+    #[test]
+    fn test_with_options() {
+        let opts = Options::new(10)
+            .first_indent("* ")
+            .indent("  ")
+            .prefix("|")
+            .suffix("|");
+        let mut iter = LineIter::with_options("abc def ghi", &opts).unwrap();
+
+        assert_eq!(iter.next().unwrap(), "|* abc   |");
+        assert_eq!(iter.next().unwrap(), "|  def   |");
+        assert_eq!(iter.next().unwrap(), "|  ghi   |");
+        assert!(iter.next().is_none());
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "\" ghi j");
+    #[test]
+    fn test_with_options_invalid() {
+        let opts = Options::new(0);
+        match LineIter::with_options("abc", &opts) {
+            Ok(_) => panic!(),
+            Err(e) => assert_eq!(e, OptionsError::ZeroWidth),
+        }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "' kl mno");
+        let opts = Options::new(4).indent("    ");
+        match LineIter::with_options("abc", &opts) {
+            Ok(_) => panic!(),
+            Err(e) => assert_eq!(
+                e,
+                OptionsError::NoRoomForText {
+                    width: 4,
+                    decoration_width: 4
+                }
+            ),
+        }
+    }
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "pq' rst");
+    #[test]
+    fn test_with_options_tab_width() {
+        let text = "a\tbc\tdef\tg";
+        let opts = Options::new(20).tab_width(4);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "a   bc  def g");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(6).tab_width(4);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "a   bc");
+        assert_eq!(iter.next().unwrap(), "def g");
+        assert!(iter.next().is_none());
 
-        let s = iter.next().unwrap();
-        assert_eq!(s, "uvw\" xyz");
+        let mut iter = LineIter::new(text, 20);
+        assert_eq!(iter.next().unwrap(), "abcdefg");
+        assert!(iter.next().is_none());
+    }
 
-        let opt = iter.next();
-        assert!(opt.is_none());
+    #[test]
+    fn test_with_options_ambiguous_width() {
+        let text = "αβγδεζηθ";
+        let opts = Options::new(8).ambiguous_width(AmbiguousWidth::Narrow);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "αβγδεζηθ");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(8).ambiguous_width(AmbiguousWidth::Wide);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "αβγδ");
+        assert_eq!(iter.next().unwrap(), "εζηθ");
+        assert!(iter.next().is_none());
+    }
 
-        iter.init(text);
+    #[test]
+    fn test_with_options_break_mode() {
+        let opts = Options::new(10).break_mode(BreakMode::Uax14);
+        let mut iter = LineIter::with_options("state-of-the-art", &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "state-of-");
+        assert_eq!(iter.next().unwrap(), "the-art");
+        assert!(iter.next().is_none());
+    }
 
-        while let Some(s) = iter.next() {
-            println!("{}", s);
-        }
+    #[test]
+    fn test_set_align_left() {
+        let mut iter = LineIter::new("abc def ghi", 8);
+        iter.set_align(Align::Left);
+
+        assert_eq!(iter.next().unwrap(), "abc def");
+        assert_eq!(iter.next().unwrap(), "ghi");
+        assert!(iter.next().is_none());
     }
 
     #[test]
-    fn test_print_japanese() {
-        let text = "".to_string()
-            + "私はその人を常に先生と呼んでいた。だからここでもただ先生と書く"
-            + "だけで本名は打ち明けない。これは世間を憚かる遠慮というよりも、"
-            + "その方が私にとって自然だからである。私はその人の記憶を呼び起す"
-            + "ごとに、すぐ「先生」といいたくなる。筆を執っても心持は同じ事で"
-            + "ある。よそよそしい頭文字などはとても使う気にならない。\n"
-            + "（夏目漱石「こころ」から引用）";
+    fn test_set_align_right() {
+        let mut iter = LineIter::new("あいう えお\n\nabc", 8);
+        iter.set_align(Align::Right);
+
+        assert_eq!(iter.next().unwrap(), "  あいう");
+        assert_eq!(iter.next().unwrap(), "    えお");
+        assert_eq!(iter.next().unwrap(), "");
+        assert_eq!(iter.next().unwrap(), "     abc");
+        assert!(iter.next().is_none());
+    }
 
-        let mut iter = LineIter::new(&text, 50);
+    #[test]
+    fn test_set_align_center() {
+        let mut iter = LineIter::new("abc def ghij", 8);
+        iter.set_align(Align::Center);
 
-        while let Some(s) = iter.next() {
-            println!("{}", s);
-        }
+        assert_eq!(iter.next().unwrap(), "abc def");
+        assert_eq!(iter.next().unwrap(), "  ghij");
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_set_align_with_indent_prefix_and_suffix() {
+        let opts = Options::new(10)
+            .indent("-")
+            .prefix("|")
+            .suffix("|")
+            .align(Align::Center);
+        let mut iter = LineIter::with_options("abc de", &opts).unwrap();
+
+        assert_eq!(iter.next().unwrap(), "|-abc de |");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(10).prefix("|").suffix("|").align(Align::Right);
+        let mut iter = LineIter::with_options("ab", &opts).unwrap();
+
+        assert_eq!(iter.next().unwrap(), "|      ab|");
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_set_hyphenator() {
+        let hyphenator = |word: &str| match word {
+            "hyphenation" => vec![2, 6],
+            "breaking" => vec![5],
+            _ => Vec::new(),
+        };
+        let text = "the hyphenation of line breaking";
+        let mut iter = LineIter::new(text, 12);
+        iter.set_hyphenator(&hyphenator);
+
+        assert_eq!(iter.next().unwrap(), "the hyphen-");
+        assert_eq!(iter.next().unwrap(), "ation of");
+        assert_eq!(iter.next().unwrap(), "line break-");
+        assert_eq!(iter.next().unwrap(), "ing");
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_set_hyphenator_no_split_point_fits() {
+        let hyphenator = |word: &str| match word {
+            "hyphenation" => vec![6],
+            _ => Vec::new(),
+        };
+        let mut iter = LineIter::new("the hyphenation", 9);
+        iter.set_hyphenator(&hyphenator);
+
+        assert_eq!(iter.next().unwrap(), "the");
+        assert_eq!(iter.next().unwrap(), "hyphen-");
+        assert_eq!(iter.next().unwrap(), "ation");
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_set_hyphenator_for_long_word() {
+        let hyphenator = |word: &str| (1..word.len()).filter(|i| i % 3 == 0).collect();
+        let mut iter = LineIter::new("abcdefghijklmn", 5);
+        iter.set_hyphenator(&hyphenator);
+
+        assert_eq!(iter.next().unwrap(), "abc-");
+        assert_eq!(iter.next().unwrap(), "def-");
+        assert_eq!(iter.next().unwrap(), "ghi-");
+        assert_eq!(iter.next().unwrap(), "jklmn");
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_hyphenator_with_options() {
+        let opts = Options::new(12).hyphenator(|word: &str| match word {
+            "hyphenation" => vec![2, 6],
+            _ => Vec::new(),
+        });
+        let mut iter = LineIter::with_options("the hyphenation", &opts).unwrap();
+
+        assert_eq!(iter.next().unwrap(), "the hyphen-");
+        assert_eq!(iter.next().unwrap(), "ation");
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_set_paragraph_mode() {
+        let text = "The Go programming\nlanguage is an open\n  source project.\n\n\n\
+            Go is expressive,\n    \nconcise.\n";
+        let mut iter = LineIter::new(text, 30);
+        iter.set_paragraph_mode(true);
+
+        assert_eq!(iter.next().unwrap(), "The Go programming language is");
+        assert_eq!(iter.next().unwrap(), "an open source project.");
+        assert_eq!(iter.next().unwrap(), "");
+        assert_eq!(iter.next().unwrap(), "");
+        assert_eq!(iter.next().unwrap(), "Go is expressive,");
+        assert_eq!(iter.next().unwrap(), "");
+        assert_eq!(iter.next().unwrap(), "concise.");
+        assert_eq!(iter.next().unwrap(), "");
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_set_paragraph_mode_off() {
+        let text = "abc\ndef";
+        let mut iter = LineIter::new(text, 30);
+        iter.set_paragraph_mode(false);
+
+        assert_eq!(iter.next().unwrap(), "abc");
+        assert_eq!(iter.next().unwrap(), "def");
+        assert!(iter.next().is_none());
+
+        let opts = Options::new(30).paragraph_mode(true);
+        let mut iter = LineIter::with_options(text, &opts).unwrap();
+
+        assert_eq!(iter.next().unwrap(), "abc def");
+        assert!(iter.next().is_none());
     }
 }
@@ -0,0 +1,148 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::borrow::Cow;
+use alloc::collections::VecDeque;
+use alloc::string::String;
+
+use crate::line_iter::LineIter;
+use crate::options::{Options, OptionsError};
+
+/// `LineIterBuf` is the struct that owns the given string and outputs it line
+/// by line.
+///
+/// Unlike `LineIter`, this struct has no lifetime parameter, so that it can be
+/// stored in other structs or returned from functions.
+/// The text is broken into lines when this instance is created, and the
+/// output lines are owned `String`s.
+///
+/// ```rust
+///     use linebreak::{LineIterBuf, Options};
+///
+///     fn wrap(name: &str) -> LineIterBuf {
+///         let text = format!("Hello, {}! Welcome to the Rust world.", name);
+///         LineIterBuf::new(text, 16)
+///     }
+///
+///     let mut iter = wrap("Ferris");
+///     assert_eq!(iter.next().unwrap(), "Hello, Ferris!");
+///     assert_eq!(iter.next().unwrap(), "Welcome to the");
+///     assert_eq!(iter.next().unwrap(), "Rust world.");
+///     assert!(iter.next().is_none());
+///
+///     let opts = Options::new(10).prefix("> ");
+///     let iter = LineIterBuf::with_options("abc def ghi", opts).unwrap();
+///     assert_eq!(iter.collect::<Vec<_>>(), ["> abc def", "> ghi"]);
+/// ```
+pub struct LineIterBuf {
+    text: Cow<'static, str>,
+    opts: Option<Options>,
+    lines: VecDeque<String>,
+}
+
+impl LineIterBuf {
+    /// Creates a `LineIterBuf` instance which owns the given string and
+    /// outputs it line by line within the specified width.
+    pub fn new<T>(text: T, line_width: usize) -> LineIterBuf
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        let text = text.into();
+        let lines = LineIter::new(&text, line_width)
+            .map(Cow::into_owned)
+            .collect();
+        LineIterBuf {
+            text,
+            opts: None,
+            lines,
+        }
+    }
+
+    /// Creates a `LineIterBuf` instance which owns the given string and the
+    /// specified `Options`.
+    /// If the settings are invalid, this function returns an `OptionsError`.
+    pub fn with_options<T>(text: T, opts: Options) -> Result<LineIterBuf, OptionsError>
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        let text = text.into();
+        let lines = LineIter::with_options(&text, &opts)?
+            .map(Cow::into_owned)
+            .collect();
+        Ok(LineIterBuf {
+            text,
+            opts: Some(opts),
+            lines,
+        })
+    }
+
+    /// Returns the string which this instance owns.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the `Options` which this instance owns, if it is created with
+    /// `with_options`.
+    pub fn options(&self) -> Option<&Options> {
+        self.opts.as_ref()
+    }
+}
+
+impl Iterator for LineIterBuf {
+    type Item = String;
+
+    /// Returns an Option of a line string.
+    fn next(&mut self) -> Option<String> {
+        self.lines.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.lines.len(), Some(self.lines.len()))
+    }
+}
+
+#[cfg(test)]
+mod test_of_line_iter_buf {
+    use super::*;
+
+    #[test]
+    fn test_new_with_string() {
+        let mut iter = LineIterBuf::new(String::from("abc def ghi"), 7);
+        assert_eq!(iter.text(), "abc def ghi");
+        assert!(iter.options().is_none());
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+        assert_eq!(iter.next().unwrap(), "abc def");
+        assert_eq!(iter.next().unwrap(), "ghi");
+        assert!(iter.next().is_none());
+        assert_eq!(iter.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn test_new_with_static_str() {
+        let iter = LineIterBuf::new("abc def ghi", 3);
+        assert_eq!(iter.collect::<Vec<_>>(), ["abc", "def", "ghi"]);
+    }
+
+    #[test]
+    fn test_move_while_iterating() {
+        let mut iter = LineIterBuf::new(String::from("abc def ghi"), 3);
+        assert_eq!(iter.next().unwrap(), "abc");
+        let mut moved = Box::new(iter);
+        assert_eq!(moved.next().unwrap(), "def");
+        let mut arr = [*moved];
+        assert_eq!(arr[0].next().unwrap(), "ghi");
+        assert!(arr[0].next().is_none());
+    }
+
+    #[test]
+    fn test_with_options() {
+        let opts = Options::new(9).indent("  ").prefix("|");
+        let iter = LineIterBuf::with_options(String::from("abc def ghi"), opts.clone()).unwrap();
+        assert_eq!(iter.options(), Some(&opts));
+        assert_eq!(iter.collect::<Vec<_>>(), ["|  abc", "|  def", "|  ghi"]);
+
+        let opts = Options::new(2).prefix("> ");
+        assert!(LineIterBuf::with_options("abc", opts).is_err());
+    }
+}
@@ -2,19 +2,160 @@
 // This program is free software under MIT License.
 // See the file LICENSE in this distribution for more details.
 
+#![allow(
+    clippy::needless_return,
+    clippy::redundant_static_lifetimes,
+    clippy::bool_assert_comparison
+)]
+
+use alloc::vec::Vec;
+
 use crate::unicode::EAST_ASIAN_WIDTH;
 use icu::properties::EastAsianWidth;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+/// `LboType` is the enum for the line break opportunity of a character.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum LboType {
+    /// A line cannot be broken before or after the character.
     Never,
+
+    /// A line can be broken before the character.
     Before,
+
+    /// A line can be broken after the character.
     After,
+
+    /// A line can be broken both before and after the character.
     Both,
+
+    /// A line must be broken at the character, such as a line feed.
     Break,
+
+    /// The character is a space, after which a line can be broken.
     Space,
 }
 
+/// `KinsokuLevel` is the enum for the strictness of the Japanese line break
+/// rules (kinsoku shori) in JIS X 4051.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum KinsokuLevel {
+    /// A line is not broken before small kana (e.g. `ゃ`, `ッ`), prolonged
+    /// sound marks (`ー`), iteration marks (e.g. `々`, `ゝ`) and the double
+    /// hyphen (`゠`).
+    #[default]
+    Strict,
+
+    /// A line can be broken before small kana and prolonged sound marks.
+    Normal,
+
+    /// A line can be broken before small kana, prolonged sound marks,
+    /// iteration marks and the double hyphen.
+    Loose,
+}
+
+impl KinsokuLevel {
+    // Relaxes the lbo type of the specified character, before which a line
+    // must not be broken in the strict level.
+    pub(crate) fn apply(self, ch: char, state: &mut LboState) {
+        if state.lbo_type != LboType::After {
+            return;
+        }
+        let relaxed = match self {
+            KinsokuLevel::Strict => false,
+            KinsokuLevel::Normal => is_small_kana_or_prolonged(ch),
+            KinsokuLevel::Loose => {
+                is_small_kana_or_prolonged(ch) || is_iteration_mark_or_hyphen(ch)
+            }
+        };
+        if relaxed {
+            state.lbo_type = match EAST_ASIAN_WIDTH.get(ch) {
+                EastAsianWidth::Wide | EastAsianWidth::Fullwidth => LboType::Both,
+                _ => LboType::Never,
+            };
+        }
+    }
+}
+
+fn is_small_kana_or_prolonged(ch: char) -> bool {
+    matches!(ch, '\u{31F0}'..='\u{31FF}' | '\u{FF67}'..='\u{FF70}')
+        || "ぁぃぅぇぉっゃゅょゎゕゖァィゥェォッャュョヮヵヶー".contains(ch)
+}
+
+fn is_iteration_mark_or_hyphen(ch: char) -> bool {
+    "々〻ゝゞヽヾ゠".contains(ch)
+}
+
+// Returns true if the specified character can hang past the end of a line.
+pub fn is_hanging_punctuation(ch: char) -> bool {
+    matches!(ch, '、' | '。' | '，' | '．')
+}
+
+/// `HangingPunctuation` is the struct for selecting the classes of the Latin
+/// punctuation which hang outside the text block.
+///
+/// A punctuation at the end of a line hangs past the line width by its width,
+/// and an opening quote at the beginning of a line hangs into the trailing
+/// spaces of the indentation.
+///
+/// ```rust
+///     use linebreak::{HangingPunctuation, LineIter};
+///
+///     let mut iter = LineIter::new("It is fine. Yes, it is.", 10);
+///     iter.set_hanging_punctuation(HangingPunctuation {
+///         stops: true,
+///         ..Default::default()
+///     });
+///     assert_eq!(iter.next().unwrap(), "It is fine.");
+///     assert_eq!(iter.next().unwrap(), "Yes, it is.");
+///     assert!(iter.next().is_none());
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HangingPunctuation {
+    /// Whether `.`, `,`, `:`, `;`, `!` and `?` hang at the end of a line.
+    pub stops: bool,
+    /// Whether hyphens and dashes (`-`, `‐`, `–` and `—`) hang at the end of a
+    /// line.
+    pub hyphens: bool,
+    /// Whether closing quotes (`"`, `'`, `”`, `’`, `»` and `›`) hang at the end
+    /// of a line.
+    pub closing_quotes: bool,
+    /// Whether opening quotes (`"`, `'`, `“`, `‘`, `«` and `‹`) hang at the
+    /// beginning of a line.
+    pub opening_quotes: bool,
+}
+
+impl HangingPunctuation {
+    /// The setting in which all classes of punctuation hang.
+    pub const ALL: HangingPunctuation = HangingPunctuation {
+        stops: true,
+        hyphens: true,
+        closing_quotes: true,
+        opening_quotes: true,
+    };
+
+    // Returns true if the specified character can hang past the end of a line.
+    pub(crate) fn hangs_at_end(&self, ch: char) -> bool {
+        match ch {
+            '.' | ',' | ':' | ';' | '!' | '?' => self.stops,
+            '-' | '‐' | '–' | '—' => self.hyphens,
+            '"' | '\'' | '”' | '’' | '»' | '›' => self.closing_quotes,
+            _ => false,
+        }
+    }
+
+    // Returns true if the specified character can hang at the beginning of a
+    // line.
+    pub(crate) fn hangs_at_start(&self, ch: char) -> bool {
+        match ch {
+            '"' | '\'' | '“' | '‘' | '«' | '‹' => self.opening_quotes,
+            _ => false,
+        }
+    }
+}
+
 pub struct LboState {
     pub lbo_type: LboType,
     pub lbo_prev: LboType,
@@ -57,10 +198,21 @@ pub fn line_break_opportunity(ch: char, state: &mut LboState) {
             return;
         }
         _ => {
+            // ASCII letters and digits are the most frequent and never
+            // provide line break opportunities.
+            if ch.is_ascii_alphanumeric() {
+                state.lbo_type = LboType::Never;
+                return;
+            }
             if contains(LBO_BREAKS, ch) {
                 state.lbo_type = LboType::Break;
                 return;
             }
+            // a zero width space is an explicit line break opportunity.
+            if ch == '\u{200B}' {
+                state.lbo_type = LboType::Space;
+                return;
+            }
             if contains(LBO_BEFORES, ch) {
                 state.lbo_type = LboType::Before;
                 return;
@@ -73,6 +225,12 @@ pub fn line_break_opportunity(ch: char, state: &mut LboState) {
                 state.lbo_type = LboType::Space;
                 return;
             }
+            // Korean text is broken at spaces like English, even though Hangul
+            // characters are wide.
+            if is_hangul(ch) {
+                state.lbo_type = LboType::Never;
+                return;
+            }
             match EAST_ASIAN_WIDTH.get(ch) {
                 EastAsianWidth::Wide | EastAsianWidth::Fullwidth => {
                     state.lbo_type = LboType::Both;
@@ -85,6 +243,93 @@ pub fn line_break_opportunity(ch: char, state: &mut LboState) {
     }
 }
 
+// Determines the line break opportunity of a character in a shell command,
+// which is broken only at the spaces between arguments.
+// The quotation states are tracked as `line_break_opportunity` does, except
+// that a quotation mark inside of the other kind of quotation or escaped by a
+// backslash does not open nor close a quotation.
+pub fn shell_break_opportunity(ch: char, escaped: bool, state: &mut LboState) {
+    state.lbo_prev = state.lbo_type;
+    state.lbo_type = LboType::Never;
+    if escaped {
+        return;
+    }
+    match ch {
+        '"' if state.open_apos == 0 => state.open_quot ^= 1,
+        '\'' if state.open_quot == 0 => state.open_apos ^= 1,
+        _ if contains(LBO_BREAKS, ch) => state.lbo_type = LboType::Break,
+        _ if ch.is_whitespace() && state.open_quot == 0 && state.open_apos == 0 => {
+            state.lbo_type = LboType::Space;
+        }
+        _ => (),
+    }
+}
+
+// Holds the characters which are added to or removed from the sets of the
+// characters before which or after which a line must not be broken.
+pub struct Kinsoku {
+    entries: Vec<(char, bool, bool)>, // (ch, no break before, no break after)
+}
+
+impl Kinsoku {
+    pub fn new() -> Kinsoku {
+        Kinsoku {
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn set_no_break_before(&mut self, ch: char, enabled: bool) {
+        self.entry(ch).1 = enabled;
+    }
+
+    pub fn set_no_break_after(&mut self, ch: char, enabled: bool) {
+        self.entry(ch).2 = enabled;
+    }
+
+    fn entry(&mut self, ch: char) -> &mut (char, bool, bool) {
+        let i = match self.entries.iter().position(|e| e.0 == ch) {
+            Some(i) => i,
+            None => {
+                let no_break_before = contains(LBO_AFTERS, ch) && !contains(LBO_BEFORES, ch);
+                let no_break_after = contains(LBO_BEFORES, ch);
+                self.entries.push((ch, no_break_before, no_break_after));
+                self.entries.len() - 1
+            }
+        };
+        &mut self.entries[i]
+    }
+
+    // Overwrites the lbo type of the specified character if the character is
+    // added to or removed from the sets.
+    pub fn apply(&self, ch: char, state: &mut LboState) {
+        if matches!(state.lbo_type, LboType::Break | LboType::Space) {
+            return;
+        }
+        if let Some(e) = self.entries.iter().find(|e| e.0 == ch) {
+            state.lbo_type = match (e.1, e.2) {
+                (true, true) => LboType::Never,
+                (true, false) => LboType::After,
+                (false, true) => LboType::Before,
+                (false, false) => match EAST_ASIAN_WIDTH.get(ch) {
+                    EastAsianWidth::Wide | EastAsianWidth::Fullwidth => LboType::Both,
+                    _ => LboType::Never,
+                },
+            };
+        }
+    }
+}
+
+pub fn is_hangul(ch: char) -> bool {
+    matches!(ch,
+        '\u{1100}'..='\u{11FF}' | // Hangul Jamo
+        '\u{3131}'..='\u{318E}' | // Hangul Compatibility Jamo
+        '\u{A960}'..='\u{A97F}' | // Hangul Jamo Extended-A
+        '\u{AC00}'..='\u{D7A3}' | // Hangul Syllables
+        '\u{D7B0}'..='\u{D7FF}' | // Hangul Jamo Extended-B
+        '\u{FFA0}'..='\u{FFDC}'   // Halfwidth Hangul
+    )
+}
+
 fn contains(candidates: &[char], ch: char) -> bool {
     for c in candidates {
         if *c == ch {
@@ -97,6 +342,8 @@ fn contains(candidates: &[char], ch: char) -> bool {
 const LBO_BREAKS: &'static [char] = &[
     '\u{000A}', // LF
     '\u{000D}', // CR
+    '\u{2028}', // LINE SEPARATOR
+    '\u{2029}', // PARAGRAPH SEPARATOR
 ];
 
 const LBO_BEFORES: &'static [char] = &[
@@ -104,6 +351,11 @@ const LBO_BEFORES: &'static [char] = &[
     '\u{005B}', // [
     '\u{007B}', // {
     '\u{00AB}', // «
+    '\u{2018}', // ‘
+    '\u{201A}', // ‚
+    '\u{201C}', // “
+    '\u{201E}', // „
+    '\u{2039}', // ‹
     '\u{3008}', // 〈
     '\u{300A}', // 《
     '\u{300C}', // 「
@@ -125,6 +377,13 @@ const LBO_AFTERS: &'static [char] = &[
     '\u{003A}', // :
     '\u{003B}', // ;
     '\u{003F}', // ?
+    '\u{00BB}', // »
+    '\u{2019}', // ’
+    '\u{201D}', // ”
+    '\u{203A}', // ›
+    '\u{303B}', // 〻
+    '\u{309D}', // ゝ
+    '\u{309E}', // ゞ
     '\u{30A0}', // ゠
     '\u{30A1}', // ァ
     '\u{30A3}', // ィ
@@ -138,6 +397,24 @@ const LBO_AFTERS: &'static [char] = &[
     '\u{30EE}', // ヮ
     '\u{30F5}', // ヵ
     '\u{30F6}', // ヶ
+    '\u{30FD}', // ヽ
+    '\u{30FE}', // ヾ
+    '\u{31F0}', // ㇰ
+    '\u{31F1}', // ㇱ
+    '\u{31F2}', // ㇲ
+    '\u{31F3}', // ㇳ
+    '\u{31F4}', // ㇴ
+    '\u{31F5}', // ㇵ
+    '\u{31F6}', // ㇶ
+    '\u{31F7}', // ㇷ
+    '\u{31F8}', // ㇸ
+    '\u{31F9}', // ㇹ
+    '\u{31FA}', // ㇺ
+    '\u{31FB}', // ㇻ
+    '\u{31FC}', // ㇼ
+    '\u{31FD}', // ㇽ
+    '\u{31FE}', // ㇾ
+    '\u{31FF}', // ㇿ
     '\u{3041}', // ぁ
     '\u{3043}', // ぃ
     '\u{3045}', // ぅ
@@ -167,6 +444,16 @@ const LBO_AFTERS: &'static [char] = &[
     '\u{3019}', // 〙
     '\u{301F}', // 〟
     '\u{FF09}', // )
+    '\u{FF67}', // ｧ
+    '\u{FF68}', // ｨ
+    '\u{FF69}', // ｩ
+    '\u{FF6A}', // ｪ
+    '\u{FF6B}', // ｫ
+    '\u{FF6C}', // ｬ
+    '\u{FF6D}', // ｭ
+    '\u{FF6E}', // ｮ
+    '\u{FF6F}', // ｯ
+    '\u{FF70}', // ｰ
     '\u{FF5D}', // ｝
 ];
 
@@ -178,6 +465,8 @@ mod test_of_linebreak {
     fn test_contains_in_lbo_breaks() {
         assert_eq!(contains(LBO_BREAKS, '\r'), true);
         assert_eq!(contains(LBO_BREAKS, '\n'), true);
+        assert_eq!(contains(LBO_BREAKS, '\u{2028}'), true);
+        assert_eq!(contains(LBO_BREAKS, '\u{2029}'), true);
         assert_eq!(contains(LBO_BREAKS, '\t'), false);
         assert_eq!(contains(LBO_BREAKS, 'a'), false);
         assert_eq!(contains(LBO_BREAKS, '1'), false);
@@ -195,6 +484,18 @@ mod test_of_linebreak {
         assert_eq!(contains(LBO_BEFORES, '1'), false);
     }
 
+    #[test]
+    fn test_contains_typographic_quotes() {
+        for ch in ['‘', '“', '„', '«', '‹'] {
+            assert!(contains(LBO_BEFORES, ch));
+            assert!(!contains(LBO_AFTERS, ch));
+        }
+        for ch in ['’', '”', '»', '›'] {
+            assert!(!contains(LBO_BEFORES, ch));
+            assert!(contains(LBO_AFTERS, ch));
+        }
+    }
+
     #[test]
     fn test_contains_in_lbo_afters() {
         assert_eq!(contains(LBO_AFTERS, '!'), true);
@@ -482,6 +783,180 @@ mod test_of_linebreak {
         assert_eq!(state.open_quot, 0);
     }
 
+    #[test]
+    fn test_line_break_opportunity_ch_is_zero_width_space() {
+        let mut state = LboState {
+            lbo_type: LboType::Never,
+            lbo_prev: LboType::Never,
+            open_apos: 0,
+            open_quot: 0,
+        };
+
+        line_break_opportunity('\u{200B}', &mut state);
+
+        assert_eq!(state.lbo_type, LboType::Space);
+        assert_eq!(state.lbo_prev, LboType::Never);
+    }
+
+    #[test]
+    fn test_line_break_opportunity_ch_is_hangul() {
+        let mut state = LboState {
+            lbo_type: LboType::Space,
+            lbo_prev: LboType::Never,
+            open_apos: 0,
+            open_quot: 0,
+        };
+
+        line_break_opportunity('한', &mut state);
+
+        assert_eq!(state.lbo_type, LboType::Never);
+        assert_eq!(state.lbo_prev, LboType::Space);
+
+        for ch in ['ᄀ', 'ㄱ', 'ꥠ', '힣', 'ퟻ', 'ﾡ'] {
+            assert!(is_hangul(ch));
+        }
+        for ch in ['a', 'あ', '漢', '\u{D7A4}'] {
+            assert!(!is_hangul(ch));
+        }
+    }
+
+    #[test]
+    fn test_shell_break_opportunity() {
+        let mut state = LboState {
+            lbo_type: LboType::Never,
+            lbo_prev: LboType::Never,
+            open_apos: 0,
+            open_quot: 0,
+        };
+        let cases = [
+            ('a', false, LboType::Never, 0, 0),
+            ('-', false, LboType::Never, 0, 0),
+            (' ', false, LboType::Space, 0, 0),
+            ('"', false, LboType::Never, 1, 0),
+            (' ', false, LboType::Never, 1, 0),
+            ('\'', false, LboType::Never, 1, 0),
+            ('"', true, LboType::Never, 1, 0),
+            ('"', false, LboType::Never, 0, 0),
+            ('\'', false, LboType::Never, 0, 1),
+            ('"', false, LboType::Never, 0, 1),
+            (' ', false, LboType::Never, 0, 1),
+            ('\'', false, LboType::Never, 0, 0),
+            (' ', true, LboType::Never, 0, 0),
+            ('\n', false, LboType::Break, 0, 0),
+        ];
+        for (ch, escaped, lbo_type, open_quot, open_apos) in cases {
+            shell_break_opportunity(ch, escaped, &mut state);
+            assert_eq!(state.lbo_type, lbo_type, "{:?}", ch);
+            assert_eq!(state.open_quot, open_quot, "{:?}", ch);
+            assert_eq!(state.open_apos, open_apos, "{:?}", ch);
+        }
+    }
+
+    #[test]
+    fn test_kinsoku_level() {
+        let mut state = LboState {
+            lbo_type: LboType::Never,
+            lbo_prev: LboType::Never,
+            open_apos: 0,
+            open_quot: 0,
+        };
+
+        let cases = [
+            ('ゃ', LboType::After, LboType::Both, LboType::Both),
+            ('ー', LboType::After, LboType::Both, LboType::Both),
+            ('ㇰ', LboType::After, LboType::Both, LboType::Both),
+            ('ｯ', LboType::After, LboType::Never, LboType::Never),
+            ('々', LboType::After, LboType::After, LboType::Both),
+            ('ゝ', LboType::After, LboType::After, LboType::Both),
+            ('゠', LboType::After, LboType::After, LboType::Both),
+            ('。', LboType::After, LboType::After, LboType::After),
+            ('」', LboType::After, LboType::After, LboType::After),
+        ];
+        for (ch, strict, normal, loose) in cases {
+            for (level, expected) in [
+                (KinsokuLevel::Strict, strict),
+                (KinsokuLevel::Normal, normal),
+                (KinsokuLevel::Loose, loose),
+            ] {
+                line_break_opportunity(ch, &mut state);
+                level.apply(ch, &mut state);
+                assert_eq!(state.lbo_type, expected, "{} {:?}", ch, level);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hanging_punctuation() {
+        let none = HangingPunctuation::default();
+        for ch in ['.', ',', '-', '—', '”', '“', 'a', '。'] {
+            assert!(!none.hangs_at_end(ch));
+            assert!(!none.hangs_at_start(ch));
+        }
+
+        let all = HangingPunctuation::ALL;
+        for ch in [
+            '.', ',', ':', ';', '!', '?', '-', '‐', '–', '—', '"', '”', '»',
+        ] {
+            assert!(all.hangs_at_end(ch));
+        }
+        for ch in ['"', '\'', '“', '‘', '«', '‹'] {
+            assert!(all.hangs_at_start(ch));
+        }
+        for ch in ['a', '。', '(', '“'] {
+            assert!(!all.hangs_at_end(ch));
+        }
+        assert!(!all.hangs_at_start('”'));
+
+        let stops = HangingPunctuation {
+            stops: true,
+            ..Default::default()
+        };
+        assert!(stops.hangs_at_end('.'));
+        assert!(!stops.hangs_at_end('-'));
+        assert!(!stops.hangs_at_end('”'));
+        assert!(!stops.hangs_at_start('“'));
+    }
+
+    #[test]
+    fn test_kinsoku() {
+        let mut state = LboState {
+            lbo_type: LboType::Never,
+            lbo_prev: LboType::Never,
+            open_apos: 0,
+            open_quot: 0,
+        };
+        let mut kinsoku = Kinsoku::new();
+
+        line_break_opportunity('a', &mut state);
+        kinsoku.apply('a', &mut state);
+        assert_eq!(state.lbo_type, LboType::Never);
+
+        kinsoku.set_no_break_before('%', true);
+        kinsoku.set_no_break_after('$', true);
+        kinsoku.set_no_break_before('。', false);
+        kinsoku.set_no_break_after('「', false);
+        kinsoku.set_no_break_before('あ', true);
+        kinsoku.set_no_break_after('あ', true);
+
+        for (ch, lbo_type) in [
+            ('%', LboType::After),
+            ('$', LboType::Before),
+            ('。', LboType::Both),
+            ('「', LboType::Both),
+            ('あ', LboType::Never),
+            ('(', LboType::Before),
+        ] {
+            line_break_opportunity(ch, &mut state);
+            kinsoku.apply(ch, &mut state);
+            assert_eq!(state.lbo_type, lbo_type);
+        }
+
+        kinsoku.set_no_break_before('%', false);
+        line_break_opportunity('%', &mut state);
+        kinsoku.apply('%', &mut state);
+        assert_eq!(state.lbo_type, LboType::Never);
+    }
+
     #[test]
     fn test_for_coverage() {
         let t = LboType::Never;
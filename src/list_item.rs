@@ -0,0 +1,78 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use crate::unicode::{char_width_with, AmbiguousWidth};
+
+// Finds a bullet or numbered list marker, such as "- ", "* ", "1. " and "a) ",
+// at the start of the specified text, and returns the display width of the
+// marker including the spaces which follow it.
+pub fn list_marker_width(text: &str, amb: AmbiguousWidth) -> Option<usize> {
+    let mut chars = text.chars();
+    let ch = chars.next()?;
+
+    let mut width = char_width_with(ch, amb);
+    match ch {
+        '-' | '*' | '+' | '\u{2022}' => (),
+        '0'..='9' => {
+            let mut digits = 1;
+            loop {
+                match chars.next()? {
+                    '0'..='9' if digits < 9 => digits += 1,
+                    '.' | ')' => break,
+                    _ => return None,
+                }
+            }
+            width += digits;
+        }
+        'a'..='z' | 'A'..='Z' => match chars.next()? {
+            '.' | ')' => width += 1,
+            _ => return None,
+        },
+        _ => return None,
+    }
+
+    let spaces = chars.take_while(|c| *c == ' ').count();
+    match spaces {
+        0 => None,
+        n => Some(width + n),
+    }
+}
+
+#[cfg(test)]
+mod test_of_list_item {
+    use super::*;
+
+    fn width(text: &str) -> Option<usize> {
+        list_marker_width(text, AmbiguousWidth::Narrow)
+    }
+
+    #[test]
+    fn test_bullet_markers() {
+        assert_eq!(width("- abc"), Some(2));
+        assert_eq!(width("* abc"), Some(2));
+        assert_eq!(width("+   abc"), Some(4));
+        assert_eq!(width("\u{2022} abc"), Some(2));
+        assert_eq!(
+            list_marker_width("\u{2022} abc", AmbiguousWidth::Wide),
+            Some(3)
+        );
+        assert_eq!(width("-abc"), None);
+        assert_eq!(width("--- abc"), None);
+        assert_eq!(width("-"), None);
+    }
+
+    #[test]
+    fn test_numbered_markers() {
+        assert_eq!(width("1. abc"), Some(3));
+        assert_eq!(width("12) abc"), Some(4));
+        assert_eq!(width("a) abc"), Some(3));
+        assert_eq!(width("B.  abc"), Some(4));
+        assert_eq!(width("1.5 abc"), None);
+        assert_eq!(width("e.g. abc"), None);
+        assert_eq!(width("ab) abc"), None);
+        assert_eq!(width("1234567890. abc"), None);
+        assert_eq!(width("1."), None);
+        assert_eq!(width(""), None);
+    }
+}
@@ -0,0 +1,182 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use core::fmt;
+
+/// `LongWordPolicy` is the enum for specifying how `LineIter` divides a word
+/// which has no line break opportunity within the line width.
+///
+/// A long word is divided only with `OverflowPolicy::ForceBreak`, which is the
+/// default; `OverflowPolicy::Overflow` keeps it intact instead.
+///
+/// ```rust
+///     use linebreak::{Hyphenator, LineIter, LongWordPolicy};
+///
+///     let text = "see abcdefghijklmnopqrstuvwxyz";
+///
+///     let mut iter = LineIter::new(text, 10);
+///     assert_eq!(iter.next().unwrap(), "see");
+///     assert_eq!(iter.next().unwrap(), "abcdefghij");
+///     assert_eq!(iter.next().unwrap(), "klmnopqrst");
+///     assert_eq!(iter.next().unwrap(), "uvwxyz");
+///     assert!(iter.next().is_none());
+///
+///     // splits a word at every five letters.
+///     struct Splitter;
+///
+///     impl Hyphenator for Splitter {
+///         fn hyphenate(&self, _word: &str) -> Vec<usize> {
+///             Vec::new()
+///         }
+///
+///         fn split(&self, _word: &str, width: usize) -> Option<usize> {
+///             Some(width / 5 * 5)
+///         }
+///     }
+///
+///     let mut iter = LineIter::new(text, 12);
+///     iter.set_hyphenator(&Splitter);
+///     iter.set_long_word_policy(LongWordPolicy::Split);
+///     assert_eq!(iter.next().unwrap(), "see");
+///     assert_eq!(iter.next().unwrap(), "abcdefghij");
+///     assert_eq!(iter.next().unwrap(), "klmnopqrst");
+///     assert_eq!(iter.next().unwrap(), "uvwxyz");
+///     assert!(iter.next().is_none());
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LongWordPolicy {
+    /// A long word is broken at the end of the line (default).
+    #[default]
+    BreakAnywhere,
+
+    /// A long word is divided at the last boundary of the parts of an
+    /// identifier within the line: after `_` and `-`, between a digit and a
+    /// letter, and before an uppercase letter following a lowercase letter.
+    /// A word without such a boundary is broken at the end of the line.
+    Identifier,
+
+    /// A long word is divided at the position returned by `Hyphenator::split`
+    /// of the hyphenator set to `LineIter`.
+    /// Without a hyphenator, a long word is broken at the end of the line.
+    Split,
+}
+
+// Checks whether an identifier can be divided between the specified
+// characters, such as `snake_|case`, `camel|Case` and `utf|8`.
+pub(crate) fn is_identifier_boundary(prev: char, ch: char) -> bool {
+    let is_delimiter = |c: char| matches!(c, '_' | '-');
+    (is_delimiter(prev) && !is_delimiter(ch))
+        || (prev.is_numeric() && ch.is_alphabetic())
+        || (prev.is_alphabetic() && ch.is_numeric())
+        || (prev.is_lowercase() && ch.is_uppercase())
+}
+
+/// `OverflowPolicy` is the enum for specifying how `LineIter` treats a content
+/// which cannot be broken and is wider than the line, such as a long word, a
+/// URL kept by `UrlBreak::Keep` and a character wider than the line.
+///
+/// ```rust
+///     use linebreak::{LineIter, OverflowError, OverflowPolicy};
+///
+///     let text = "see abcdefghijkl";
+///
+///     let mut iter = LineIter::new(text, 8);
+///     iter.set_overflow_policy(OverflowPolicy::Overflow);
+///     assert_eq!(iter.next().unwrap(), "see");
+///     assert_eq!(iter.next().unwrap(), "abcdefghijkl");
+///     assert!(iter.next().is_none());
+///
+///     let mut iter = LineIter::new(text, 8);
+///     iter.set_overflow_policy(OverflowPolicy::Error);
+///     assert_eq!(iter.next().unwrap(), "see");
+///     assert!(iter.next().is_none());
+///     assert_eq!(iter.overflow_error(), Some(OverflowError { offset: 4, width: 8 }));
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum OverflowPolicy {
+    /// A content is broken to fit the line (default).
+    /// A long word is divided as specified by `LongWordPolicy`, and a content
+    /// which cannot be divided, such as a character wider than the line or a
+    /// URL kept by `UrlBreak::Keep`, is put on a line by itself.
+    #[default]
+    ForceBreak,
+
+    /// A long word is kept intact, and is output as a line which is wider
+    /// than the line width.
+    Overflow,
+
+    /// The iteration stops at a content, and `LineIter::overflow_error`
+    /// returns an `OverflowError`.
+    Error,
+}
+
+/// `OverflowError` is the struct for the error which stops `LineIter` with
+/// `OverflowPolicy::Error`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OverflowError {
+    /// The byte offset in the text at which the content starts.
+    pub offset: usize,
+    /// The width available for the content.
+    pub width: usize,
+}
+
+impl fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The content at {} cannot be broken within the width ({})",
+            self.offset, self.width
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OverflowError {}
+
+#[cfg(test)]
+mod test_of_long_word {
+    use super::*;
+
+    #[test]
+    fn test_long_word_policy() {
+        assert_eq!(LongWordPolicy::default(), LongWordPolicy::BreakAnywhere);
+        assert_ne!(LongWordPolicy::Split, LongWordPolicy::Identifier);
+    }
+
+    #[test]
+    fn test_is_identifier_boundary() {
+        assert!(is_identifier_boundary('_', 'n'));
+        assert!(is_identifier_boundary('-', 'N'));
+        assert!(is_identifier_boundary('l', 'C'));
+        assert!(is_identifier_boundary('f', '8'));
+        assert!(is_identifier_boundary('2', 'x'));
+        assert!(!is_identifier_boundary('_', '_'));
+        assert!(!is_identifier_boundary('a', '_'));
+        assert!(!is_identifier_boundary('A', 'B'));
+        assert!(!is_identifier_boundary('C', 'a'));
+        assert!(!is_identifier_boundary('1', '2'));
+    }
+
+    #[test]
+    fn test_overflow_policy() {
+        assert_eq!(OverflowPolicy::default(), OverflowPolicy::ForceBreak);
+        assert_ne!(OverflowPolicy::Overflow, OverflowPolicy::Error);
+    }
+
+    #[test]
+    fn test_overflow_error() {
+        let err = OverflowError {
+            offset: 4,
+            width: 8,
+        };
+        assert_eq!(
+            err.to_string(),
+            "The content at 4 cannot be broken within the width (8)"
+        );
+    }
+}
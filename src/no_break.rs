@@ -0,0 +1,215 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+/// `NoBreakRule` is the trait for deciding whether a line must not be broken at
+/// a space between two words, so that small tokens such as a number and its
+/// unit are not separated.
+///
+/// `LineIter` consults this trait at each space with the word before the space
+/// and the word after it, and treats the space as a part of a word if any rule
+/// returns true.
+///
+/// A closure which takes the two words and returns a bool implements this
+/// trait.
+///
+/// ```rust
+///     use linebreak::{LineIter, NoBreakRule};
+///
+///     struct Titles;
+///
+///     impl NoBreakRule for Titles {
+///         fn keeps_together(&self, before: &str, _after: &str) -> bool {
+///             matches!(before, "Mr." | "Mrs." | "Dr.")
+///         }
+///     }
+///
+///     let titles = Titles;
+///     let mut iter = LineIter::new("Call Dr. Smith now", 9);
+///     iter.add_no_break_rule(&titles);
+///     assert_eq!(iter.next().unwrap(), "Call");
+///     assert_eq!(iter.next().unwrap(), "Dr. Smith");
+///     assert_eq!(iter.next().unwrap(), "now");
+///     assert!(iter.next().is_none());
+/// ```
+pub trait NoBreakRule {
+    /// Returns true if a line must not be broken at the space between the
+    /// specified words.
+    fn keeps_together(&self, before: &str, after: &str) -> bool;
+}
+
+impl<F> NoBreakRule for F
+where
+    F: Fn(&str, &str) -> bool,
+{
+    fn keeps_together(&self, before: &str, after: &str) -> bool {
+        self(before, after)
+    }
+}
+
+/// `NoBreakPair` is the enum for the built-in rules of the pairs of words which
+/// are kept together on a line.
+///
+/// ```rust
+///     use linebreak::{LineIter, NoBreakPair};
+///
+///     let mut iter = LineIter::new("It weighs 25 kg in total.", 12);
+///     iter.add_no_break_rule(&NoBreakPair::NumberUnit);
+///     assert_eq!(iter.next().unwrap(), "It weighs");
+///     assert_eq!(iter.next().unwrap(), "25 kg in");
+///     assert_eq!(iter.next().unwrap(), "total.");
+///     assert!(iter.next().is_none());
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NoBreakPair {
+    /// A number followed by a unit or a percent sign, such as `10 kg`,
+    /// `5 %` and `3.5 GHz`.
+    NumberUnit,
+
+    /// A single-letter word followed by the next word, such as the Czech,
+    /// Slovak and Polish prepositions `v`, `z` and `w`.
+    SingleLetterWord,
+
+    /// A section or paragraph sign followed by a number, such as `§ 12`.
+    SectionSign,
+
+    /// An abbreviation or an initial followed by the next word, such as
+    /// `e.g. this`, `Dr. Smith` and `商品No. 5`, so that the period of an
+    /// abbreviation is not taken as the end of a sentence.
+    Abbreviation,
+}
+
+impl NoBreakRule for NoBreakPair {
+    fn keeps_together(&self, before: &str, after: &str) -> bool {
+        let before = before.trim_start_matches(is_opening);
+        let after = after.trim_end_matches(is_closing);
+        match self {
+            NoBreakPair::NumberUnit => is_number(before) && UNITS.contains(&after),
+            NoBreakPair::SingleLetterWord => {
+                let mut chars = before.chars();
+                matches!((chars.next(), chars.next()), (Some(c), None) if c.is_alphabetic())
+            }
+            NoBreakPair::SectionSign => {
+                matches!(before, "§" | "§§" | "¶")
+                    && after.starts_with(|c: char| c.is_ascii_digit())
+            }
+            NoBreakPair::Abbreviation => is_abbreviation(before),
+        }
+    }
+}
+
+fn is_opening(ch: char) -> bool {
+    matches!(ch, '(' | '[' | '{' | '"' | '\'' | '“' | '‘' | '«')
+}
+
+fn is_closing(ch: char) -> bool {
+    matches!(
+        ch,
+        '.' | ',' | ':' | ';' | '!' | '?' | ')' | ']' | '}' | '"' | '\'' | '”' | '’' | '»'
+    )
+}
+
+// Checks whether the specified word is an abbreviation which ends with a
+// period, such as `e.g.`, `Dr.` and `商品No.`, or an initial such as `J.`.
+pub(crate) fn is_abbreviation(word: &str) -> bool {
+    let word = word.trim_start_matches(is_opening);
+    let Some(stem) = word.strip_suffix('.') else {
+        return false;
+    };
+    let mut chars = stem.chars();
+    if matches!((chars.next(), chars.next()), (Some(c), None) if c.is_uppercase()) {
+        return true;
+    }
+    // an abbreviation can follow a word of a language which does not put
+    // spaces between words, such as `商品No.`.
+    ABBREVIATIONS
+        .iter()
+        .any(|abbr| match word.strip_suffix(abbr) {
+            Some(head) => !head.ends_with(|c: char| c.is_ascii_alphanumeric()),
+            None => false,
+        })
+}
+
+fn is_number(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_digit())
+        && s.ends_with(|c: char| c.is_ascii_digit())
+        && s.chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | ','))
+}
+
+const UNITS: &[&str] = &[
+    "%", "‰", "°", "°C", "°F", "K", // ratios and temperatures
+    "mm", "cm", "m", "km", "in", "ft", "mi", // lengths
+    "mg", "g", "kg", "t", "lb", "oz", // weights
+    "ml", "l", "L", // volumes
+    "ms", "s", "min", "h", // times
+    "Hz", "kHz", "MHz", "GHz", // frequencies
+    "B", "kB", "KB", "MB", "GB", "TB", "KiB", "MiB", "GiB", "TiB", // sizes
+    "V", "A", "mA", "W", "kW", "Wh", "kWh", "Ω", // electricity
+    "km/h", "m/s", "mph", // speeds
+    "px", "pt", "em", // typography
+    "€", "$", "£", "¥", // currencies
+];
+
+const ABBREVIATIONS: &[&str] = &[
+    "Mr.", "Mrs.", "Ms.", "Dr.", "Prof.", "Sr.", "Jr.", "St.", "Mt.", // titles
+    "e.g.", "i.e.", "cf.", "vs.", "approx.", "U.S.", "U.K.", // latin and others
+    "No.", "Nos.", "Vol.", "Fig.", "Eq.", "Ch.", "Sec.", "pp.", // references
+];
+
+#[cfg(test)]
+mod test_of_no_break {
+    use super::*;
+
+    #[test]
+    fn test_closure_as_no_break_rule() {
+        let f = |before: &str, after: &str| before == "Mr." && !after.is_empty();
+        assert!(f.keeps_together("Mr.", "Smith"));
+        assert!(!f.keeps_together("Mrs.", "Smith"));
+    }
+
+    #[test]
+    fn test_number_unit() {
+        let rule = NoBreakPair::NumberUnit;
+        assert!(rule.keeps_together("10", "kg"));
+        assert!(rule.keeps_together("50", "%"));
+        assert!(rule.keeps_together("3.5", "GHz,"));
+        assert!(rule.keeps_together("(1,000", "km)"));
+        assert!(!rule.keeps_together("10", "apples"));
+        assert!(!rule.keeps_together("ten", "kg"));
+        assert!(!rule.keeps_together("10,", "kg"));
+    }
+
+    #[test]
+    fn test_single_letter_word() {
+        let rule = NoBreakPair::SingleLetterWord;
+        assert!(rule.keeps_together("v", "Praze"));
+        assert!(rule.keeps_together("(a", "b"));
+        assert!(rule.keeps_together("Z", "domu"));
+        assert!(!rule.keeps_together("ve", "městě"));
+        assert!(!rule.keeps_together("1", "x"));
+    }
+
+    #[test]
+    fn test_section_sign() {
+        let rule = NoBreakPair::SectionSign;
+        assert!(rule.keeps_together("§", "12"));
+        assert!(rule.keeps_together("§§", "3–5"));
+        assert!(rule.keeps_together("¶", "4."));
+        assert!(!rule.keeps_together("§", "a"));
+        assert!(!rule.keeps_together("see", "12"));
+    }
+
+    #[test]
+    fn test_abbreviation() {
+        let rule = NoBreakPair::Abbreviation;
+        assert!(rule.keeps_together("e.g.", "this"));
+        assert!(rule.keeps_together("(Dr.", "Smith"));
+        assert!(rule.keeps_together("J.", "Smith"));
+        assert!(rule.keeps_together("商品No.", "5"));
+        assert!(!rule.keeps_together("Piano.", "The"));
+        assert!(!rule.keeps_together("end.", "The"));
+        assert!(!rule.keeps_together("e.g.,", "this"));
+        assert!(!rule.keeps_together("Dr", "Smith"));
+    }
+}
@@ -0,0 +1,1172 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::ansi::AnsiPolicy;
+use crate::gutter::Gutter;
+use crate::hyphenator::Hyphenator;
+use crate::linebreak::{HangingPunctuation, KinsokuLevel};
+use crate::long_word::{LongWordPolicy, OverflowPolicy};
+use crate::no_break::NoBreakRule;
+use crate::unicode::{char_width_with, text_width_with, AmbiguousWidth, NonPrintPolicy};
+use crate::url::UrlBreak;
+use crate::width_provider::WidthProvider;
+
+/// `OptionsError` is the enum for the reasons why an `Options` instance is
+/// invalid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OptionsError {
+    /// The line width is zero.
+    ZeroWidth,
+
+    /// The total width of the prefix, the suffix and the indentation is not
+    /// less than the line width, so no character can be put on a line.
+    NoRoomForText {
+        /// The line width.
+        width: usize,
+        /// The total width of the prefix, the suffix and the indentation.
+        decoration_width: usize,
+    },
+}
+
+impl fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionsError::ZeroWidth => write!(f, "The line width is zero"),
+            OptionsError::NoRoomForText {
+                width,
+                decoration_width,
+            } => write!(
+                f,
+                "The width of prefix, suffix and indent ({}) is not less than \
+                the line width ({})",
+                decoration_width, width
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OptionsError {}
+
+/// `Shared` is the struct for holding a user-defined object in `Options`.
+/// Two instances are equal when they refer to the same object.
+pub(crate) struct Shared<T: ?Sized>(pub(crate) Arc<T>);
+
+impl<T: ?Sized> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Shared(Arc::clone(&self.0))
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for Shared<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Shared({:p})", Arc::as_ptr(&self.0) as *const u8)
+    }
+}
+
+impl<T: ?Sized> PartialEq for Shared<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<T: ?Sized> Eq for Shared<T> {}
+
+/// `BreakMode` is the enum for selecting the rules which determine line break
+/// opportunities.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BreakMode {
+    /// The built-in rules of this crate, which are designed for English and
+    /// Japanese texts.
+    #[default]
+    Basic,
+
+    /// The rules based on the Unicode Line Breaking Algorithm (UAX #14).
+    /// This mode uses the Line_Break property of each character and the pair
+    /// rules of UAX #14, so that texts in other languages are also broken at
+    /// appropriate positions.
+    /// Characters of the class SA (e.g. Thai), which need a dictionary, are
+    /// resolved to AL, so they are broken only at spaces.
+    Uax14,
+
+    /// The rules for source code, which break a line after commas,
+    /// semicolons, opening brackets and operators, and at spaces, but never
+    /// inside of string literals.
+    /// This mode is for displaying long lines of generated code readably.
+    Code,
+}
+
+/// `Align` is the enum for specifying the alignment of the lines output by
+/// `LineIter`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Align {
+    /// Lines are aligned to the left (default).
+    #[default]
+    Left,
+
+    /// Lines are aligned to the right of the line width.
+    Right,
+
+    /// Lines are centered within the line width.
+    Center,
+}
+
+/// `FormFeed` is the enum for specifying how `LineIter` treats form feeds
+/// (U+000C).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FormFeed {
+    /// A form feed is treated according to the `BreakMode`: as a space in
+    /// `BreakMode::Basic` and as a line break in `BreakMode::Uax14` (default).
+    #[default]
+    Normal,
+
+    /// A form feed breaks a line and is removed from the output.
+    Break,
+
+    /// A form feed breaks a line and is output on its own line.
+    Emit,
+}
+
+/// `LineEnding` is the enum for specifying the terminator which is put between
+/// wrapped lines when they are joined into a text.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LineEnding {
+    /// Lines are joined with a line feed `\n` (default).
+    #[default]
+    Lf,
+
+    /// Lines are joined with a carriage return and a line feed `\r\n`.
+    CrLf,
+
+    /// Lines are joined without any terminator.
+    None,
+}
+
+impl LineEnding {
+    /// Returns the terminator string of this line ending.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::None => "",
+        }
+    }
+}
+
+/// `IdeographicSpace` is the enum for specifying how `LineIter` treats
+/// ideographic spaces (U+3000).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum IdeographicSpace {
+    /// An ideographic space is a space, at which a line can be broken and
+    /// which is trimmed at the start and the end of a line (default).
+    #[default]
+    Space,
+
+    /// An ideographic space is a printable character of width 2, at which a
+    /// line is not broken and which is kept at the start and the end of a
+    /// line, such as the indentation of a Japanese paragraph.
+    Printable,
+}
+
+/// `Options` is the struct for configuring a `LineIter` instance at once.
+///
+/// The settings are given with the chained methods and validated when a
+/// `LineIter` instance is created with `LineIter::with_options`.
+///
+/// With the `serde` feature, this struct can be loaded from configuration
+/// files.
+/// The settings which are missing in the files are the defaults (the width is
+/// 80), and a `Hyphenator` and a `WidthProvider` are not serialized nor
+/// deserialized.
+///
+/// ```rust
+///     use linebreak::{BreakMode, LineIter, Options};
+///
+///     let opts = Options::new(12)
+///         .first_indent("* ")
+///         .indent("  ")
+///         .break_mode(BreakMode::Uax14);
+///
+///     let mut iter = LineIter::with_options("abc def ghi jkl mno", &opts).unwrap();
+///     assert_eq!(iter.next().unwrap(), "* abc def");
+///     assert_eq!(iter.next().unwrap(), "  ghi jkl");
+///     assert_eq!(iter.next().unwrap(), "  mno");
+///     assert!(iter.next().is_none());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default = "Options::default_for_serde"))]
+pub struct Options {
+    pub(crate) width: usize,
+    pub(crate) indent: String,
+    pub(crate) first_indent: Option<String>,
+    pub(crate) first_line_offset: usize,
+    pub(crate) first_line_width: Option<usize>,
+    pub(crate) first_line_count: usize,
+    pub(crate) prefix: String,
+    pub(crate) suffix: String,
+    pub(crate) continuation_marker: String,
+    pub(crate) shell_command: bool,
+    pub(crate) line_numbers: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) gutter: Option<(usize, Shared<dyn Gutter + Send + Sync>)>,
+    pub(crate) max_lines: usize,
+    pub(crate) placeholder: String,
+    pub(crate) tab_width: usize,
+    pub(crate) ambiguous_width: AmbiguousWidth,
+    pub(crate) width_overrides: BTreeMap<char, usize>,
+    pub(crate) break_mode: BreakMode,
+    pub(crate) quote_pairs: Vec<(char, char)>,
+    pub(crate) align: Align,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) hyphenator: Option<Shared<dyn Hyphenator + Send + Sync>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) width_provider: Option<Shared<dyn WidthProvider + Send + Sync>>,
+    pub(crate) paragraph_mode: bool,
+    pub(crate) break_on_hyphen: bool,
+    pub(crate) quote_aware: bool,
+    pub(crate) break_hangul: bool,
+    pub(crate) kinsoku_level: KinsokuLevel,
+    pub(crate) burasage: bool,
+    pub(crate) hanging: HangingPunctuation,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) no_break_rules: Vec<Shared<dyn NoBreakRule + Send + Sync>>,
+    pub(crate) protected_delimiters: Vec<(String, String)>,
+    pub(crate) sentence_slack: usize,
+    pub(crate) widow_control: bool,
+    pub(crate) url_break: UrlBreak,
+    pub(crate) long_word_policy: LongWordPolicy,
+    pub(crate) overflow_policy: OverflowPolicy,
+    pub(crate) keep_zero_width: bool,
+    pub(crate) non_print_policy: NonPrintPolicy,
+    pub(crate) ansi_policy: AnsiPolicy,
+    pub(crate) strict_line_breaks: bool,
+    pub(crate) form_feed: FormFeed,
+    pub(crate) ideographic_space: IdeographicSpace,
+    pub(crate) line_ending: LineEnding,
+    pub(crate) preserve_leading_whitespace: bool,
+    pub(crate) auto_indent: bool,
+    pub(crate) auto_indent_hang: usize,
+    pub(crate) list_items: bool,
+    #[cfg(feature = "bidi")]
+    pub(crate) bidi: bool,
+}
+
+impl Options {
+    // The settings which are missing in a deserialized data are the defaults,
+    // and the width is the tentative value used when the terminal width is
+    // unknown.
+    #[cfg(feature = "serde")]
+    fn default_for_serde() -> Options {
+        Options::new(80)
+    }
+
+    /// Creates an `Options` instance with the specified line width and the
+    /// default settings.
+    pub fn new(width: usize) -> Options {
+        Options {
+            width,
+            indent: String::new(),
+            first_indent: None,
+            first_line_offset: 0,
+            first_line_width: None,
+            first_line_count: 1,
+            prefix: String::new(),
+            suffix: String::new(),
+            continuation_marker: String::new(),
+            shell_command: false,
+            line_numbers: 0,
+            gutter: None,
+            max_lines: 0,
+            placeholder: "…".to_string(),
+            tab_width: 0,
+            ambiguous_width: AmbiguousWidth::Wide,
+            width_overrides: BTreeMap::new(),
+            break_mode: BreakMode::Basic,
+            quote_pairs: Vec::new(),
+            align: Align::Left,
+            hyphenator: None,
+            width_provider: None,
+            paragraph_mode: false,
+            break_on_hyphen: false,
+            quote_aware: true,
+            break_hangul: false,
+            kinsoku_level: KinsokuLevel::Strict,
+            burasage: false,
+            hanging: HangingPunctuation::default(),
+            no_break_rules: Vec::new(),
+            protected_delimiters: Vec::new(),
+            sentence_slack: 0,
+            widow_control: false,
+            url_break: UrlBreak::Normal,
+            long_word_policy: LongWordPolicy::BreakAnywhere,
+            overflow_policy: OverflowPolicy::ForceBreak,
+            keep_zero_width: true,
+            non_print_policy: NonPrintPolicy::Strip,
+            ansi_policy: AnsiPolicy::Ignore,
+            strict_line_breaks: false,
+            form_feed: FormFeed::Normal,
+            ideographic_space: IdeographicSpace::Space,
+            line_ending: LineEnding::Lf,
+            preserve_leading_whitespace: false,
+            auto_indent: false,
+            auto_indent_hang: 0,
+            list_items: false,
+            #[cfg(feature = "bidi")]
+            bidi: false,
+        }
+    }
+
+    /// Sets the line width.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets an indentation for the lines.
+    /// If a first indentation is set, this indentation is applied to the lines
+    /// other than the first line of each paragraph.
+    pub fn indent(mut self, indent: &str) -> Self {
+        self.indent = indent.to_string();
+        self
+    }
+
+    /// Sets an indentation for the first line of each paragraph.
+    pub fn first_indent(mut self, indent: &str) -> Self {
+        self.first_indent = Some(indent.to_string());
+        self
+    }
+
+    /// Sets the number of the columns by which the first output line is
+    /// shortened, such as the width of a prompt which is already printed.
+    /// An offset which leaves no room for text is an error of `validate`.
+    pub fn first_line_offset(mut self, offset: usize) -> Self {
+        self.first_line_offset = offset;
+        self
+    }
+
+    /// Sets the width of the first lines, which are as many as the number set
+    /// by `first_line_count` (one by default).
+    pub fn first_line_width(mut self, width: usize) -> Self {
+        self.first_line_width = Some(width);
+        self
+    }
+
+    /// Sets the number of the first lines to which the width set by
+    /// `first_line_width` is applied.
+    pub fn first_line_count(mut self, count: usize) -> Self {
+        self.first_line_count = count;
+        self
+    }
+
+    /// Sets the width of the lines after the first lines.
+    /// This is the same as `width`, and reads naturally together with
+    /// `first_line_width`.
+    pub fn subsequent_width(self, width: usize) -> Self {
+        self.width(width)
+    }
+
+    /// Sets a string which is put at the beginning of every line.
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Sets a string which is put at the end of every line.
+    pub fn suffix(mut self, suffix: &str) -> Self {
+        self.suffix = suffix.to_string();
+        self
+    }
+
+    /// Sets a marker which is put at the end of every line which is continued
+    /// to the next line.
+    pub fn continuation_marker(mut self, marker: &str) -> Self {
+        self.continuation_marker = marker.to_string();
+        self
+    }
+
+    /// Sets whether to wrap the text as shell command lines, which are broken
+    /// only between arguments and continued with ` \\`.
+    /// If no continuation marker is set, ` \\` is used as the marker.
+    pub fn shell_command(mut self, enabled: bool) -> Self {
+        self.shell_command = enabled;
+        self
+    }
+
+    /// Sets the number of the digits of the line numbers which are put at the
+    /// beginning of every line.
+    /// If this value is zero (default), no line number is put.
+    pub fn line_numbers(mut self, digits: usize) -> Self {
+        self.line_numbers = digits;
+        self
+    }
+
+    /// Sets a `Gutter` with its width, which generates a string put at the
+    /// beginning of every line instead of the line numbers.
+    pub fn gutter<G>(mut self, width: usize, gutter: G) -> Self
+    where
+        G: Gutter + Send + Sync + 'static,
+    {
+        self.gutter = Some((width, Shared(Arc::new(gutter))));
+        self
+    }
+
+    /// Sets the maximum number of the output lines.
+    /// If the text needs more lines, the last line is truncated and ends with
+    /// the placeholder.
+    /// If this value is zero (default), the number of lines is not limited.
+    pub fn max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = max_lines;
+        self
+    }
+
+    /// Sets a string which is put at the end of the last line truncated by
+    /// the maximum number of lines.
+    /// The default placeholder is `"…"`.
+    pub fn placeholder(mut self, placeholder: &str) -> Self {
+        self.placeholder = placeholder.to_string();
+        self
+    }
+
+    /// Sets the width of a tab stop.
+    /// If this value is greater than zero, a tab character is expanded to
+    /// spaces up to the next tab stop.
+    /// If this value is zero (default), a tab character is treated as a line
+    /// break opportunity and removed from the output.
+    pub fn tab_width(mut self, tab_width: usize) -> Self {
+        self.tab_width = tab_width;
+        self
+    }
+
+    /// Sets the display width of the characters whose East-Asian-Width is
+    /// Ambiguous.
+    pub fn ambiguous_width(mut self, ambiguous_width: AmbiguousWidth) -> Self {
+        self.ambiguous_width = ambiguous_width;
+        self
+    }
+
+    /// Sets whether to measure the widths of characters in CJK contexts, in
+    /// which ambiguous characters are wide, as the `_cjk` functions of the
+    /// `unicode-width` crate do.
+    /// This is the same as setting `AmbiguousWidth::Wide` or
+    /// `AmbiguousWidth::Narrow` with `ambiguous_width`.
+    pub fn cjk(mut self, enabled: bool) -> Self {
+        self.ambiguous_width = match enabled {
+            true => AmbiguousWidth::Wide,
+            false => AmbiguousWidth::Narrow,
+        };
+        self
+    }
+
+    /// Sets the display width of the ambiguous characters which is recommended
+    /// for the current locale, given by `AmbiguousWidth::from_locale`.
+    #[cfg(feature = "std")]
+    pub fn ambiguous_from_locale(mut self) -> Self {
+        self.ambiguous_width = AmbiguousWidth::from_locale();
+        self
+    }
+
+    /// Sets the display width of the specified character, which overrides the
+    /// width given by the built-in `char_width` function or a `WidthProvider`.
+    pub fn width_override(mut self, ch: char, width: usize) -> Self {
+        self.width_overrides.insert(ch, width);
+        self
+    }
+
+    /// Sets the rules which determine line break opportunities.
+    pub fn break_mode(mut self, mode: BreakMode) -> Self {
+        self.break_mode = mode;
+        self
+    }
+
+    /// Sets the strictness of the Japanese line break rules in JIS X 4051.
+    pub fn kinsoku_level(mut self, level: KinsokuLevel) -> Self {
+        self.kinsoku_level = level;
+        self
+    }
+
+    /// Sets whether to let the Japanese comma and full stop hang past the end
+    /// of a line (burasage).
+    pub fn burasage(mut self, enabled: bool) -> Self {
+        self.burasage = enabled;
+        self
+    }
+
+    /// Sets the classes of the Latin punctuation which hang outside the text
+    /// block.
+    pub fn hanging_punctuation(mut self, hanging: HangingPunctuation) -> Self {
+        self.hanging = hanging;
+        self
+    }
+
+    /// Adds a `NoBreakRule` which decides whether a line must not be broken at
+    /// a space between two words.
+    pub fn no_break_rule<R>(mut self, rule: R) -> Self
+    where
+        R: NoBreakRule + Send + Sync + 'static,
+    {
+        self.no_break_rules.push(Shared(Arc::new(rule)));
+        self
+    }
+
+    /// Adds a pair of the delimiters which enclose a span of the text which
+    /// must not be broken.
+    pub fn protected_delimiters(mut self, open: &str, close: &str) -> Self {
+        self.protected_delimiters
+            .push((open.to_string(), close.to_string()));
+        self
+    }
+
+    /// Sets the maximum width which can be left unused at the end of a line to
+    /// break the line at a sentence boundary.
+    /// The default is 0, which disables this feature.
+    pub fn sentence_slack(mut self, slack: usize) -> Self {
+        self.sentence_slack = slack;
+        self
+    }
+
+    /// Sets whether to avoid leaving a single word alone on the last line of a
+    /// paragraph.
+    pub fn widow_control(mut self, enabled: bool) -> Self {
+        self.widow_control = enabled;
+        self
+    }
+
+    /// Adds a pair of the opening and the closing quotation marks, so that a
+    /// line is not broken after the opening mark nor before the closing mark.
+    pub fn quote_pair(mut self, open: char, close: char) -> Self {
+        self.quote_pairs.push((open, close));
+        self
+    }
+
+    /// Sets the alignment of the lines within the line width.
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Sets a `Hyphenator` which splits a word running over the end of a line.
+    pub fn hyphenator<H>(mut self, hyphenator: H) -> Self
+    where
+        H: Hyphenator + Send + Sync + 'static,
+    {
+        self.hyphenator = Some(Shared(Arc::new(hyphenator)));
+        self
+    }
+
+    /// Sets a `WidthProvider` which measures the display widths of characters
+    /// instead of the built-in `char_width` function.
+    pub fn width_provider<W>(mut self, provider: W) -> Self
+    where
+        W: WidthProvider + Send + Sync + 'static,
+    {
+        self.width_provider = Some(Shared(Arc::new(provider)));
+        self
+    }
+
+    /// Sets whether to reflow the text by paragraphs, which treats a single
+    /// line break as a space and separates paragraphs only by blank lines.
+    pub fn paragraph_mode(mut self, enabled: bool) -> Self {
+        self.paragraph_mode = enabled;
+        self
+    }
+
+    /// Sets whether to break a line after a hyphen between letters or digits.
+    pub fn break_on_hyphen(mut self, enabled: bool) -> Self {
+        self.break_on_hyphen = enabled;
+        self
+    }
+
+    /// Sets whether to pair the ASCII quotation marks `"` and `'`.
+    /// If disabled, they are treated as ordinary characters, which is suitable
+    /// for source code, shell commands and CSV with unbalanced quotes.
+    pub fn quote_aware(mut self, enabled: bool) -> Self {
+        self.quote_aware = enabled;
+        self
+    }
+
+    /// Sets whether to break a line between any Hangul characters, instead of
+    /// only at spaces.
+    pub fn break_hangul(mut self, enabled: bool) -> Self {
+        self.break_hangul = enabled;
+        self
+    }
+
+    /// Sets how to break URLs and file paths.
+    pub fn url_break(mut self, url_break: UrlBreak) -> Self {
+        self.url_break = url_break;
+        self
+    }
+
+    /// Sets how to divide a word which is wider than a line.
+    pub fn long_word_policy(mut self, policy: LongWordPolicy) -> Self {
+        self.long_word_policy = policy;
+        self
+    }
+
+    /// Sets how to treat a content which cannot be broken and is wider than a
+    /// line.
+    pub fn overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    /// Sets whether to keep the characters which have no width and are
+    /// attached to the previous character, such as combining marks.
+    /// This is enabled by default.
+    pub fn keep_zero_width(mut self, enabled: bool) -> Self {
+        self.keep_zero_width = enabled;
+        self
+    }
+
+    /// Sets how to output the non-printable characters, such as control
+    /// characters.
+    /// The default policy is `NonPrintPolicy::Strip`.
+    pub fn non_print_policy(mut self, policy: NonPrintPolicy) -> Self {
+        self.non_print_policy = policy;
+        self
+    }
+
+    /// Sets how to treat the ANSI escape sequences, such as the SGR sequences
+    /// for colors.
+    /// The default policy is `AnsiPolicy::Ignore`.
+    pub fn ansi_policy(mut self, policy: AnsiPolicy) -> Self {
+        self.ansi_policy = policy;
+        self
+    }
+
+    /// Sets whether to treat each of CR and LF in a CRLF sequence as a line
+    /// break.
+    /// This is disabled by default.
+    pub fn strict_line_breaks(mut self, enabled: bool) -> Self {
+        self.strict_line_breaks = enabled;
+        self
+    }
+
+    /// Sets how to treat form feeds (U+000C).
+    /// The default is `FormFeed::Normal`.
+    pub fn form_feed(mut self, form_feed: FormFeed) -> Self {
+        self.form_feed = form_feed;
+        self
+    }
+
+    /// Sets how to treat ideographic spaces (U+3000).
+    /// The default is `IdeographicSpace::Space`.
+    pub fn ideographic_space(mut self, ideographic_space: IdeographicSpace) -> Self {
+        self.ideographic_space = ideographic_space;
+        self
+    }
+
+    /// Sets the terminator which `WrapWriter` and `Wrapped` put between lines.
+    /// The default is `LineEnding::Lf`.
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Sets whether to keep the spaces at the start of each line of the source
+    /// text.
+    /// This is disabled by default.
+    pub fn preserve_leading_whitespace(mut self, enabled: bool) -> Self {
+        self.preserve_leading_whitespace = enabled;
+        self
+    }
+
+    /// Sets whether to indent the wrapped lines of each source line with the
+    /// leading whitespace of the source line.
+    /// This is disabled by default.
+    pub fn auto_indent(mut self, enabled: bool) -> Self {
+        self.auto_indent = enabled;
+        self
+    }
+
+    /// Sets the number of the spaces which are added to the indentation
+    /// detected by the auto indent mode for the wrapped lines.
+    pub fn auto_indent_hang(mut self, hang: usize) -> Self {
+        self.auto_indent_hang = hang;
+        self
+    }
+
+    /// Sets whether to recognize bullet and numbered list markers at the start
+    /// of each source line or paragraph, and indent the wrapped lines of a list
+    /// item with the width of its marker.
+    /// This is disabled by default.
+    pub fn list_items(mut self, enabled: bool) -> Self {
+        self.list_items = enabled;
+        self
+    }
+
+    /// Sets whether to reorder the lines of bidirectional texts for display.
+    /// This is disabled by default, and is available with the `bidi` feature.
+    #[cfg(feature = "bidi")]
+    pub fn bidi(mut self, enabled: bool) -> Self {
+        self.bidi = enabled;
+        self
+    }
+
+    /// Checks whether the settings of this instance are valid.
+    ///
+    /// ```rust
+    ///     use linebreak::{Options, OptionsError};
+    ///
+    ///     assert_eq!(Options::new(10).indent("  ").validate(), Ok(()));
+    ///     assert_eq!(Options::new(0).validate(), Err(OptionsError::ZeroWidth));
+    ///     assert_eq!(
+    ///         Options::new(4).prefix("// ").indent("  ").validate(),
+    ///         Err(OptionsError::NoRoomForText { width: 4, decoration_width: 5 })
+    ///     );
+    /// ```
+    pub fn validate(&self) -> Result<(), OptionsError> {
+        // the first lines are validated with the narrower one of their width
+        // and the width of the subsequent lines.
+        let width = match self.first_line_width {
+            Some(first_width) if self.first_line_count > 0 => first_width.min(self.width),
+            _ => self.width,
+        };
+        if width == 0 {
+            return Err(OptionsError::ZeroWidth);
+        }
+
+        let char_width = |ch: char| match &self.width_provider {
+            Some(provider) => provider.0.char_width(ch),
+            None => char_width_with(ch, self.ambiguous_width),
+        };
+        let text_width = |text: &str| match (&self.width_provider, self.width_overrides.is_empty())
+        {
+            (_, false) => text
+                .chars()
+                .map(|ch| {
+                    self.width_overrides
+                        .get(&ch)
+                        .copied()
+                        .unwrap_or_else(|| char_width(ch))
+                })
+                .sum(),
+            (Some(provider), true) => provider.0.text_width(text),
+            (None, true) => text_width_with(text, self.ambiguous_width),
+        };
+        let mut indent_width = text_width(&self.indent);
+        if let Some(first_indent) = &self.first_indent {
+            indent_width = indent_width.max(text_width(first_indent));
+        }
+        let mut marker_width = text_width(&self.continuation_marker);
+        if self.shell_command {
+            // the continued lines of a shell command are indented by two more
+            // spaces and end with " \\" unless the marker is set.
+            indent_width = indent_width.max(text_width(&self.indent) + 2);
+            if self.continuation_marker.is_empty() {
+                marker_width = 2;
+            }
+        }
+        let gutter_width = match &self.gutter {
+            Some((width, _)) => *width,
+            None if self.line_numbers > 0 => self.line_numbers + 1,
+            None => 0,
+        };
+        // the first line is also shortened by the offset.
+        let decoration_width = text_width(&self.prefix)
+            + text_width(&self.suffix)
+            + marker_width
+            + indent_width
+            + gutter_width
+            + self.first_line_offset;
+        if decoration_width >= width {
+            return Err(OptionsError::NoRoomForText {
+                width,
+                decoration_width,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_of_options {
+    use super::*;
+    use crate::no_break::NoBreakPair;
+
+    #[test]
+    fn test_break_mode_default() {
+        assert_eq!(BreakMode::default(), BreakMode::Basic);
+    }
+
+    #[test]
+    fn test_new() {
+        let opts = Options::new(80);
+        assert_eq!(opts.width, 80);
+        assert_eq!(opts.indent, "");
+        assert_eq!(opts.first_indent, None);
+        assert_eq!(opts.first_line_offset, 0);
+        assert_eq!(opts.first_line_width, None);
+        assert_eq!(opts.first_line_count, 1);
+        assert_eq!(opts.prefix, "");
+        assert_eq!(opts.suffix, "");
+        assert_eq!(opts.continuation_marker, "");
+        assert!(!opts.shell_command);
+        assert_eq!(opts.line_numbers, 0);
+        assert!(opts.gutter.is_none());
+        assert_eq!(opts.max_lines, 0);
+        assert_eq!(opts.placeholder, "…");
+        assert_eq!(opts.tab_width, 0);
+        assert_eq!(opts.ambiguous_width, AmbiguousWidth::Wide);
+        assert!(opts.width_overrides.is_empty());
+        assert_eq!(opts.break_mode, BreakMode::Basic);
+        assert!(opts.quote_pairs.is_empty());
+        assert_eq!(opts.align, Align::Left);
+        assert!(opts.hyphenator.is_none());
+        assert!(opts.width_provider.is_none());
+        assert!(!opts.paragraph_mode);
+        assert!(!opts.break_on_hyphen);
+        assert!(opts.quote_aware);
+        assert!(!opts.break_hangul);
+        assert_eq!(opts.kinsoku_level, KinsokuLevel::Strict);
+        assert!(!opts.burasage);
+        assert_eq!(opts.hanging, HangingPunctuation::default());
+        assert!(opts.no_break_rules.is_empty());
+        assert!(opts.protected_delimiters.is_empty());
+        assert_eq!(opts.sentence_slack, 0);
+        assert!(!opts.widow_control);
+        assert_eq!(opts.url_break, UrlBreak::Normal);
+        assert_eq!(opts.long_word_policy, LongWordPolicy::BreakAnywhere);
+        assert_eq!(opts.overflow_policy, OverflowPolicy::ForceBreak);
+        assert!(opts.keep_zero_width);
+        assert_eq!(opts.non_print_policy, NonPrintPolicy::Strip);
+        assert_eq!(opts.ansi_policy, AnsiPolicy::Ignore);
+        assert!(!opts.strict_line_breaks);
+        assert_eq!(opts.form_feed, FormFeed::Normal);
+        assert_eq!(opts.ideographic_space, IdeographicSpace::Space);
+        assert_eq!(opts.line_ending, LineEnding::Lf);
+        assert!(!opts.preserve_leading_whitespace);
+        assert!(!opts.auto_indent);
+        assert_eq!(opts.auto_indent_hang, 0);
+        assert!(!opts.list_items);
+    }
+
+    #[test]
+    fn test_chained_setters() {
+        let opts = Options::new(80)
+            .width(40)
+            .indent("  ")
+            .first_indent("- ")
+            .first_line_offset(7)
+            .first_line_width(30)
+            .first_line_count(2)
+            .prefix("> ")
+            .suffix(" <")
+            .continuation_marker("\\")
+            .shell_command(true)
+            .line_numbers(3)
+            .max_lines(5)
+            .placeholder("...")
+            .tab_width(4)
+            .ambiguous_width(AmbiguousWidth::Narrow)
+            .width_override('\u{E0B0}', 1)
+            .break_mode(BreakMode::Uax14)
+            .quote_pair('⸢', '⸣')
+            .align(Align::Center)
+            .paragraph_mode(true)
+            .break_on_hyphen(true)
+            .quote_aware(false)
+            .break_hangul(true)
+            .kinsoku_level(KinsokuLevel::Loose)
+            .burasage(true)
+            .hanging_punctuation(HangingPunctuation::ALL)
+            .no_break_rule(NoBreakPair::NumberUnit)
+            .protected_delimiters("{", "}")
+            .sentence_slack(8)
+            .widow_control(true)
+            .url_break(UrlBreak::Keep)
+            .long_word_policy(LongWordPolicy::Identifier)
+            .overflow_policy(OverflowPolicy::Error)
+            .keep_zero_width(false)
+            .non_print_policy(NonPrintPolicy::EscapeCaret)
+            .ansi_policy(AnsiPolicy::Reapply)
+            .strict_line_breaks(true)
+            .form_feed(FormFeed::Emit)
+            .ideographic_space(IdeographicSpace::Printable)
+            .line_ending(LineEnding::CrLf)
+            .preserve_leading_whitespace(true)
+            .auto_indent(true)
+            .auto_indent_hang(2)
+            .list_items(true);
+        assert_eq!(opts.width, 40);
+        assert_eq!(opts.indent, "  ");
+        assert_eq!(opts.first_indent, Some("- ".to_string()));
+        assert_eq!(opts.first_line_offset, 7);
+        assert_eq!(opts.first_line_width, Some(30));
+        assert_eq!(opts.first_line_count, 2);
+        assert_eq!(opts.prefix, "> ");
+        assert_eq!(opts.suffix, " <");
+        assert_eq!(opts.continuation_marker, "\\");
+        assert!(opts.shell_command);
+        assert_eq!(opts.line_numbers, 3);
+        assert_eq!(opts.max_lines, 5);
+        assert_eq!(opts.placeholder, "...");
+        assert_eq!(opts.tab_width, 4);
+        assert_eq!(opts.ambiguous_width, AmbiguousWidth::Narrow);
+        assert_eq!(opts.width_overrides.get(&'\u{E0B0}'), Some(&1));
+        assert_eq!(opts.break_mode, BreakMode::Uax14);
+        assert_eq!(opts.quote_pairs, [('⸢', '⸣')]);
+        assert_eq!(opts.align, Align::Center);
+        assert!(opts.paragraph_mode);
+        assert!(opts.break_on_hyphen);
+        assert!(!opts.quote_aware);
+        assert!(opts.break_hangul);
+        assert_eq!(opts.kinsoku_level, KinsokuLevel::Loose);
+        assert!(opts.burasage);
+        assert_eq!(opts.hanging, HangingPunctuation::ALL);
+        assert_eq!(opts.no_break_rules.len(), 1);
+        assert_eq!(
+            opts.protected_delimiters,
+            [("{".to_string(), "}".to_string())]
+        );
+        assert_eq!(opts.sentence_slack, 8);
+        assert!(opts.widow_control);
+        assert_eq!(opts.url_break, UrlBreak::Keep);
+        assert_eq!(opts.long_word_policy, LongWordPolicy::Identifier);
+        assert_eq!(opts.overflow_policy, OverflowPolicy::Error);
+        assert!(!opts.keep_zero_width);
+        assert_eq!(opts.non_print_policy, NonPrintPolicy::EscapeCaret);
+        assert_eq!(opts.ansi_policy, AnsiPolicy::Reapply);
+        assert!(opts.strict_line_breaks);
+        assert_eq!(opts.form_feed, FormFeed::Emit);
+        assert_eq!(opts.ideographic_space, IdeographicSpace::Printable);
+        assert_eq!(opts.line_ending, LineEnding::CrLf);
+        assert!(opts.preserve_leading_whitespace);
+        assert!(opts.auto_indent);
+        assert_eq!(opts.auto_indent_hang, 2);
+        assert!(opts.list_items);
+    }
+
+    #[test]
+    fn test_cjk() {
+        assert_eq!(
+            Options::new(80).cjk(false).ambiguous_width,
+            AmbiguousWidth::Narrow
+        );
+        assert_eq!(
+            Options::new(80).cjk(false).cjk(true).ambiguous_width,
+            AmbiguousWidth::Wide
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_ambiguous_from_locale() {
+        let opts = Options::new(80).ambiguous_from_locale();
+        assert_eq!(opts.ambiguous_width, AmbiguousWidth::from_locale());
+    }
+
+    #[test]
+    fn test_width_override() {
+        let opts = Options::new(4).prefix("\u{E0B0}\u{E0B0}");
+        assert_eq!(opts.validate(), Ok(()));
+        let opts = opts.width_override('\u{E0B0}', 2);
+        assert!(opts.validate().is_err());
+        let opts = opts.width_override('\u{E0B0}', 1);
+        assert_eq!(opts.validate(), Ok(()));
+        let mut iter = crate::LineIter::with_options("ab cd", &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "\u{E0B0}\u{E0B0}ab");
+        assert_eq!(iter.next().unwrap(), "\u{E0B0}\u{E0B0}cd");
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_gutter() {
+        let opts = Options::new(6).gutter(3, |n: usize| format!("{}:", n));
+        assert_eq!(opts.gutter.as_ref().map(|g| g.0), Some(3));
+        assert_eq!(opts, opts.clone());
+        assert_eq!(opts.validate(), Ok(()));
+        assert_eq!(
+            Options::new(6).line_numbers(2).prefix("| ").validate(),
+            Ok(())
+        );
+        assert_eq!(
+            Options::new(5).line_numbers(2).prefix("| ").validate(),
+            Err(OptionsError::NoRoomForText {
+                width: 5,
+                decoration_width: 5
+            })
+        );
+        let mut iter = crate::LineIter::with_options("abc def", &opts).unwrap();
+        assert_eq!(iter.next().unwrap(), "1: abc");
+        assert_eq!(iter.next().unwrap(), "2: def");
+        assert!(iter.next().is_none());
+    }
+
+    #[cfg(feature = "bidi")]
+    #[test]
+    fn test_bidi() {
+        assert!(!Options::new(80).bidi);
+        assert!(Options::new(80).bidi(true).bidi);
+    }
+
+    #[test]
+    fn test_width_provider() {
+        let opts = Options::new(4).width_provider(|_: char| 1).prefix("あい");
+        let cloned = opts.clone();
+        assert!(opts.width_provider.is_some());
+        assert_eq!(opts, cloned);
+        assert_eq!(opts.validate(), Ok(()));
+        assert_ne!(
+            opts,
+            Options::new(4).width_provider(|_: char| 1).prefix("あい")
+        );
+        assert!(Options::new(4).prefix("あい").validate().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize() {
+        let json = r#"{"width": 40, "indent": "  ", "break_mode": "uax14", "align": "center"}"#;
+        let opts: Options = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            opts,
+            Options::new(40)
+                .indent("  ")
+                .break_mode(BreakMode::Uax14)
+                .align(Align::Center)
+        );
+
+        let opts: Options = serde_json::from_str("{}").unwrap();
+        assert_eq!(opts, Options::new(80));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize_and_deserialize() {
+        let opts = Options::new(40)
+            .first_indent("- ")
+            .ambiguous_width(AmbiguousWidth::Narrow)
+            .url_break(UrlBreak::AtDelimiters)
+            .long_word_policy(LongWordPolicy::Identifier)
+            .overflow_policy(OverflowPolicy::Overflow)
+            .non_print_policy(NonPrintPolicy::EscapeCaret)
+            .form_feed(FormFeed::Emit)
+            .line_ending(LineEnding::CrLf);
+        let json = serde_json::to_string(&opts).unwrap();
+        assert!(json.contains(r#""url_break":"at_delimiters""#));
+        assert!(json.contains(r#""line_ending":"cr_lf""#));
+        assert!(json.contains(r#""long_word_policy":"identifier""#));
+        assert!(json.contains(r#""overflow_policy":"overflow""#));
+        let deserialized: Options = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, opts);
+
+        let opts = Options::new(40).hyphenator(|_: &str| Vec::new());
+        let json = serde_json::to_string(&opts).unwrap();
+        let deserialized: Options = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, Options::new(40));
+    }
+
+    #[test]
+    fn test_hyphenator() {
+        let opts = Options::new(80).hyphenator(|_: &str| vec![1]);
+        let cloned = opts.clone();
+        assert!(opts.hyphenator.is_some());
+        assert_eq!(opts, cloned);
+        assert_ne!(opts, Options::new(80).hyphenator(|_: &str| vec![1]));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(Options::new(1).validate(), Ok(()));
+        assert_eq!(Options::new(0).validate(), Err(OptionsError::ZeroWidth));
+        assert_eq!(
+            Options::new(3).indent("ああ").validate(),
+            Err(OptionsError::NoRoomForText {
+                width: 3,
+                decoration_width: 4
+            })
+        );
+        assert_eq!(
+            Options::new(5).first_indent("ああ").indent(" ").validate(),
+            Ok(())
+        );
+        assert_eq!(
+            Options::new(4).first_indent("ああ").validate(),
+            Err(OptionsError::NoRoomForText {
+                width: 4,
+                decoration_width: 4
+            })
+        );
+        assert_eq!(
+            Options::new(2)
+                .indent("α")
+                .ambiguous_width(AmbiguousWidth::Narrow)
+                .validate(),
+            Ok(())
+        );
+        assert_eq!(
+            Options::new(10).first_line_width(0).validate(),
+            Err(OptionsError::ZeroWidth)
+        );
+        assert_eq!(
+            Options::new(10).prefix("> ").first_line_width(2).validate(),
+            Err(OptionsError::NoRoomForText {
+                width: 2,
+                decoration_width: 2
+            })
+        );
+        assert_eq!(
+            Options::new(10)
+                .first_line_width(2)
+                .first_line_count(0)
+                .validate(),
+            Ok(())
+        );
+        assert_eq!(
+            Options::new(10)
+                .prefix("> ")
+                .first_line_offset(7)
+                .validate(),
+            Ok(())
+        );
+        assert_eq!(
+            Options::new(10)
+                .prefix("> ")
+                .first_line_offset(8)
+                .validate(),
+            Err(OptionsError::NoRoomForText {
+                width: 10,
+                decoration_width: 10
+            })
+        );
+        assert_eq!(
+            Options::new(2).subsequent_width(10).first_line_width(20),
+            Options::new(10).first_line_width(20)
+        );
+    }
+
+    #[test]
+    fn test_options_error_to_string() {
+        assert_eq!(
+            OptionsError::ZeroWidth.to_string(),
+            "The line width is zero"
+        );
+        assert_eq!(
+            OptionsError::NoRoomForText {
+                width: 4,
+                decoration_width: 5
+            }
+            .to_string(),
+            "The width of prefix, suffix and indent (5) is not less than the line width (4)"
+        );
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+/// `ParagraphIter` is the struct that outputs the paragraphs in the given
+/// string one by one.
+///
+/// Paragraphs are separated by blank lines, which are empty or contain only
+/// whitespaces, or by the PARAGRAPH SEPARATOR character (U+2029).
+/// Each paragraph is a slice of the given string, and does not contain the
+/// trailing line break.
+///
+/// ```rust
+///     use linebreak::{LineIter, ParagraphIter};
+///
+///     let text = "Lorem ipsum dolor\nsit amet.\n\nConsectetur adipiscing elit.";
+///
+///     let mut lines = Vec::new();
+///     for (i, para) in ParagraphIter::new(text).enumerate() {
+///         let indent = format!("{}. ", i + 1);
+///         let mut iter = LineIter::new(para, 16);
+///         iter.set_paragraph_mode(true);
+///         iter.set_first_indent(&indent);
+///         iter.set_indent("   ");
+///         lines.extend(iter.map(|line| line.into_owned()));
+///     }
+///
+///     assert_eq!(lines, [
+///         "1. Lorem ipsum",
+///         "   dolor sit",
+///         "   amet.",
+///         "2. Consectetur",
+///         "   adipiscing",
+///         "   elit.",
+///     ]);
+/// ```
+pub struct ParagraphIter<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> ParagraphIter<'a> {
+    /// Creates a `ParagraphIter` instance which outputs the paragraphs in the
+    /// given string.
+    pub fn new(text: &'a str) -> ParagraphIter<'a> {
+        ParagraphIter { text, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for ParagraphIter<'a> {
+    type Item = &'a str;
+
+    /// Returns an Option of a paragraph string.
+    /// If there is a remaining paragraph, this method returns a `Some(&str)`,
+    /// otherwise returns `None`.
+    fn next(&mut self) -> Option<&'a str> {
+        let text = self.text;
+        let mut start: Option<usize> = None;
+        let mut end = self.pos;
+        let mut i = self.pos;
+
+        while i < text.len() {
+            let line_end = match text[i..].find(['\n', '\u{2029}']) {
+                Some(n) => i + n,
+                None => text.len(),
+            };
+            let line = text[i..line_end].trim_end_matches('\r');
+            let sep = text[line_end..].chars().next();
+            let next_line = line_end + sep.map_or(0, |c| c.len_utf8());
+
+            if line.trim().is_empty() {
+                if let Some(start) = start {
+                    self.pos = next_line;
+                    return Some(&text[start..end]);
+                }
+            } else {
+                if start.is_none() {
+                    start = Some(i);
+                }
+                end = i + line.len();
+                if sep == Some('\u{2029}') {
+                    self.pos = next_line;
+                    return Some(&text[start.unwrap_or(i)..end]);
+                }
+            }
+            i = next_line;
+        }
+
+        self.pos = text.len();
+        start.map(|start| &text[start..end])
+    }
+}
+
+#[cfg(test)]
+mod test_of_paragraph_iter {
+    use super::*;
+
+    #[test]
+    fn test_empty() {
+        let mut iter = ParagraphIter::new("");
+        assert!(iter.next().is_none());
+
+        let mut iter = ParagraphIter::new("\n  \n\n");
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_one_paragraph() {
+        let mut iter = ParagraphIter::new("abc\ndef\n");
+        assert_eq!(iter.next(), Some("abc\ndef"));
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_paragraphs_separated_by_blank_lines() {
+        let text = "\n\nabc\ndef\n\n \t\nghi\n\n\njkl";
+        let mut iter = ParagraphIter::new(text);
+        assert_eq!(iter.next(), Some("abc\ndef"));
+        assert_eq!(iter.next(), Some("ghi"));
+        assert_eq!(iter.next(), Some("jkl"));
+        assert!(iter.next().is_none());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_paragraphs_separated_by_paragraph_separator() {
+        let text = "abc\u{2029}def\nghi\u{2029}\u{2029}jkl\u{2029}";
+        let mut iter = ParagraphIter::new(text);
+        assert_eq!(iter.next(), Some("abc"));
+        assert_eq!(iter.next(), Some("def\nghi"));
+        assert_eq!(iter.next(), Some("jkl"));
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_paragraphs_with_crlf() {
+        let text = "abc\r\ndef\r\n\r\nghi\r\n";
+        let mut iter = ParagraphIter::new(text);
+        assert_eq!(iter.next(), Some("abc\r\ndef"));
+        assert_eq!(iter.next(), Some("ghi"));
+        assert!(iter.next().is_none());
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use rayon::prelude::*;
+
+use crate::fill::{fill, refill};
+
+const SEPARATOR: &str = "\n\n";
+
+/// Breaks the specified text into lines within the specified width as `fill`
+/// does, with wrapping the paragraphs separated by blank lines concurrently.
+///
+/// The paragraphs are independent units of line breaking, so the result is the
+/// same as `fill`.
+/// This function is available with the `parallel` feature.
+///
+/// ```rust
+///     use linebreak::{fill, par_fill};
+///
+///     let text = "abc def ghi\n\njkl mno pqr\n\nstu";
+///     assert_eq!(par_fill(text, 7), "abc def\nghi\n\njkl mno\npqr\n\nstu");
+///     assert_eq!(par_fill(text, 7), fill(text, 7));
+/// ```
+pub fn par_fill(text: &str, width: usize) -> String {
+    split_paragraphs(text)
+        .par_iter()
+        .map(|paragraph| fill(paragraph, width))
+        .collect::<Vec<String>>()
+        .join(SEPARATOR)
+}
+
+/// Re-flows the specified text within the specified new width as `refill`
+/// does, with wrapping the paragraphs separated by blank lines concurrently.
+///
+/// This function is available with the `parallel` feature.
+///
+/// ```rust
+///     use linebreak::{par_refill, refill};
+///
+///     let text = "abc\ndef ghi\n\njkl mno\npqr";
+///     assert_eq!(par_refill(text, 7), "abc def\nghi\n\njkl mno\npqr");
+///     assert_eq!(par_refill(text, 7), refill(text, 7));
+/// ```
+pub fn par_refill(text: &str, width: usize) -> String {
+    split_paragraphs(text)
+        .par_iter()
+        .map(|paragraph| refill(paragraph, width))
+        .collect::<Vec<String>>()
+        .join(SEPARATOR)
+}
+
+// Splits the specified text at every "\n\n", except for the ones which follow
+// a carriage return, so that a CRLF is not divided.
+fn split_paragraphs(text: &str) -> Vec<&str> {
+    let mut paragraphs = Vec::new();
+    let mut start = 0;
+    for (i, _) in text.match_indices(SEPARATOR) {
+        if i > 0 && text.as_bytes()[i - 1] == b'\r' {
+            continue;
+        }
+        paragraphs.push(&text[start..i]);
+        start = i + SEPARATOR.len();
+    }
+    paragraphs.push(&text[start..]);
+    paragraphs
+}
+
+#[cfg(test)]
+mod test_of_parallel {
+    use super::*;
+
+    #[test]
+    fn test_split_paragraphs() {
+        assert_eq!(split_paragraphs(""), [""]);
+        assert_eq!(split_paragraphs("abc"), ["abc"]);
+        assert_eq!(split_paragraphs("abc\n\ndef"), ["abc", "def"]);
+        assert_eq!(split_paragraphs("abc\n\n\ndef"), ["abc", "\ndef"]);
+        assert_eq!(split_paragraphs("abc\n\n"), ["abc", ""]);
+        assert_eq!(split_paragraphs("abc\r\n\ndef"), ["abc\r\n\ndef"]);
+    }
+
+    #[test]
+    fn test_par_fill_is_same_as_fill() {
+        let texts = [
+            "",
+            "abc def ghi jkl",
+            "abc\n\n\ndef ghi jkl\n\n",
+            "abc\r\n\r\ndef ghi\r\n\njkl mno",
+            "句読点は、行頭に置くことは禁止である。\n\n句読点は、行頭に置く",
+            "漢字、\n\nx",
+        ];
+        for text in texts {
+            assert_eq!(par_fill(text, 7), fill(text, 7));
+            assert_eq!(par_refill(text, 7), refill(text, 7));
+            assert_eq!(par_fill(text, 4), fill(text, 4));
+            assert_eq!(par_refill(text, 4), refill(text, 4));
+        }
+    }
+
+    #[test]
+    fn test_par_fill_large_text() {
+        let text = "The Rust programming language helps you write faster, \
+            more reliable software.\n\n"
+            .repeat(100);
+        assert_eq!(par_fill(&text, 30), fill(&text, 30));
+        assert_eq!(par_refill(&text, 30), refill(&text, 30));
+    }
+}
@@ -0,0 +1,145 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::linebreak::{LboState, LboType};
+
+// Holds the byte ranges of the text which must not be broken, sorted by their
+// start positions and merged if they overlap.
+pub struct ProtectedSpans {
+    ranges: Vec<Range<usize>>,
+}
+
+impl ProtectedSpans {
+    pub fn new() -> ProtectedSpans {
+        ProtectedSpans { ranges: Vec::new() }
+    }
+
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+    }
+
+    pub fn add(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        let i = self.ranges.partition_point(|r| r.end < range.start);
+        let mut j = i;
+        let mut merged = range;
+        while j < self.ranges.len() && self.ranges[j].start <= merged.end {
+            merged.start = merged.start.min(self.ranges[j].start);
+            merged.end = merged.end.max(self.ranges[j].end);
+            j += 1;
+        }
+        self.ranges.splice(i..j, [merged]);
+    }
+
+    // Adds the spans which start with `open` and end with `close` in the text.
+    // An opening delimiter without a closing delimiter does not make a span.
+    pub fn add_delimited(&mut self, text: &str, open: &str, close: &str) {
+        if open.is_empty() || close.is_empty() {
+            return;
+        }
+        let mut from = 0;
+        while let Some(i) = text[from..].find(open) {
+            let start = from + i;
+            let inner = start + open.len();
+            match text[inner..].find(close) {
+                Some(j) => {
+                    let end = inner + j + close.len();
+                    self.add(start..end);
+                    from = end;
+                }
+                None => break,
+            }
+        }
+    }
+
+    // Returns the span which contains the specified position.
+    pub fn find(&self, pos: usize) -> Option<Range<usize>> {
+        let i = self.ranges.partition_point(|r| r.start <= pos);
+        if i == 0 || pos >= self.ranges[i - 1].end {
+            return None;
+        }
+        Some(self.ranges[i - 1].clone())
+    }
+
+    // Overwrites the lbo type of the character at the specified position in
+    // the specified span, so that a line is not broken inside of the span.
+    pub fn apply(range: &Range<usize>, pos: usize, ch: char, state: &mut LboState) {
+        if state.lbo_type == LboType::Break {
+            return;
+        }
+        let is_first = pos == range.start;
+        let is_last = pos + ch.len_utf8() >= range.end;
+        state.lbo_type = match (is_first, is_last, state.lbo_type) {
+            (true, true, t) => t,
+            (true, false, LboType::Before | LboType::Both) => LboType::Before,
+            (false, true, LboType::After | LboType::Both | LboType::Space) => LboType::After,
+            _ => LboType::Never,
+        };
+    }
+}
+
+#[cfg(test)]
+mod test_of_protected {
+    use super::*;
+
+    #[test]
+    fn test_add_merges_overlapping_ranges() {
+        let mut spans = ProtectedSpans::new();
+        spans.add(10..15);
+        spans.add(2..4);
+        spans.add(20..22);
+        spans.add(3..11);
+        spans.add(5..5);
+        assert_eq!(spans.ranges, [2..15, 20..22]);
+        spans.add(15..20);
+        assert_eq!(spans.ranges, vec![2..22]);
+        spans.clear();
+        assert!(spans.ranges.is_empty());
+    }
+
+    #[test]
+    fn test_add_delimited() {
+        let mut spans = ProtectedSpans::new();
+        spans.add_delimited("a {b c} d {e} {f", "{", "}");
+        assert_eq!(spans.ranges, [2..7, 10..13]);
+
+        let mut spans = ProtectedSpans::new();
+        spans.add_delimited("x ⟦y z⟧", "⟦", "⟧");
+        assert_eq!(spans.ranges, vec![2..11]);
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut spans = ProtectedSpans::new();
+        spans.add(2..7);
+        let mut state = LboState {
+            lbo_type: LboType::Never,
+            lbo_prev: LboType::Never,
+            open_apos: 0,
+            open_quot: 0,
+        };
+        let cases = [
+            (0, 'a', LboType::Both, LboType::Both),
+            (2, '{', LboType::Before, LboType::Before),
+            (2, 'x', LboType::After, LboType::Never),
+            (3, ' ', LboType::Space, LboType::Never),
+            (3, 'あ', LboType::Both, LboType::Never),
+            (6, '}', LboType::After, LboType::After),
+            (6, 'x', LboType::Before, LboType::Never),
+            (7, ' ', LboType::Space, LboType::Space),
+        ];
+        for (pos, ch, lbo_type, expected) in cases {
+            state.lbo_type = lbo_type;
+            if let Some(range) = spans.find(pos) {
+                ProtectedSpans::apply(&range, pos, ch, &mut state);
+            }
+            assert_eq!(state.lbo_type, expected, "{} {}", pos, ch);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::string::String;
+
+use crate::line_iter::LineIter;
+use crate::unicode::text_width;
+
+/// Re-flows the specified text which contains quoted paragraphs of emails,
+/// such as lines starting with `> ` or `> > `, within the specified width.
+///
+/// The quote prefix of each line is stripped, and the consecutive lines which
+/// have the same quote depth are re-flowed as paragraphs, then the prefix of
+/// the first line of the paragraphs is re-applied to every output line.
+/// If a prefix is too wide for the width, the lines are output as they are.
+///
+/// ```rust
+///     use linebreak::fill_quoted;
+///
+///     let text = "> The Rust programming language\n\
+///                 > helps you write faster, more reliable software.\n\
+///                 >\n\
+///                 > > Rust gives you the option.\n\
+///                 Agreed.";
+///     assert_eq!(
+///         fill_quoted(text, 24),
+///         "> The Rust programming\n\
+///          > language helps you\n\
+///          > write faster, more\n\
+///          > reliable software.\n\
+///          >\n\
+///          > > Rust gives you the\n\
+///          > > option.\n\
+///          Agreed."
+///     );
+/// ```
+pub fn fill_quoted(text: &str, width: usize) -> String {
+    let mut filled = String::with_capacity(text.len());
+    let mut block = String::new();
+    let mut block_prefix = String::new();
+    let mut block_depth = 0;
+    let mut is_first = true;
+
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let (depth, prefix_len) = quote_prefix(line);
+        if is_first || depth != block_depth {
+            if !is_first {
+                push_block(&mut filled, &block, &block_prefix, width);
+                filled.push('\n');
+                block.clear();
+            }
+            block_depth = depth;
+            block_prefix.clear();
+            block_prefix.push_str(line[..prefix_len].trim_end());
+            if depth > 0 {
+                block_prefix.push(' ');
+            }
+            is_first = false;
+        } else {
+            block.push('\n');
+        }
+        block.push_str(&line[prefix_len..]);
+    }
+    push_block(&mut filled, &block, &block_prefix, width);
+    filled
+}
+
+// Returns the depth and the byte length of the quote prefix of a line.
+fn quote_prefix(line: &str) -> (usize, usize) {
+    let mut depth = 0;
+    let mut len = 0;
+    let mut i = 0;
+    for (j, ch) in line.char_indices() {
+        match ch {
+            '>' => {
+                depth += 1;
+                i = j + 1;
+                len = i;
+            }
+            ' ' | '\t' if depth > 0 => {
+                if j == i {
+                    len = j + 1;
+                }
+            }
+            ' ' | '\t' => (),
+            _ => break,
+        }
+    }
+    (depth, len)
+}
+
+fn push_block(filled: &mut String, block: &str, prefix: &str, width: usize) {
+    if prefix.is_empty() || text_width(prefix) < width {
+        let mut iter = LineIter::new(block, width);
+        iter.set_prefix(prefix);
+        iter.set_paragraph_mode(true);
+        for (i, line) in iter.enumerate() {
+            if i > 0 {
+                filled.push('\n');
+            }
+            filled.push_str(line.trim_end());
+        }
+    } else {
+        for (i, line) in block.split('\n').enumerate() {
+            if i > 0 {
+                filled.push('\n');
+            }
+            filled.push_str(prefix);
+            filled.push_str(line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_of_quote {
+    use super::*;
+
+    #[test]
+    fn test_quote_prefix() {
+        assert_eq!(quote_prefix("abc"), (0, 0));
+        assert_eq!(quote_prefix("> abc"), (1, 2));
+        assert_eq!(quote_prefix(">abc"), (1, 1));
+        assert_eq!(quote_prefix("> > abc"), (2, 4));
+        assert_eq!(quote_prefix(">> abc"), (2, 3));
+        assert_eq!(quote_prefix(">  abc"), (1, 2));
+        assert_eq!(quote_prefix(">"), (1, 1));
+        assert_eq!(quote_prefix("  abc"), (0, 0));
+        assert_eq!(quote_prefix(" > abc"), (1, 3));
+    }
+
+    #[test]
+    fn test_fill_quoted_empty() {
+        assert_eq!(fill_quoted("", 10), "");
+    }
+
+    #[test]
+    fn test_fill_quoted_without_quotes() {
+        assert_eq!(fill_quoted("abc def\nghi", 7), "abc def\nghi");
+    }
+
+    #[test]
+    fn test_fill_quoted_nested() {
+        let text = ">> abc def\n>> ghi\n> jkl mno pqr\r\n> stu";
+        assert_eq!(
+            fill_quoted(text, 9),
+            ">> abc\n>> def\n>> ghi\n> jkl mno\n> pqr stu"
+        );
+    }
+
+    #[test]
+    fn test_fill_quoted_with_paragraphs() {
+        let text = "> abc\n> def\n>\n> ghi\n\njkl";
+        assert_eq!(fill_quoted(text, 20), "> abc def\n>\n> ghi\n\njkl");
+    }
+
+    #[test]
+    fn test_fill_quoted_with_too_wide_prefix() {
+        let text = "> > > abc def";
+        assert_eq!(fill_quoted(text, 6), "> > > abc def");
+    }
+}
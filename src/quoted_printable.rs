@@ -0,0 +1,138 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::string::String;
+
+// The maximum length of an encoded line including a soft line break `=`,
+// which is defined in RFC 2045.
+const MAX_LINE_LENGTH: usize = 76;
+
+/// Wraps the specified quoted-printable encoded text (RFC 2045), so that
+/// every line is at most 76 characters long.
+///
+/// A long line is divided with soft line breaks, which are `=` at the ends of
+/// lines, preferably after a space or a tab, and never inside of an encoded
+/// triple such as `=3D`.
+/// The hard line breaks of the text are kept, and the soft line breaks use
+/// CRLF if the text contains CRLF, or LF otherwise.
+///
+/// ```rust
+///     use linebreak::wrap_quoted_printable;
+///
+///     let text = "Caf=C3=A9 au lait ".repeat(5);
+///     assert_eq!(
+///         wrap_quoted_printable(&text),
+///         "Caf=C3=A9 au lait Caf=C3=A9 au lait Caf=C3=A9 au lait Caf=C3=A9 au lait =\n\
+///          Caf=C3=A9 au lait "
+///     );
+/// ```
+pub fn wrap_quoted_printable(encoded: &str) -> String {
+    let soft_break = if encoded.contains("\r\n") {
+        "=\r\n"
+    } else {
+        "=\n"
+    };
+    let mut wrapped = String::with_capacity(encoded.len() + encoded.len() / 25);
+    for (i, line) in encoded.split('\n').enumerate() {
+        if i > 0 {
+            wrapped.push('\n');
+        }
+        let (mut rest, cr) = match line.strip_suffix('\r') {
+            Some(s) => (s, "\r"),
+            None => (line, ""),
+        };
+        while rest.len() > MAX_LINE_LENGTH {
+            let pos = soft_break_pos(rest);
+            wrapped.push_str(&rest[..pos]);
+            wrapped.push_str(soft_break);
+            rest = &rest[pos..];
+        }
+        wrapped.push_str(rest);
+        wrapped.push_str(cr);
+    }
+    wrapped
+}
+
+// Returns the byte position at which the specified line is divided, so that
+// the first part and a soft line break fit within the maximum length.
+fn soft_break_pos(line: &str) -> usize {
+    let max = MAX_LINE_LENGTH - 1;
+    if let Some(i) = line.as_bytes()[..max]
+        .iter()
+        .rposition(|b| matches!(b, b' ' | b'\t'))
+    {
+        return i + 1;
+    }
+    let bytes = line.as_bytes();
+    let mut pos = max;
+    if bytes[pos - 1] == b'=' {
+        pos -= 1;
+    } else if bytes[pos - 2] == b'=' {
+        pos -= 2;
+    }
+    // an unencoded non-ASCII character is not valid, but is not divided.
+    while !line.is_char_boundary(pos) {
+        pos -= 1;
+    }
+    pos
+}
+
+#[cfg(test)]
+mod test_of_quoted_printable {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn lengths(text: &str) -> Vec<usize> {
+        text.lines().map(|line| line.len()).collect()
+    }
+
+    #[test]
+    fn test_short_lines() {
+        assert_eq!(wrap_quoted_printable(""), "");
+        assert_eq!(wrap_quoted_printable("abc=3Ddef"), "abc=3Ddef");
+        let text = "a".repeat(76);
+        assert_eq!(wrap_quoted_printable(&text), text);
+    }
+
+    #[test]
+    fn test_breaks_at_the_limit() {
+        let text = "a".repeat(160);
+        let wrapped = wrap_quoted_printable(&text);
+        assert_eq!(lengths(&wrapped), [76, 76, 10]);
+        assert!(wrapped.starts_with(&("a".repeat(75) + "=\n")));
+        assert_eq!(wrapped.replace("=\n", ""), text);
+    }
+
+    #[test]
+    fn test_does_not_split_encoded_triples() {
+        for n in 72..76 {
+            let text = "a".repeat(n) + &"=E3=81=82".repeat(3);
+            let wrapped = wrap_quoted_printable(&text);
+            for line in wrapped.lines() {
+                assert!(line.len() <= 76);
+                let body = line.strip_suffix('=').unwrap_or(line);
+                let i = body.rfind('=').unwrap_or(0);
+                assert!(body.len() >= i + 3, "{}", line);
+            }
+            assert_eq!(wrapped.replace("=\n", ""), text);
+        }
+    }
+
+    #[test]
+    fn test_prefers_spaces() {
+        let text = "word ".repeat(20);
+        let wrapped = wrap_quoted_printable(&text);
+        assert_eq!(lengths(&wrapped), [76, 25]);
+        assert!(wrapped.starts_with(&("word ".repeat(15) + "=\n")));
+    }
+
+    #[test]
+    fn test_keeps_hard_line_breaks() {
+        let text = "a".repeat(80) + "\r\nabc\r\n";
+        assert_eq!(
+            wrap_quoted_printable(&text),
+            "a".repeat(75) + "=\r\naaaaa\r\nabc\r\n"
+        );
+    }
+}
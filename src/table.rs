@@ -0,0 +1,118 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::columns::push_side_by_side;
+use crate::line_iter::LineIter;
+
+/// Formats the specified rows of cells as a table, in which the content of
+/// each cell is broken into lines within the width of its column.
+///
+/// The number of the columns is the length of `widths`: the cells beyond it
+/// are ignored, and the missing cells are treated as empty.
+/// The cells in a row are padded to the same number of lines, and each line
+/// of a cell is padded with spaces by its display width, so that the columns
+/// are aligned even if the cells contain wide characters.
+/// The columns are separated with the specified separator.
+/// The lines are joined with `\n`, and the trailing spaces of each line are
+/// removed.
+///
+/// ```rust
+///     use linebreak::format_table;
+///
+///     let rows = [
+///         ["Name", "Description"],
+///         ["linebreak", "breaks a text into lines"],
+///         ["漢字", "全角文字"],
+///     ];
+///     assert_eq!(
+///         format_table(&rows, &[9, 12], " | "),
+///         "Name      | Description\n\
+///          linebreak | breaks a\n\
+///          \x20         | text into\n\
+///          \x20         | lines\n\
+///          漢字      | 全角文字"
+///     );
+/// ```
+pub fn format_table<R, S>(rows: &[R], widths: &[usize], separator: &str) -> String
+where
+    R: AsRef<[S]>,
+    S: AsRef<str>,
+{
+    let mut formatted = String::new();
+    if widths.is_empty() {
+        return formatted;
+    }
+    // a column of zero width is laid out with the width 1, in which the cells
+    // are broken into lines.
+    let widths: Vec<usize> = widths.iter().map(|width| (*width).max(1)).collect();
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            formatted.push('\n');
+        }
+        let row = row.as_ref();
+        let cells: Vec<Vec<Cow<str>>> = widths
+            .iter()
+            .enumerate()
+            .map(|(j, width)| {
+                let text = row.get(j).map_or("", |cell| cell.as_ref());
+                let mut lines: Vec<Cow<str>> = LineIter::new(text, *width).collect();
+                // an empty row is output as an empty line.
+                if lines.is_empty() {
+                    lines.push(Cow::Borrowed(""));
+                }
+                lines
+            })
+            .collect();
+        push_side_by_side(&mut formatted, &cells, &widths, separator);
+    }
+    formatted
+}
+
+#[cfg(test)]
+mod test_of_table {
+    use super::*;
+
+    #[test]
+    fn test_no_columns() {
+        assert_eq!(format_table(&[["abc"]], &[], " "), "");
+        assert_eq!(format_table::<[&str; 1], &str>(&[], &[3], " "), "");
+    }
+
+    #[test]
+    fn test_shared_row_height() {
+        let rows = [["abc def ghi", "jkl"], ["mno", "pqr stu"]];
+        assert_eq!(
+            format_table(&rows, &[3, 3], "|"),
+            "abc|jkl\ndef|\nghi|\nmno|pqr\n   |stu"
+        );
+    }
+
+    #[test]
+    fn test_missing_and_extra_cells() {
+        let rows: [&[&str]; 3] = [&["a", "b", "c"], &["d"], &[]];
+        assert_eq!(format_table(&rows, &[2, 2], " "), "a  b\nd\n");
+    }
+
+    #[test]
+    fn test_wide_characters() {
+        let rows = [["あいう", "x"], ["ab", "y"]];
+        assert_eq!(format_table(&rows, &[4, 1], " "), "あい x\nう\nab   y");
+    }
+
+    #[test]
+    fn test_zero_width_column() {
+        let rows = [["a", "b c"]];
+        assert_eq!(format_table(&rows, &[0, 1], "|"), "a|b\n |c");
+    }
+
+    #[test]
+    fn test_owned_cells() {
+        let rows = vec![vec![String::from("abc"), String::from("def")]];
+        assert_eq!(format_table(&rows, &[5, 5], ": "), "abc  : def");
+    }
+}
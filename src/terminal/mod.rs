@@ -3,7 +3,27 @@
 // See the file LICENSE in this distribution for more details.
 
 /// `Size` is the struct for storing the size of the current terminal.
-#[derive(Debug)]
+///
+/// The column and row numbers are `usize` on every platform, which is also
+/// the type of the widths in this crate, so that they can be passed to
+/// `LineIter` and `fill` without casts.
+///
+/// This struct can be converted from and to a tuple of `(u16, u16)`, which is
+/// `(col, row)`, as used by many terminal libraries, and can be converted
+/// with checks from a tuple of `(i32, i32)`, as used by the platform APIs
+/// which store sizes in signed integers.
+///
+/// ```rust
+///     use linebreak::Size;
+///
+///     let size = Size::new(80, 24);
+///     assert_eq!(size, Size::from((80, 24)));
+///     assert_eq!(<(u16, u16)>::from(size), (80, 24));
+///     assert_eq!(Size::try_from((80i32, 24i32)), Ok(size));
+///     assert!(Size::try_from((-1i32, 24i32)).is_err());
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Size {
     /// The column number of the terminal.
     pub col: usize,
@@ -11,45 +31,196 @@ pub struct Size {
     pub row: usize,
 }
 
-#[cfg(unix)]
+impl Size {
+    /// Creates a `Size` instance with the specified column and row numbers.
+    pub fn new(col: usize, row: usize) -> Size {
+        Size { col, row }
+    }
+}
+
+impl From<(u16, u16)> for Size {
+    fn from((col, row): (u16, u16)) -> Self {
+        Size::new(col as usize, row as usize)
+    }
+}
+
+/// A negative column or row number is an error.
+impl TryFrom<(i32, i32)> for Size {
+    type Error = core::num::TryFromIntError;
+
+    fn try_from((col, row): (i32, i32)) -> Result<Self, Self::Error> {
+        Ok(Size::new(usize::try_from(col)?, usize::try_from(row)?))
+    }
+}
+
+/// The column and row numbers which exceed `u16::MAX` are saturated.
+impl From<Size> for (u16, u16) {
+    fn from(size: Size) -> Self {
+        let saturate = |n: usize| u16::try_from(n).unwrap_or(u16::MAX);
+        (saturate(size.col), saturate(size.row))
+    }
+}
+
+/// `Capabilities` is the struct for storing what the current terminal can
+/// process.
+///
+/// ```rust
+///     use linebreak::term_capabilities;
+///
+///     let caps = term_capabilities();
+///     if caps.virtual_terminal {
+///         println!("\u{1b}[1mbold\u{1b}[0m");
+///     }
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Whether the terminal processes ANSI escape sequences, which is
+    /// `ENABLE_VIRTUAL_TERMINAL_PROCESSING` of the console mode on Windows.
+    pub virtual_terminal: bool,
+}
+
+/// `TermStream` is the enum for specifying the stream of which the terminal
+/// size is retrieved.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TermStream {
+    /// The standard output.
+    Stdout,
+    /// The standard error.
+    Stderr,
+    /// The standard input.
+    Stdin,
+    /// An arbitrary file descriptor.
+    #[cfg(unix)]
+    Fd(std::os::unix::io::RawFd),
+    /// An arbitrary handle.
+    #[cfg(windows)]
+    Handle(std::os::windows::io::RawHandle),
+}
+
+#[cfg(all(unix, not(feature = "rustix")))]
 mod unix;
-#[cfg(unix)]
-pub use self::unix::{term_cols, term_size};
+#[cfg(all(unix, not(feature = "rustix")))]
+pub use self::unix::{
+    capabilities, is_tty_stderr, is_tty_stdout, term_cols, term_size, term_size_of,
+};
+
+#[cfg(all(unix, feature = "rustix"))]
+mod unix_rustix;
+#[cfg(all(unix, feature = "rustix"))]
+pub use self::unix_rustix::{
+    capabilities, is_tty_stderr, is_tty_stdout, term_cols, term_size, term_size_of,
+};
 
 #[cfg(windows)]
 mod windows;
 #[cfg(windows)]
-pub use self::windows::{term_cols, term_size};
+pub use self::windows::{
+    capabilities, is_tty_stderr, is_tty_stdout, term_cols, term_size, term_size_of,
+};
 
 #[cfg(not(any(unix, windows)))]
 mod unknown;
 #[cfg(not(any(unix, windows)))]
-pub use self::unknown::{term_cols, term_size};
+pub use self::unknown::{
+    capabilities, is_tty_stderr, is_tty_stdout, term_cols, term_size, term_size_of,
+};
 
 #[cfg(test)]
-mod test_of_term_cols {
+mod test_of_size {
     use super::*;
 
-    #[cfg(unix)]
     #[test]
-    fn test_get_terminal_cols() {
-        match term_cols() {
-            Ok(c) => println!("term cols = {}", c),
-            Err(e) => {
-                println!("term cols error = {}", e.to_string());
-                assert_eq!(e.raw_os_error().unwrap(), 25); // NOTTY
-            }
+    fn test_new() {
+        let size = Size::new(80, 24);
+        assert_eq!(size.col, 80);
+        assert_eq!(size.row, 24);
+        assert_eq!(Size::default(), Size::new(0, 0));
+    }
+
+    #[test]
+    fn test_compare_and_copy() {
+        let size = Size::new(80, 24);
+        let copied = size;
+        assert_eq!(size, copied);
+        assert_ne!(size, Size::new(81, 24));
+        assert_ne!(size, Size::new(80, 25));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize_and_deserialize() {
+        let json = serde_json::to_string(&Size::new(80, 24)).unwrap();
+        assert_eq!(json, r#"{"col":80,"row":24}"#);
+        let size: Size = serde_json::from_str(&json).unwrap();
+        assert_eq!(size, Size::new(80, 24));
+    }
+
+    #[test]
+    fn test_convert_from_and_to_tuple() {
+        assert_eq!(Size::from((120u16, 40u16)), Size::new(120, 40));
+        assert_eq!(<(u16, u16)>::from(Size::new(120, 40)), (120, 40));
+        assert_eq!(<(u16, u16)>::from(Size::new(70000, 5)), (u16::MAX, 5));
+    }
+
+    #[test]
+    fn test_try_convert_from_signed_tuple() {
+        assert_eq!(Size::try_from((120i32, 40i32)), Ok(Size::new(120, 40)));
+        assert_eq!(Size::try_from((0i32, 0i32)), Ok(Size::new(0, 0)));
+        assert!(Size::try_from((-1i32, 40i32)).is_err());
+        assert!(Size::try_from((120i32, i32::MIN)).is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_of_is_tty {
+    use super::*;
+
+    #[test]
+    fn test_is_tty_stdout() {
+        let is_tty = is_tty_stdout();
+        println!("stdout is tty = {}", is_tty);
+        if !is_tty {
+            assert!(term_size_of(TermStream::Stdout).is_err());
         }
     }
 
-    #[cfg(windows)]
+    #[test]
+    fn test_is_tty_stderr() {
+        println!("stderr is tty = {}", is_tty_stderr());
+    }
+}
+
+#[cfg(test)]
+mod test_of_capabilities {
+    use super::*;
+
+    #[test]
+    fn test_capabilities() {
+        let caps = capabilities();
+        println!("capabilities = {:?}", caps);
+        if !is_tty_stdout() && !is_tty_stderr() {
+            assert!(!caps.virtual_terminal);
+        }
+        assert!(!Capabilities::default().virtual_terminal);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::to_string_in_format_args)]
+mod test_of_term_cols {
+    use super::*;
+
+    #[cfg(any(unix, windows))]
     #[test]
     fn test_get_terminal_cols() {
         match term_cols() {
             Ok(c) => println!("term cols = {}", c),
             Err(e) => {
                 println!("term cols error = {}", e.to_string());
-                assert_eq!(e.raw_os_error().unwrap() & 0xffff, 6); // Invalid Handler
+                // The error for the standard output is returned, even when
+                // the fallback to the controlling terminal also fails.
+                let err = term_size_of(TermStream::Stdout).unwrap_err();
+                assert_eq!(e.raw_os_error(), err.raw_os_error());
             }
         }
     }
@@ -65,29 +236,21 @@ mod test_of_term_cols {
 }
 
 #[cfg(test)]
+#[allow(clippy::to_string_in_format_args)]
 mod test_of_term_size {
     use super::*;
 
-    #[cfg(unix)]
-    #[test]
-    fn test_get_terminal_size() {
-        match term_size() {
-            Ok(sz) => println!("term size = {} x {}", sz.col, sz.row),
-            Err(e) => {
-                println!("term size error = {}", e.to_string());
-                assert_eq!(e.raw_os_error().unwrap(), 25); // NOTTY
-            }
-        }
-    }
-
-    #[cfg(windows)]
+    #[cfg(any(unix, windows))]
     #[test]
     fn test_get_terminal_size() {
         match term_size() {
             Ok(sz) => println!("term size = {} x {}", sz.col, sz.row),
             Err(e) => {
                 println!("term size error = {}", e.to_string());
-                assert_eq!(e.raw_os_error().unwrap() & 0xffff, 6); // Invalid Handler
+                // The error for the standard output is returned, even when
+                // the fallback to the controlling terminal also fails.
+                let err = term_size_of(TermStream::Stdout).unwrap_err();
+                assert_eq!(e.raw_os_error(), err.raw_os_error());
             }
         }
     }
@@ -101,3 +264,34 @@ mod test_of_term_size {
         }
     }
 }
+
+#[cfg(test)]
+mod test_of_term_size_of {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_get_terminal_size_of_streams() {
+        for stream in [TermStream::Stdout, TermStream::Stderr, TermStream::Stdin] {
+            match term_size_of(stream) {
+                Ok(sz) => println!("term size of {:?} = {} x {}", stream, sz.col, sz.row),
+                Err(e) => assert_eq!(e.raw_os_error().unwrap(), 25), // NOTTY
+            }
+        }
+        match term_size_of(TermStream::Fd(-1)) {
+            Ok(_) => panic!(),
+            Err(e) => assert_eq!(e.raw_os_error().unwrap(), 9), // BADF
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_get_terminal_size_of_streams() {
+        for stream in [TermStream::Stdout, TermStream::Stderr, TermStream::Stdin] {
+            match term_size_of(stream) {
+                Ok(sz) => println!("term size of {:?} = {} x {}", stream, sz.col, sz.row),
+                Err(e) => println!("term size error = {}", e),
+            }
+        }
+    }
+}
@@ -2,37 +2,61 @@
 // This program is free software under MIT License.
 // See the file LICENSE in this distribution for more details.
 
-use super::Size;
-use libc::{ioctl, winsize, STDOUT_FILENO, TIOCGWINSZ};
+use super::{Capabilities, Size, TermStream};
+use libc::{ioctl, isatty, winsize, STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO, TIOCGWINSZ};
+use std::fs::File;
 use std::io;
+use std::os::unix::io::AsRawFd;
 
-pub fn term_cols() -> Result<usize, io::Error> {
-    let mut ws = winsize {
-        ws_row: 0,
-        ws_col: 0,
-        ws_xpixel: 0,
-        ws_ypixel: 0,
-    };
-    let r = unsafe { ioctl(STDOUT_FILENO, TIOCGWINSZ, &mut ws) };
-    match r {
-        0 => Ok(ws.ws_col as usize),
-        _ => Err(io::Error::last_os_error()),
+pub fn is_tty_stdout() -> bool {
+    unsafe { isatty(STDOUT_FILENO) == 1 }
+}
+
+pub fn is_tty_stderr() -> bool {
+    unsafe { isatty(STDERR_FILENO) == 1 }
+}
+
+// The terminals on Unix process ANSI escape sequences, except for the one
+// whose type is `dumb`.
+pub fn capabilities() -> Capabilities {
+    let is_dumb = std::env::var_os("TERM").map_or(false, |term| term == "dumb");
+    Capabilities {
+        virtual_terminal: (is_tty_stdout() || is_tty_stderr()) && !is_dumb,
     }
 }
 
+pub fn term_cols() -> Result<usize, io::Error> {
+    term_size().map(|size| size.col)
+}
+
 pub fn term_size() -> Result<Size, io::Error> {
+    term_size_of(TermStream::Stdout).or_else(|e| term_size_of_tty().map_err(|_| e))
+}
+
+// Retrieves the size of the controlling terminal, which is available even if
+// the standard output is redirected.
+fn term_size_of_tty() -> Result<Size, io::Error> {
+    let tty = File::open("/dev/tty")?;
+    term_size_of(TermStream::Fd(tty.as_raw_fd()))
+}
+
+pub fn term_size_of(stream: TermStream) -> Result<Size, io::Error> {
+    let fd = match stream {
+        TermStream::Stdout => STDOUT_FILENO,
+        TermStream::Stderr => STDERR_FILENO,
+        TermStream::Stdin => STDIN_FILENO,
+        TermStream::Fd(fd) => fd,
+    };
+
     let mut ws = winsize {
         ws_row: 0,
         ws_col: 0,
         ws_xpixel: 0,
         ws_ypixel: 0,
     };
-    let r = unsafe { ioctl(STDOUT_FILENO, TIOCGWINSZ, &mut ws) };
+    let r = unsafe { ioctl(fd, TIOCGWINSZ, &mut ws) };
     match r {
-        0 => Ok(Size {
-            col: ws.ws_col as usize,
-            row: ws.ws_row as usize,
-        }),
+        0 => Ok(Size::from((ws.ws_col, ws.ws_row))),
         _ => Err(io::Error::last_os_error()),
     }
 }
@@ -0,0 +1,61 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use super::{Capabilities, Size, TermStream};
+use rustix::io::Errno;
+use rustix::termios::{isatty, tcgetwinsize};
+use std::fs::File;
+use std::io;
+use std::os::unix::io::{AsFd, BorrowedFd};
+
+pub fn is_tty_stdout() -> bool {
+    isatty(io::stdout().as_fd())
+}
+
+pub fn is_tty_stderr() -> bool {
+    isatty(io::stderr().as_fd())
+}
+
+// The terminals on Unix process ANSI escape sequences, except for the one
+// whose type is `dumb`.
+pub fn capabilities() -> Capabilities {
+    let is_dumb = std::env::var_os("TERM").map_or(false, |term| term == "dumb");
+    Capabilities {
+        virtual_terminal: (is_tty_stdout() || is_tty_stderr()) && !is_dumb,
+    }
+}
+
+pub fn term_cols() -> Result<usize, io::Error> {
+    term_size().map(|size| size.col)
+}
+
+pub fn term_size() -> Result<Size, io::Error> {
+    term_size_of(TermStream::Stdout).or_else(|e| term_size_of_tty().map_err(|_| e))
+}
+
+// Retrieves the size of the controlling terminal, which is available even if
+// the standard output is redirected.
+fn term_size_of_tty() -> Result<Size, io::Error> {
+    let tty = File::open("/dev/tty")?;
+    term_size_of_fd(tty.as_fd())
+}
+
+pub fn term_size_of(stream: TermStream) -> Result<Size, io::Error> {
+    match stream {
+        TermStream::Stdout => term_size_of_fd(io::stdout().as_fd()),
+        TermStream::Stderr => term_size_of_fd(io::stderr().as_fd()),
+        TermStream::Stdin => term_size_of_fd(io::stdin().as_fd()),
+        // a negative file descriptor cannot be borrowed, and is reported as
+        // `EBADF` as the libc backend does.
+        TermStream::Fd(fd) if fd < 0 => Err(Errno::BADF.into()),
+        // the caller of `term_size_of` is responsible for keeping the file
+        // descriptor open during this call, as with the libc backend.
+        TermStream::Fd(fd) => term_size_of_fd(unsafe { BorrowedFd::borrow_raw(fd) }),
+    }
+}
+
+fn term_size_of_fd(fd: BorrowedFd<'_>) -> Result<Size, io::Error> {
+    let ws = tcgetwinsize(fd)?;
+    Ok(Size::from((ws.ws_col, ws.ws_row)))
+}
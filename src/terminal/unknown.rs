@@ -2,13 +2,36 @@
 // This program is free software under MIT License.
 // See the file LICENSE in this distribution for more details.
 
-use super::Size;
+use super::{Capabilities, Size, TermStream};
 use std::io;
 
+fn unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "terminals are not supported on this platform",
+    )
+}
+
+pub fn is_tty_stdout() -> bool {
+    false
+}
+
+pub fn is_tty_stderr() -> bool {
+    false
+}
+
+pub fn capabilities() -> Capabilities {
+    Capabilities::default()
+}
+
 pub fn term_cols() -> Result<usize, io::Error> {
-    Error::new(ErrorKind::Unsupported)
+    Err(unsupported())
+}
+
+pub fn term_size() -> Result<Size, io::Error> {
+    Err(unsupported())
 }
 
-pub fn term_size() -> Result<usize, io::Error> {
-    Error::new(ErrorKind::Unsupported)
+pub fn term_size_of(_stream: TermStream) -> Result<Size, io::Error> {
+    Err(unsupported())
 }
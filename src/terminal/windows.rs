@@ -2,40 +2,69 @@
 // This program is free software under MIT License.
 // See the file LICENSE in this distribution for more details.
 
-use super::Size;
+use super::{Capabilities, Size, TermStream};
+use std::fs::OpenOptions;
 use std::io;
+use std::os::windows::io::AsRawHandle;
 
+use windows::Win32::Foundation::HANDLE;
 use windows::Win32::System::Console::{
-    GetConsoleScreenBufferInfo, GetStdHandle, CONSOLE_CHARACTER_ATTRIBUTES,
-    CONSOLE_SCREEN_BUFFER_INFO, COORD, SMALL_RECT, STD_OUTPUT_HANDLE,
+    GetConsoleMode, GetConsoleScreenBufferInfo, GetStdHandle, CONSOLE_CHARACTER_ATTRIBUTES,
+    CONSOLE_MODE, CONSOLE_SCREEN_BUFFER_INFO, COORD, ENABLE_VIRTUAL_TERMINAL_PROCESSING,
+    SMALL_RECT, STD_ERROR_HANDLE, STD_HANDLE, STD_INPUT_HANDLE, STD_OUTPUT_HANDLE,
 };
 
-pub fn term_cols() -> Result<usize, io::Error> {
-    let mut bi = CONSOLE_SCREEN_BUFFER_INFO {
-        dwSize: COORD { X: 0, Y: 0 },
-        dwCursorPosition: COORD { X: 0, Y: 0 },
-        wAttributes: CONSOLE_CHARACTER_ATTRIBUTES(0),
-        srWindow: SMALL_RECT {
-            Left: 0,
-            Top: 0,
-            Right: 0,
-            Bottom: 0,
-        },
-        dwMaximumWindowSize: COORD { X: 0, Y: 0 },
-    };
+// Returns the console mode of the specified standard handle, or `None` if the
+// handle is not a console.
+fn console_mode(std_handle: STD_HANDLE) -> Option<CONSOLE_MODE> {
+    let h = unsafe { GetStdHandle(std_handle) }.ok()?;
+    let mut mode = CONSOLE_MODE(0);
+    unsafe { GetConsoleMode(h, &mut mode) }.ok()?;
+    Some(mode)
+}
 
-    let h = match unsafe { GetStdHandle(STD_OUTPUT_HANDLE) } {
-        Ok(h) => h,
-        Err(e) => return Err(io::Error::from_raw_os_error(e.code().0)),
-    };
+pub fn is_tty_stdout() -> bool {
+    console_mode(STD_OUTPUT_HANDLE).is_some()
+}
+
+pub fn is_tty_stderr() -> bool {
+    console_mode(STD_ERROR_HANDLE).is_some()
+}
 
-    match unsafe { GetConsoleScreenBufferInfo(h, &mut bi) } {
-        Ok(_) => Ok((bi.srWindow.Right - bi.srWindow.Left + 1) as usize),
-        Err(e) => Err(io::Error::from_raw_os_error(e.code().0)),
+// The console mode of the standard error is used if the standard output is
+// redirected.
+pub fn capabilities() -> Capabilities {
+    let mode = console_mode(STD_OUTPUT_HANDLE).or_else(|| console_mode(STD_ERROR_HANDLE));
+    Capabilities {
+        virtual_terminal: match mode {
+            Some(mode) => mode.0 & ENABLE_VIRTUAL_TERMINAL_PROCESSING.0 != 0,
+            None => false,
+        },
     }
 }
 
+pub fn term_cols() -> Result<usize, io::Error> {
+    term_size().map(|size| size.col)
+}
+
+// The size is retrieved via the standard error, and then via the active
+// console screen buffer, if the standard output is redirected.
 pub fn term_size() -> Result<Size, io::Error> {
+    term_size_of(TermStream::Stdout).or_else(|e| {
+        term_size_of(TermStream::Stderr)
+            .or_else(|_| term_size_of_conout())
+            .map_err(|_| e)
+    })
+}
+
+// Retrieves the size of the active console screen buffer, which is available
+// even if the standard output is redirected.
+fn term_size_of_conout() -> Result<Size, io::Error> {
+    let conout = OpenOptions::new().read(true).write(true).open("CONOUT$")?;
+    term_size_of(TermStream::Handle(conout.as_raw_handle()))
+}
+
+pub fn term_size_of(stream: TermStream) -> Result<Size, io::Error> {
     let mut bi = CONSOLE_SCREEN_BUFFER_INFO {
         dwSize: COORD { X: 0, Y: 0 },
         dwCursorPosition: COORD { X: 0, Y: 0 },
@@ -49,16 +78,27 @@ pub fn term_size() -> Result<Size, io::Error> {
         dwMaximumWindowSize: COORD { X: 0, Y: 0 },
     };
 
-    let h = match unsafe { GetStdHandle(STD_OUTPUT_HANDLE) } {
+    let std_handle = match stream {
+        TermStream::Stdout => STD_OUTPUT_HANDLE,
+        TermStream::Stderr => STD_ERROR_HANDLE,
+        TermStream::Stdin => STD_INPUT_HANDLE,
+        TermStream::Handle(h) => return console_size(HANDLE(h as isize), &mut bi),
+    };
+    let h = match unsafe { GetStdHandle(std_handle) } {
         Ok(h) => h,
         Err(e) => return Err(io::Error::from_raw_os_error(e.code().0)),
     };
+    console_size(h, &mut bi)
+}
 
-    match unsafe { GetConsoleScreenBufferInfo(h, &mut bi) } {
-        Ok(_) => Ok(Size {
-            col: (bi.srWindow.Right - bi.srWindow.Left + 1) as usize,
-            row: (bi.srWindow.Bottom - bi.srWindow.Top + 1) as usize,
-        }),
-        Err(e) => Err(io::Error::from_raw_os_error(e.code().0)),
+fn console_size(h: HANDLE, bi: &mut CONSOLE_SCREEN_BUFFER_INFO) -> Result<Size, io::Error> {
+    if let Err(e) = unsafe { GetConsoleScreenBufferInfo(h, bi) } {
+        return Err(io::Error::from_raw_os_error(e.code().0));
     }
+    // the window rectangle is computed in i32, so that it does not overflow
+    // and a broken rectangle is an error.
+    let win = &bi.srWindow;
+    let col = i32::from(win.Right) - i32::from(win.Left) + 1;
+    let row = i32::from(win.Bottom) - i32::from(win.Top) + 1;
+    Size::try_from((col, row)).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
 }
@@ -0,0 +1,354 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use crate::linebreak::{LboState, LboType};
+use icu::properties::maps;
+use icu::properties::maps::CodePointMapDataBorrowed;
+use icu::properties::{GeneralCategory, LineBreak};
+
+const LINE_BREAK: CodePointMapDataBorrowed<'static, LineBreak> = maps::line_break();
+const GENERAL_CATEGORY: CodePointMapDataBorrowed<'static, GeneralCategory> =
+    maps::general_category();
+
+pub struct Uax14State {
+    prev: LineBreak,
+    space: bool,
+    ri_odd: bool,
+}
+
+impl Uax14State {
+    pub fn new() -> Uax14State {
+        Uax14State {
+            prev: LineBreak::MandatoryBreak,
+            space: false,
+            ri_odd: false,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.prev = LineBreak::MandatoryBreak;
+        self.space = false;
+        self.ri_odd = false;
+    }
+
+    pub fn line_break_opportunity(&mut self, ch: char, state: &mut LboState) {
+        state.lbo_prev = state.lbo_type;
+
+        let mut cls = resolve_class(ch);
+
+        match cls {
+            LineBreak::MandatoryBreak
+            | LineBreak::CarriageReturn
+            | LineBreak::LineFeed
+            | LineBreak::NextLine => {
+                self.reset();
+                state.lbo_type = LboType::Break;
+                return;
+            }
+            LineBreak::Space => {
+                if self.prev != LineBreak::MandatoryBreak {
+                    self.space = true;
+                }
+                state.lbo_type = LboType::Space;
+                return;
+            }
+            LineBreak::CombiningMark | LineBreak::ZWJ => {
+                if !self.space && self.prev != LineBreak::MandatoryBreak {
+                    // LB9: treat X CM* as X.
+                    state.lbo_type = LboType::Never;
+                    return;
+                }
+                // LB10: treat any remaining CM as AL.
+                cls = LineBreak::Alphabetic;
+            }
+            _ => (),
+        }
+
+        let allowed = self.is_break_allowed(cls);
+
+        if cls == LineBreak::RegionalIndicator {
+            self.ri_odd = !(self.prev == LineBreak::RegionalIndicator && self.ri_odd);
+        } else {
+            self.ri_odd = false;
+        }
+        self.prev = cls;
+        self.space = false;
+
+        if cls == LineBreak::ZWSpace {
+            state.lbo_type = LboType::After;
+        } else if allowed {
+            state.lbo_type = LboType::Both;
+        } else {
+            state.lbo_type = LboType::Never;
+        }
+    }
+
+    fn is_break_allowed(&self, cur: LineBreak) -> bool {
+        use LineBreak as Lb;
+        let prev = self.prev;
+
+        // LB2: never break at the start of text.
+        if prev == Lb::MandatoryBreak {
+            return false;
+        }
+        // LB8
+        if prev == Lb::ZWSpace {
+            return true;
+        }
+        // LB7
+        if cur == Lb::ZWSpace {
+            return false;
+        }
+        if !self.space {
+            // LB11
+            if cur == Lb::WordJoiner || prev == Lb::WordJoiner {
+                return false;
+            }
+            // LB12
+            if prev == Lb::Glue {
+                return false;
+            }
+            // LB12a
+            if cur == Lb::Glue && prev != Lb::BreakAfter && prev != Lb::Hyphen {
+                return false;
+            }
+        }
+        // LB13
+        if matches!(
+            cur,
+            Lb::ClosePunctuation
+                | Lb::CloseParenthesis
+                | Lb::Exclamation
+                | Lb::InfixNumeric
+                | Lb::BreakSymbols
+        ) {
+            return false;
+        }
+        // LB14
+        if prev == Lb::OpenPunctuation {
+            return false;
+        }
+        // LB15
+        if prev == Lb::Quotation && cur == Lb::OpenPunctuation {
+            return false;
+        }
+        // LB16
+        if matches!(prev, Lb::ClosePunctuation | Lb::CloseParenthesis) && cur == Lb::Nonstarter {
+            return false;
+        }
+        // LB17
+        if prev == Lb::BreakBoth && cur == Lb::BreakBoth {
+            return false;
+        }
+        // LB18
+        if self.space {
+            return true;
+        }
+        // LB19
+        if cur == Lb::Quotation || prev == Lb::Quotation {
+            return false;
+        }
+        // LB20
+        if cur == Lb::ContingentBreak || prev == Lb::ContingentBreak {
+            return true;
+        }
+        // LB21
+        if matches!(cur, Lb::BreakAfter | Lb::Hyphen | Lb::Nonstarter) || prev == Lb::BreakBefore {
+            return false;
+        }
+        // LB21b
+        if prev == Lb::BreakSymbols && cur == Lb::HebrewLetter {
+            return false;
+        }
+        // LB22
+        if cur == Lb::Inseparable {
+            return false;
+        }
+        let is_ahl = |c: LineBreak| c == Lb::Alphabetic || c == Lb::HebrewLetter;
+        // LB23
+        if (is_ahl(prev) && cur == Lb::Numeric) || (prev == Lb::Numeric && is_ahl(cur)) {
+            return false;
+        }
+        // LB23a
+        if prev == Lb::PrefixNumeric && matches!(cur, Lb::Ideographic | Lb::EBase | Lb::EModifier) {
+            return false;
+        }
+        if matches!(prev, Lb::Ideographic | Lb::EBase | Lb::EModifier) && cur == Lb::PostfixNumeric
+        {
+            return false;
+        }
+        // LB24
+        let is_prpo = |c: LineBreak| c == Lb::PrefixNumeric || c == Lb::PostfixNumeric;
+        if (is_prpo(prev) && is_ahl(cur)) || (is_ahl(prev) && is_prpo(cur)) {
+            return false;
+        }
+        // LB25 (simplified)
+        if matches!(
+            prev,
+            Lb::ClosePunctuation | Lb::CloseParenthesis | Lb::Numeric
+        ) && is_prpo(cur)
+        {
+            return false;
+        }
+        if is_prpo(prev) && matches!(cur, Lb::OpenPunctuation | Lb::Numeric) {
+            return false;
+        }
+        if matches!(
+            prev,
+            Lb::Hyphen | Lb::InfixNumeric | Lb::Numeric | Lb::BreakSymbols
+        ) && cur == Lb::Numeric
+        {
+            return false;
+        }
+        // LB26
+        if prev == Lb::JL && matches!(cur, Lb::JL | Lb::JV | Lb::H2 | Lb::H3) {
+            return false;
+        }
+        if matches!(prev, Lb::JV | Lb::H2) && matches!(cur, Lb::JV | Lb::JT) {
+            return false;
+        }
+        if matches!(prev, Lb::JT | Lb::H3) && cur == Lb::JT {
+            return false;
+        }
+        // LB27
+        let is_hangul = |c: LineBreak| matches!(c, Lb::JL | Lb::JV | Lb::JT | Lb::H2 | Lb::H3);
+        if (is_hangul(prev) && cur == Lb::PostfixNumeric)
+            || (prev == Lb::PrefixNumeric && is_hangul(cur))
+        {
+            return false;
+        }
+        // LB28
+        if is_ahl(prev) && is_ahl(cur) {
+            return false;
+        }
+        // LB29
+        if prev == Lb::InfixNumeric && is_ahl(cur) {
+            return false;
+        }
+        // LB30
+        if (is_ahl(prev) || prev == Lb::Numeric) && cur == Lb::OpenPunctuation {
+            return false;
+        }
+        if prev == Lb::CloseParenthesis && (is_ahl(cur) || cur == Lb::Numeric) {
+            return false;
+        }
+        // LB30a
+        if prev == Lb::RegionalIndicator && cur == Lb::RegionalIndicator && self.ri_odd {
+            return false;
+        }
+        // LB30b
+        if prev == Lb::EBase && cur == Lb::EModifier {
+            return false;
+        }
+        // LB31
+        true
+    }
+}
+
+// LB1: resolves the classes which have no own rules.
+fn resolve_class(ch: char) -> LineBreak {
+    match LINE_BREAK.get(ch) {
+        LineBreak::Ambiguous
+        | LineBreak::Surrogate
+        | LineBreak::Unknown
+        | LineBreak::Aksara
+        | LineBreak::AksaraPrebase
+        | LineBreak::AksaraStart
+        | LineBreak::ViramaFinal
+        | LineBreak::Virama => LineBreak::Alphabetic,
+        LineBreak::ComplexContext => match GENERAL_CATEGORY.get(ch) {
+            GeneralCategory::NonspacingMark | GeneralCategory::SpacingMark => {
+                LineBreak::CombiningMark
+            }
+            _ => LineBreak::Alphabetic,
+        },
+        LineBreak::ConditionalJapaneseStarter => LineBreak::Nonstarter,
+        cls => cls,
+    }
+}
+
+#[cfg(test)]
+mod test_of_uax14 {
+    use super::*;
+
+    fn new_state() -> LboState {
+        LboState {
+            lbo_type: LboType::Never,
+            lbo_prev: LboType::Never,
+            open_apos: 0,
+            open_quot: 0,
+        }
+    }
+
+    fn classify(text: &str) -> Vec<LboType> {
+        let mut ustate = Uax14State::new();
+        let mut state = new_state();
+        let mut vec = Vec::new();
+        for ch in text.chars() {
+            ustate.line_break_opportunity(ch, &mut state);
+            vec.push(state.lbo_type);
+        }
+        vec
+    }
+
+    #[test]
+    fn test_letters_and_spaces() {
+        use LboType::*;
+        assert_eq!(classify("ab cd"), vec![Never, Never, Space, Both, Never]);
+    }
+
+    #[test]
+    fn test_hyphen() {
+        use LboType::*;
+        assert_eq!(classify("a-b"), vec![Never, Never, Both]);
+        assert_eq!(classify("-1"), vec![Never, Never]);
+    }
+
+    #[test]
+    fn test_ideographs_and_close_punctuation() {
+        use LboType::*;
+        assert_eq!(classify("漢字、漢"), vec![Never, Both, Never, Both]);
+        assert_eq!(classify("「漢"), vec![Never, Never]);
+    }
+
+    #[test]
+    fn test_hard_breaks() {
+        use LboType::*;
+        assert_eq!(classify("a\nb"), vec![Never, Break, Never]);
+        assert_eq!(classify("a\u{0085}b"), vec![Never, Break, Never]);
+    }
+
+    #[test]
+    fn test_combining_marks() {
+        use LboType::*;
+        assert_eq!(classify("漢\u{0301}字"), vec![Never, Never, Both]);
+    }
+
+    #[test]
+    fn test_zero_width_space() {
+        use LboType::*;
+        assert_eq!(
+            classify("ab\u{200B}cd"),
+            vec![Never, Never, After, Both, Never]
+        );
+    }
+
+    #[test]
+    fn test_regional_indicators() {
+        use LboType::*;
+        assert_eq!(
+            classify("\u{1F1EF}\u{1F1F5}\u{1F1FA}\u{1F1F8}"),
+            vec![Never, Never, Both, Never]
+        );
+    }
+
+    #[test]
+    fn test_numbers() {
+        use LboType::*;
+        assert_eq!(
+            classify("$10.5%"),
+            vec![Never, Never, Never, Never, Never, Never]
+        );
+    }
+}
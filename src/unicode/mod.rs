@@ -2,8 +2,14 @@
 // This program is free software under MIT License.
 // See the file LICENSE in this distribution for more details.
 
+#![allow(clippy::needless_return, clippy::bool_assert_comparison)]
+
+use alloc::vec::Vec;
+use core::fmt::Write;
 use icu::properties::maps;
 use icu::properties::maps::CodePointMapDataBorrowed;
+use icu::properties::sets;
+use icu::properties::sets::CodePointSetDataBorrowed;
 use icu::properties::EastAsianWidth;
 use icu::properties::GeneralCategory;
 
@@ -11,6 +17,33 @@ const GENERAL_CATEGORY: CodePointMapDataBorrowed<'static, GeneralCategory> =
     maps::general_category();
 pub const EAST_ASIAN_WIDTH: CodePointMapDataBorrowed<'static, EastAsianWidth> =
     maps::east_asian_width();
+const EMOJI_PRESENTATION: CodePointSetDataBorrowed<'static> = sets::emoji_presentation();
+const EXTENDED_PICTOGRAPHIC: CodePointSetDataBorrowed<'static> = sets::extended_pictographic();
+
+const VS16: char = '\u{FE0F}';
+
+// The version of the Unicode Character Database of the data compiled in the
+// `icu` crate, which has to be updated with it.
+const UNICODE_VERSION: (u8, u8, u8) = (15, 1, 0);
+
+/// Returns the version of the Unicode Character Database which the width
+/// calculation and the line breaking of this crate are based on, as a tuple of
+/// `(major, minor, update)`.
+///
+/// The characters which are assigned in the later versions are treated as
+/// unassigned.
+/// To match the widths with a terminal which uses an older table, such as a
+/// `wcwidth` of an old C library, a `WidthProvider` can be used.
+///
+/// ```rust
+///    use linebreak::unicode_version;
+///
+///    let (major, minor, _) = unicode_version();
+///    assert!((major, minor) >= (15, 0));
+/// ```
+pub fn unicode_version() -> (u8, u8, u8) {
+    UNICODE_VERSION
+}
 
 /// Checks whether the specified codepoint is one of the printable characters
 /// that includes letters, marks, numbers, punctuations, symbols from Unicode
@@ -24,9 +57,9 @@ pub const EAST_ASIAN_WIDTH: CodePointMapDataBorrowed<'static, EastAsianWidth> =
 ///    assert_eq!(is_print('a'), true);
 /// ```
 pub fn is_print(ch: char) -> bool {
-    if ch == ' ' {
-        // 0x20,SP,SPACE
-        return true;
+    if ch.is_ascii() {
+        // 0x20,SP,SPACE and the ASCII graphic characters
+        return ch == ' ' || ch.is_ascii_graphic();
     }
     match GENERAL_CATEGORY.get(ch) {
         GeneralCategory::LowercaseLetter => true,      // Ll
@@ -55,9 +88,118 @@ pub fn is_print(ch: char) -> bool {
     }
 }
 
+/// `AmbiguousWidth` is the enum for specifying the display width of the
+/// characters whose East-Asian-Width is Ambiguous.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AmbiguousWidth {
+    /// Ambiguous characters are treated as narrow (width 1).
+    Narrow,
+
+    /// Ambiguous characters are treated as wide (width 2).
+    /// This is the default, which is the same as `char_width`.
+    #[default]
+    Wide,
+}
+
+impl AmbiguousWidth {
+    /// Returns the recommended setting for the specified locale name, such as
+    /// `ja_JP.UTF-8`.
+    ///
+    /// The terminals in Chinese, Japanese and Korean locales usually display
+    /// ambiguous characters as wide, and the others as narrow.
+    /// If the locale name is empty, this function returns the default value.
+    ///
+    /// ```rust
+    ///     use linebreak::AmbiguousWidth;
+    ///
+    ///     assert_eq!(AmbiguousWidth::for_locale("ja_JP.UTF-8"), AmbiguousWidth::Wide);
+    ///     assert_eq!(AmbiguousWidth::for_locale("en_US.UTF-8"), AmbiguousWidth::Narrow);
+    /// ```
+    pub fn for_locale(locale: &str) -> AmbiguousWidth {
+        if locale.is_empty() {
+            return AmbiguousWidth::default();
+        }
+        let lang = locale
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        match lang.as_str() {
+            "ja" | "zh" | "ko" | "yue" => AmbiguousWidth::Wide,
+            _ => AmbiguousWidth::Narrow,
+        }
+    }
+
+    /// Returns the recommended setting for the current locale, which is given
+    /// by the first non-empty one of the environment variables `LC_ALL`,
+    /// `LC_CTYPE` and `LANG`.
+    /// If none of them is set, this function returns the default value.
+    #[cfg(feature = "std")]
+    pub fn from_locale() -> AmbiguousWidth {
+        for name in ["LC_ALL", "LC_CTYPE", "LANG"] {
+            if let Ok(locale) = std::env::var(name) {
+                if !locale.is_empty() {
+                    return AmbiguousWidth::for_locale(&locale);
+                }
+            }
+        }
+        AmbiguousWidth::default()
+    }
+}
+
+/// `NonPrintPolicy` is the enum for specifying how `LineIter` outputs the
+/// non-printable characters, such as control characters, except whitespaces.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum NonPrintPolicy {
+    /// Non-printable characters are removed from the output (default).
+    #[default]
+    Strip,
+
+    /// Non-printable characters are output verbatim, and have width 0.
+    Keep,
+
+    /// Non-printable characters are output as escapes like `\u{0002}`.
+    EscapeUnicode,
+
+    /// C0 control characters and DEL are output in caret notation like `^B`
+    /// and `^?`, and the other non-printable characters are output as escapes
+    /// like `\u{200E}`.
+    EscapeCaret,
+}
+
+// Checks whether the specified character is subject to `NonPrintPolicy`.
+pub(crate) fn is_non_print(ch: char) -> bool {
+    !is_print(ch) && !ch.is_whitespace() && !is_zero_width_attached(ch)
+}
+
+// Stores the escape of the specified character into the specified vector in
+// reverse order, so that the characters can be taken with `pop`.
+pub(crate) fn escape_non_print(ch: char, policy: NonPrintPolicy, escaped: &mut Vec<char>) {
+    let mut s = alloc::string::String::new();
+    match policy {
+        NonPrintPolicy::EscapeCaret if (ch as u32) < 0x20 => {
+            s.push('^');
+            s.push(((ch as u8) + 0x40) as char);
+        }
+        NonPrintPolicy::EscapeCaret if ch == '\u{7F}' => s.push_str("^?"),
+        _ => {
+            let _ = write!(s, "\\u{{{:04X}}}", ch as u32);
+        }
+    }
+    escaped.clear();
+    escaped.extend(s.chars().rev());
+}
+
 /// Returns the display width of the specified character.
 /// A display width is determined by the Unicode Standard Annex #11 (UAX11)
 /// East-Asian-Width.
+/// Emoji characters which have the emoji presentation by default, and the
+/// pictographs in the planes of emoji (U+1F000 and above) have width 2, and
+/// nonspacing marks, enclosing marks and variation selectors have width 0.
 ///
 /// ```rust
 ///     use linebreak::char_width;
@@ -66,11 +208,24 @@ pub fn is_print(ch: char) -> bool {
 ///     assert_eq!(char_width(' '), 1);
 ///     assert_eq!(char_width('a'), 1);
 ///     assert_eq!(char_width('ａ'), 2);
+///     assert_eq!(char_width('🔥'), 2);
+///     assert_eq!(char_width('\u{FE0F}'), 0);
+///     assert_eq!(char_width('\u{0301}'), 0); // COMBINING ACUTE ACCENT
 /// ```
 pub fn char_width(ch: char) -> usize {
+    if ch.is_ascii() {
+        return ascii_width(ch as u8);
+    }
     if !is_print(ch) {
         return 0;
     }
+    if is_combining(ch) {
+        return 0;
+    }
+    if EMOJI_PRESENTATION.contains(ch) || (ch >= '\u{1F000}' && EXTENDED_PICTOGRAPHIC.contains(ch))
+    {
+        return 2;
+    }
     match EAST_ASIAN_WIDTH.get(ch) {
         EastAsianWidth::Halfwidth => 1,
         EastAsianWidth::Narrow => 1,
@@ -81,29 +236,204 @@ pub fn char_width(ch: char) -> usize {
     }
 }
 
+// Returns the display width of the specified character following the
+// previous character.
+// VARIATION SELECTOR-16 (U+FE0F) widens the previous pictograph which has the
+// text presentation by default to width 2, so this function returns 1 for it.
+pub(crate) fn char_width_after(prev: Option<char>, ch: char, ambiguous: AmbiguousWidth) -> usize {
+    if ch == VS16 {
+        if let Some(prev) = prev {
+            if EXTENDED_PICTOGRAPHIC.contains(prev) && char_width_with(prev, ambiguous) == 1 {
+                return 1;
+            }
+        }
+        return 0;
+    }
+    char_width_with(ch, ambiguous)
+}
+
+pub(crate) fn char_width_with(ch: char, ambiguous: AmbiguousWidth) -> usize {
+    if ambiguous == AmbiguousWidth::Narrow
+        && !ch.is_ascii()
+        && EAST_ASIAN_WIDTH.get(ch) == EastAsianWidth::Ambiguous
+    {
+        if !is_print(ch) {
+            return 0;
+        }
+        return 1;
+    }
+    char_width(ch)
+}
+
 /// Returns the display width of the specified text.
 /// This function calculates the width of the text taking into account the
 /// letter width determined by the Unicode Standard Annex #11 (UAX11)
 /// East-Asian-Width.
+/// A pictograph followed by VARIATION SELECTOR-16 (U+FE0F), which requests
+/// the emoji presentation, is counted as width 2, and so is a pair of
+/// regional indicators which represents a flag.
 ///
 /// ```rust
 ///     use linebreak::text_width;
 ///
 ///    assert_eq!(text_width("Hello, world!"), 13);
 ///    assert_eq!(text_width("こんにちわ、世界！"), 18);
+///    assert_eq!(text_width("\u{26A0}"), 1);
+///    assert_eq!(text_width("\u{26A0}\u{FE0F}"), 2);
+///    assert_eq!(text_width("🇯🇵"), 2);
 /// ```
 pub fn text_width(text: &str) -> usize {
-    let mut w: usize = 0;
-    for ch in text.chars() {
-        w += char_width(ch);
+    text_width_with(text, AmbiguousWidth::Wide)
+}
+
+/// Returns the display width of the specified character in CJK contexts,
+/// where the characters whose East-Asian-Width is Ambiguous are wide.
+///
+/// This function corresponds to `UnicodeWidthChar::width_cjk` of the
+/// `unicode-width` crate, and returns the same value as `char_width`.
+///
+/// ```rust
+///     use linebreak::char_width_cjk;
+///
+///     assert_eq!(char_width_cjk('a'), 1);
+///     assert_eq!(char_width_cjk('α'), 2);
+///     assert_eq!(char_width_cjk('あ'), 2);
+/// ```
+pub fn char_width_cjk(ch: char) -> usize {
+    char_width_with(ch, AmbiguousWidth::Wide)
+}
+
+/// Returns the display width of the specified text in CJK contexts, where the
+/// characters whose East-Asian-Width is Ambiguous are wide.
+///
+/// This function corresponds to `UnicodeWidthStr::width_cjk` of the
+/// `unicode-width` crate, and returns the same value as `text_width`.
+/// `LineIter::set_cjk` and `Options::cjk` switch the width calculation of line
+/// breaking between this and the non-CJK one.
+///
+/// ```rust
+///     use linebreak::text_width_cjk;
+///
+///     assert_eq!(text_width_cjk("abc"), 3);
+///     assert_eq!(text_width_cjk("αβγ"), 6);
+/// ```
+pub fn text_width_cjk(text: &str) -> usize {
+    text_width_with(text, AmbiguousWidth::Wide)
+}
+
+pub(crate) fn text_width_with(text: &str, ambiguous: AmbiguousWidth) -> usize {
+    // scans the leading ASCII bytes directly, which are most of English texts.
+    let bytes = text.as_bytes();
+    let n = bytes
+        .iter()
+        .position(|b| !b.is_ascii())
+        .unwrap_or(bytes.len());
+    let mut w: usize = bytes[..n].iter().map(|b| ascii_width(*b)).sum();
+    if n == bytes.len() {
+        return w;
+    }
+
+    let mut prev = text[..n].chars().next_back();
+    let mut chars = text[n..].chars().peekable();
+    while let Some(ch) = chars.next() {
+        if is_regional_indicator(ch) {
+            if let Some(next) = chars.next_if(|c| is_regional_indicator(*c)) {
+                w += 2;
+                prev = Some(next);
+                continue;
+            }
+        }
+        w += char_width_after(prev, ch, ambiguous);
+        prev = Some(ch);
     }
     return w;
 }
 
+// Returns the display width of the specified ASCII character: 1 for a
+// graphic character and the space, and 0 for a control character.
+fn ascii_width(b: u8) -> usize {
+    (0x20..0x7f).contains(&b) as usize
+}
+
+fn is_combining(ch: char) -> bool {
+    !ch.is_ascii()
+        && matches!(
+            GENERAL_CATEGORY.get(ch),
+            GeneralCategory::NonspacingMark | GeneralCategory::EnclosingMark
+        )
+}
+
+// Checks whether the specified character has no width and is attached to the
+// previous character, such as combining marks, variation selectors and
+// ZERO WIDTH (NON-)JOINER.
+pub(crate) fn is_zero_width_attached(ch: char) -> bool {
+    ch == '\u{200C}' || ch == '\u{200D}' || is_combining(ch)
+}
+
+// Checks whether the specified character is a REGIONAL INDICATOR SYMBOL
+// LETTER, a pair of which represents a flag.
+pub(crate) fn is_regional_indicator(ch: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&ch)
+}
+
 #[cfg(test)]
 mod test_of_unicode {
     use super::*;
 
+    #[test]
+    fn test_width_cjk() {
+        for ch in ['a', 'α', '─', '○', 'あ', '\u{0301}', '\u{FE0F}'] {
+            assert_eq!(char_width_cjk(ch), char_width(ch));
+        }
+        assert_eq!(char_width_cjk('α'), 2);
+        assert_eq!(char_width_with('α', AmbiguousWidth::Narrow), 1);
+        assert_eq!(text_width_cjk("α─○"), 6);
+        assert_eq!(text_width_with("α─○", AmbiguousWidth::Narrow), 3);
+    }
+
+    #[test]
+    fn test_ambiguous_width_for_locale() {
+        assert_eq!(
+            AmbiguousWidth::for_locale("ja_JP.UTF-8"),
+            AmbiguousWidth::Wide
+        );
+        assert_eq!(
+            AmbiguousWidth::for_locale("zh_CN.GB18030"),
+            AmbiguousWidth::Wide
+        );
+        assert_eq!(AmbiguousWidth::for_locale("zh-TW"), AmbiguousWidth::Wide);
+        assert_eq!(
+            AmbiguousWidth::for_locale("ko_KR.eucKR"),
+            AmbiguousWidth::Wide
+        );
+        assert_eq!(AmbiguousWidth::for_locale("JA"), AmbiguousWidth::Wide);
+        assert_eq!(
+            AmbiguousWidth::for_locale("en_US.UTF-8"),
+            AmbiguousWidth::Narrow
+        );
+        assert_eq!(
+            AmbiguousWidth::for_locale("de_DE@euro"),
+            AmbiguousWidth::Narrow
+        );
+        assert_eq!(AmbiguousWidth::for_locale("C"), AmbiguousWidth::Narrow);
+        assert_eq!(AmbiguousWidth::for_locale("POSIX"), AmbiguousWidth::Narrow);
+        assert_eq!(AmbiguousWidth::for_locale(""), AmbiguousWidth::Wide);
+    }
+
+    #[test]
+    fn test_unicode_version() {
+        assert_eq!(unicode_version(), (15, 1, 0));
+        // U+2FFC is assigned in Unicode 15.1, and U+1FAE9 in Unicode 16.0.
+        assert_ne!(
+            GENERAL_CATEGORY.get('\u{2FFC}'),
+            GeneralCategory::Unassigned
+        );
+        assert_eq!(
+            GENERAL_CATEGORY.get('\u{1FAE9}'),
+            GeneralCategory::Unassigned
+        );
+    }
+
     #[test]
     fn test_is_print() {
         for ch in '\0'..char::MAX {
@@ -134,6 +464,28 @@ mod test_of_unicode {
         }
     }
 
+    #[test]
+    fn test_char_width_of_ascii() {
+        for ch in '\0'..='\x7f' {
+            let expected = match GENERAL_CATEGORY.get(ch) {
+                GeneralCategory::Control => 0,
+                _ => 1,
+            };
+            assert_eq!(char_width(ch), expected);
+            assert_eq!(char_width_with(ch, AmbiguousWidth::Narrow), expected);
+        }
+    }
+
+    #[test]
+    fn test_text_width_with_ascii_prefix() {
+        assert_eq!(text_width_with("", AmbiguousWidth::Wide), 0);
+        assert_eq!(text_width_with("abc\tdef\n", AmbiguousWidth::Wide), 6);
+        assert_eq!(text_width_with("abc\u{3042}d", AmbiguousWidth::Wide), 6);
+        assert_eq!(text_width_with("\u{26A0}\u{FE0F}", AmbiguousWidth::Wide), 2);
+        assert_eq!(text_width_with("a\u{FE0F}", AmbiguousWidth::Wide), 1);
+        assert_eq!(text_width_with("a\u{00B1}", AmbiguousWidth::Narrow), 2);
+    }
+
     #[test]
     fn test_char_width() {
         let ch = 'क';
@@ -165,10 +517,102 @@ mod test_of_unicode {
         assert_eq!(char_width(ch), 0);
     }
 
+    #[test]
+    fn test_char_width_with_ambiguous_width() {
+        assert_eq!(char_width_with('α', AmbiguousWidth::Wide), 2);
+        assert_eq!(char_width_with('α', AmbiguousWidth::Narrow), 1);
+        assert_eq!(char_width_with('a', AmbiguousWidth::Narrow), 1);
+        assert_eq!(char_width_with('あ', AmbiguousWidth::Narrow), 2);
+        assert_eq!(char_width_with('\u{E000}', AmbiguousWidth::Narrow), 0);
+    }
+
+    #[test]
+    fn test_char_width_of_emoji() {
+        assert_eq!(char_width('😀'), 2);
+        assert_eq!(char_width('🦬'), 2);
+        assert_eq!(char_width('⌚'), 2);
+        assert_eq!(char_width('\u{2764}'), 1); // HEAVY BLACK HEART
+        assert_eq!(char_width('©'), 1);
+        assert_eq!(char_width('\u{FE0E}'), 0);
+        assert_eq!(char_width(VS16), 0);
+        assert_eq!(char_width_with('😀', AmbiguousWidth::Narrow), 2);
+    }
+
+    #[test]
+    fn test_char_width_of_zero_width_chars() {
+        assert_eq!(char_width('\u{0301}'), 0); // COMBINING ACUTE ACCENT
+        assert_eq!(char_width('\u{20DD}'), 0); // COMBINING ENCLOSING CIRCLE
+        assert_eq!(char_width('\u{3099}'), 0); // COMBINING KATAKANA-HIRAGANA VOICED SOUND MARK
+        assert_eq!(char_width('\u{200D}'), 0); // ZERO WIDTH JOINER
+        assert_eq!(char_width('\u{0903}'), 1); // DEVANAGARI SIGN VISARGA (Mc)
+        assert_eq!(text_width("e\u{0301}"), 1);
+
+        assert!(is_zero_width_attached('\u{0301}'));
+        assert!(is_zero_width_attached('\u{FE0F}'));
+        assert!(is_zero_width_attached('\u{200C}'));
+        assert!(is_zero_width_attached('\u{200D}'));
+        assert!(!is_zero_width_attached('\u{200B}'));
+        assert!(!is_zero_width_attached('\u{0903}'));
+        assert!(!is_zero_width_attached('a'));
+    }
+
+    #[test]
+    fn test_is_non_print() {
+        assert!(is_non_print('\u{02}'));
+        assert!(is_non_print('\u{1B}'));
+        assert!(is_non_print('\u{7F}'));
+        assert!(is_non_print('\u{200E}'));
+        assert!(!is_non_print('\t'));
+        assert!(!is_non_print('\n'));
+        assert!(!is_non_print('\u{200D}'));
+        assert!(!is_non_print('a'));
+    }
+
+    #[test]
+    fn test_escape_non_print() {
+        let mut v = Vec::new();
+        escape_non_print('\u{02}', NonPrintPolicy::EscapeUnicode, &mut v);
+        assert_eq!(v.iter().rev().collect::<String>(), "\\u{0002}");
+        escape_non_print('\u{02}', NonPrintPolicy::EscapeCaret, &mut v);
+        assert_eq!(v.iter().rev().collect::<String>(), "^B");
+        escape_non_print('\u{00}', NonPrintPolicy::EscapeCaret, &mut v);
+        assert_eq!(v.iter().rev().collect::<String>(), "^@");
+        escape_non_print('\u{7F}', NonPrintPolicy::EscapeCaret, &mut v);
+        assert_eq!(v.iter().rev().collect::<String>(), "^?");
+        escape_non_print('\u{E0001}', NonPrintPolicy::EscapeCaret, &mut v);
+        assert_eq!(v.iter().rev().collect::<String>(), "\\u{E0001}");
+        assert_eq!(v.pop(), Some('\\'));
+    }
+
+    #[test]
+    fn test_char_width_after() {
+        let amb = AmbiguousWidth::Wide;
+        assert_eq!(char_width_after(None, VS16, amb), 0);
+        assert_eq!(char_width_after(Some('\u{2764}'), VS16, amb), 1);
+        assert_eq!(char_width_after(Some('😀'), VS16, amb), 0);
+        assert_eq!(char_width_after(Some('a'), VS16, amb), 0);
+        assert_eq!(char_width_after(Some('\u{2764}'), '\u{FE0E}', amb), 0);
+        assert_eq!(char_width_after(Some('a'), 'b', amb), 1);
+    }
+
     #[test]
     fn test_text_width() {
         assert_eq!(text_width("abc"), 3);
         assert_eq!(text_width("あいう"), 6);
         assert_eq!(text_width(""), 0);
+        assert_eq!(text_width("I \u{2764}\u{FE0F} Rust"), 9);
+        assert_eq!(text_width("\u{26A0}\u{FE0E}"), 1);
+        assert_eq!(text_width("😀\u{FE0F}"), 2);
+    }
+
+    #[test]
+    fn test_text_width_of_flags() {
+        assert_eq!(text_width("🇯🇵"), 2);
+        assert_eq!(text_width("🇯🇵🇺🇸"), 4);
+        assert_eq!(text_width("🇯🇵🇺"), 4);
+        assert_eq!(text_width("a🇯b🇵"), 6);
+        assert!(is_regional_indicator('\u{1F1E6}'));
+        assert!(is_regional_indicator('\u{1F1FF}'));
+        assert!(!is_regional_indicator('\u{1F200}'));
     }
 }
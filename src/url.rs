@@ -0,0 +1,186 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use alloc::string::String;
+use core::str::Chars;
+
+use crate::linebreak::{LboState, LboType};
+
+/// `UrlBreak` is the enum for specifying how `LineIter` breaks URLs and file
+/// paths.
+///
+/// A URL is a word which starts with a scheme followed by `://` or with
+/// `www.`, and a file path is a word which starts with `/`, `./`, `../` or
+/// `~/`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum UrlBreak {
+    /// URLs and file paths are not recognized, and are broken as the other
+    /// words (default).
+    #[default]
+    Normal,
+
+    /// URLs and file paths are kept intact.
+    /// If one of them is wider than the line width, it is put on its own line
+    /// and overflows the line.
+    Keep,
+
+    /// URLs and file paths are broken only after `/`, `?`, `&` and `=` which
+    /// follow the scheme part.
+    AtDelimiters,
+}
+
+pub struct UrlState {
+    len: usize,
+    pos: usize,
+    head: usize,
+    pub overflow: bool,
+}
+
+impl UrlState {
+    pub fn new() -> UrlState {
+        UrlState {
+            len: 0,
+            pos: 0,
+            head: 0,
+            overflow: false,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.len = 0;
+        self.pos = 0;
+        self.head = 0;
+        self.overflow = false;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.pos < self.len
+    }
+
+    pub fn start(&mut self, len: usize, head: usize, overflow: bool) {
+        self.len = len;
+        self.pos = 0;
+        self.head = head;
+        self.overflow = overflow;
+    }
+
+    pub fn line_break_opportunity(&mut self, ch: char, mode: UrlBreak, state: &mut LboState) {
+        let pos = self.pos;
+        self.pos += 1;
+
+        state.lbo_type = match mode {
+            UrlBreak::AtDelimiters if pos >= self.head && matches!(ch, '/' | '?' | '&' | '=') => {
+                LboType::After
+            }
+            _ => LboType::Never,
+        };
+    }
+}
+
+// Finds a URL or a file path which starts with the specified character and
+// continues with the following characters, and returns the number of the
+// characters of the word and of the part in which no line break is allowed.
+pub fn find_url(ch: char, rest: Chars) -> Option<(usize, usize)> {
+    let mut word = String::new();
+    word.push(ch);
+    for c in rest {
+        if c.is_whitespace() {
+            break;
+        }
+        word.push(c);
+    }
+    let len = word.chars().count();
+
+    let body = word.trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '}', '>', '"', '\'']);
+
+    if let Some(i) = body.find("://") {
+        let scheme = &body[..i];
+        let valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+        if valid && body.len() > i + 3 {
+            return Some((len, i + 3));
+        }
+        return None;
+    }
+
+    for head in ["www.", "/", "./", "../", "~/"] {
+        if body.starts_with(head) && body.len() > head.len() {
+            return Some((len, head.len()));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test_of_url {
+    use super::*;
+
+    fn find(word: &str) -> Option<(usize, usize)> {
+        let mut chars = word.chars();
+        let ch = chars.next().unwrap();
+        find_url(ch, chars)
+    }
+
+    #[test]
+    fn test_find_url() {
+        assert_eq!(find("https://example.com/a"), Some((21, 8)));
+        assert_eq!(find("git+ssh://host"), Some((14, 10)));
+        assert_eq!(find("www.rust-lang.org"), Some((17, 4)));
+        assert_eq!(find("https://example.com. Next"), Some((20, 8)));
+        assert_eq!(find("https://"), None);
+        assert_eq!(find("1http://example.com"), None);
+        assert_eq!(find("www."), None);
+        assert_eq!(find("hello"), None);
+        assert_eq!(find("and/or"), None);
+    }
+
+    #[test]
+    fn test_find_path() {
+        assert_eq!(find("/usr/local/bin"), Some((14, 1)));
+        assert_eq!(find("./src/lib.rs"), Some((12, 2)));
+        assert_eq!(find("../README.md,"), Some((13, 3)));
+        assert_eq!(find("~/.cargo"), Some((8, 2)));
+        assert_eq!(find("/"), None);
+        assert_eq!(find("/."), None);
+    }
+
+    #[test]
+    fn test_line_break_opportunity() {
+        let mut state = LboState {
+            lbo_type: LboType::Never,
+            lbo_prev: LboType::Never,
+            open_apos: 0,
+            open_quot: 0,
+        };
+        let url = "http://a/b?c=d&e";
+
+        let mut ustate = UrlState::new();
+        ustate.start(url.len(), 7, false);
+        let mut vec = Vec::new();
+        for ch in url.chars() {
+            assert!(ustate.is_active());
+            ustate.line_break_opportunity(ch, UrlBreak::AtDelimiters, &mut state);
+            vec.push(state.lbo_type);
+        }
+        assert!(!ustate.is_active());
+        let afters: Vec<usize> = (0..vec.len())
+            .filter(|i| vec[*i] == LboType::After)
+            .collect();
+        assert_eq!(afters, [8, 10, 12, 14]);
+
+        ustate.start(url.len(), 7, true);
+        for ch in url.chars() {
+            ustate.line_break_opportunity(ch, UrlBreak::Keep, &mut state);
+            assert_eq!(state.lbo_type, LboType::Never);
+        }
+        assert!(ustate.overflow);
+        ustate.reset();
+        assert!(!ustate.is_active());
+        assert!(!ustate.overflow);
+    }
+}
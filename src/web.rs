@@ -0,0 +1,20 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use web_sys::Element;
+
+/// Returns the number of the columns which fit in the content area of the
+/// specified element, such as the container of a web-based terminal.
+///
+/// The second argument is the width in pixels of a narrow character of the
+/// font used in the element.
+/// This function returns `None` if the column width is not positive.
+/// This function is available with the `web` feature on `wasm32` targets.
+pub fn element_cols(element: &Element, col_width: f64) -> Option<usize> {
+    if col_width <= 0.0 {
+        return None;
+    }
+    let width = f64::from(element.client_width().max(0));
+    Some((width / col_width) as usize)
+}
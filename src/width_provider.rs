@@ -0,0 +1,79 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+/// `WidthProvider` is the trait for measuring the display widths of
+/// characters instead of the built-in `char_width` function.
+///
+/// This trait is used to supply a width table which is specific to a terminal
+/// or a font, for example, a table which treats East Asian ambiguous
+/// characters as narrow, or which is compatible with `wcwidth`.
+///
+/// A closure which takes a character and returns its width implements this
+/// trait.
+///
+/// ```rust
+///     use linebreak::{char_width, text_width_by, LineIter, WidthProvider};
+///
+///     // treats CJK characters as narrow, like some fonts do.
+///     struct Narrow;
+///
+///     impl WidthProvider for Narrow {
+///         fn char_width(&self, ch: char) -> usize {
+///             char_width(ch).min(1)
+///         }
+///     }
+///
+///     assert_eq!(text_width_by("あいう", &Narrow), 3);
+///
+///     let mut iter = LineIter::new("あいう えおか", 4);
+///     iter.set_width_provider(&Narrow);
+///     assert_eq!(iter.next().unwrap(), "あいう");
+///     assert_eq!(iter.next().unwrap(), "えおか");
+///     assert!(iter.next().is_none());
+/// ```
+pub trait WidthProvider {
+    /// Returns the display width of the specified character.
+    fn char_width(&self, ch: char) -> usize;
+
+    /// Returns the display width of the specified text.
+    /// The default implementation sums up the widths of the characters.
+    fn text_width(&self, text: &str) -> usize {
+        text.chars().map(|ch| self.char_width(ch)).sum()
+    }
+}
+
+impl<F> WidthProvider for F
+where
+    F: Fn(char) -> usize,
+{
+    fn char_width(&self, ch: char) -> usize {
+        self(ch)
+    }
+}
+
+/// Returns the display width of the specified text, which is measured with
+/// the specified `WidthProvider`.
+///
+/// ```rust
+///     use linebreak::text_width_by;
+///
+///     assert_eq!(text_width_by("a\tb", &|ch: char| if ch == '\t' { 8 } else { 1 }), 10);
+/// ```
+pub fn text_width_by(text: &str, provider: &dyn WidthProvider) -> usize {
+    provider.text_width(text)
+}
+
+#[cfg(test)]
+mod test_of_width_provider {
+    use super::*;
+
+    #[test]
+    fn test_closure_as_width_provider() {
+        let f = |ch: char| if ch.is_ascii() { 1 } else { 3 };
+        assert_eq!(f.char_width('a'), 1);
+        assert_eq!(f.char_width('あ'), 3);
+        assert_eq!(f.text_width("aあb"), 5);
+        assert_eq!(text_width_by("", &f), 0);
+    }
+}
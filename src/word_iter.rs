@@ -0,0 +1,254 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use core::ops::Range;
+use core::str::CharIndices;
+
+use crate::code::CodeState;
+use crate::linebreak::{line_break_opportunity, LboState, LboType};
+use crate::options::BreakMode;
+use crate::uax14::Uax14State;
+use crate::unicode::{is_zero_width_attached, text_width_with, AmbiguousWidth};
+
+/// `WordKind` is the enum for the kinds of the tokens output by `WordIter`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WordKind {
+    /// A word, a quoted run or a fullwidth character, which is not broken by
+    /// `LineIter` unless it is wider than a line.
+    Word,
+
+    /// A run of spaces, at which `LineIter` can break a line.
+    Space,
+
+    /// A line break, such as a line feed or a CRLF.
+    Break,
+}
+
+/// `Word` is the struct for storing a token output by `WordIter`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Word<'a> {
+    /// The token string.
+    pub text: &'a str,
+    /// The byte range in the original text which the token covers.
+    pub range: Range<usize>,
+    /// The display width of the token.
+    pub width: usize,
+    /// The kind of the token.
+    pub kind: WordKind,
+}
+
+/// `WordIter` is the struct for dividing a text into the tokens between which
+/// `LineIter` can break lines.
+///
+/// This struct is useful for layout engines which place the tokens in their
+/// own ways while keeping the segmentation consistent with `LineIter`.
+///
+/// ```rust
+///     use linebreak::{WordIter, WordKind};
+///
+///     let mut iter = WordIter::new("Hello, world!\n句読点。");
+///     let texts: Vec<&str> = iter.by_ref().map(|word| word.text).collect();
+///     assert_eq!(texts, ["Hello,", " ", "world!", "\n", "句", "読", "点。"]);
+///
+///     let mut iter = WordIter::new("a  b");
+///     let word = iter.nth(1).unwrap();
+///     assert_eq!(word.text, "  ");
+///     assert_eq!(word.range, 1..3);
+///     assert_eq!(word.width, 2);
+///     assert_eq!(word.kind, WordKind::Space);
+/// ```
+pub struct WordIter<'a> {
+    text: &'a str,
+    chars: CharIndices<'a>,
+    pending: Option<(usize, char, LboType)>,
+    state: LboState,
+    uax14: Uax14State,
+    code: CodeState,
+    break_mode: BreakMode,
+    ambiguous_width: AmbiguousWidth,
+}
+
+impl<'a> WordIter<'a> {
+    /// Creates a `WordIter` instance which outputs the tokens of the given
+    /// string.
+    pub fn new(text: &'a str) -> WordIter<'a> {
+        WordIter {
+            text,
+            chars: text.char_indices(),
+            pending: None,
+            state: LboState {
+                lbo_type: LboType::Never,
+                lbo_prev: LboType::Never,
+                open_apos: 0,
+                open_quot: 0,
+            },
+            uax14: Uax14State::new(),
+            code: CodeState::new(),
+            break_mode: BreakMode::Basic,
+            ambiguous_width: AmbiguousWidth::Wide,
+        }
+    }
+
+    /// Sets the rules which determine the boundaries of the tokens.
+    /// This should be the same as the `BreakMode` of `LineIter`.
+    pub fn set_break_mode(&mut self, mode: BreakMode) {
+        self.break_mode = mode;
+    }
+
+    /// Sets the display width of East Asian ambiguous characters, which is
+    /// used for the widths of the tokens.
+    pub fn set_ambiguous_width(&mut self, ambiguous_width: AmbiguousWidth) {
+        self.ambiguous_width = ambiguous_width;
+    }
+
+    fn read_char(&mut self) -> Option<(usize, char, LboType)> {
+        if let Some(pending) = self.pending.take() {
+            return Some(pending);
+        }
+        let (i, ch) = self.chars.next()?;
+        match self.break_mode {
+            BreakMode::Basic => line_break_opportunity(ch, &mut self.state),
+            BreakMode::Uax14 => self.uax14.line_break_opportunity(ch, &mut self.state),
+            BreakMode::Code => self.code.line_break_opportunity(ch, &mut self.state),
+        }
+        Some((i, ch, self.state.lbo_type))
+    }
+}
+
+impl<'a> Iterator for WordIter<'a> {
+    type Item = Word<'a>;
+
+    /// Returns the next token.
+    fn next(&mut self) -> Option<Word<'a>> {
+        let (start, ch, lbo) = self.read_char()?;
+        let mut end = start + ch.len_utf8();
+
+        let kind = match lbo {
+            LboType::Break => WordKind::Break,
+            LboType::Space => WordKind::Space,
+            _ => WordKind::Word,
+        };
+
+        if kind == WordKind::Break {
+            // a CRLF is a line break.
+            if ch == '\r' {
+                match self.read_char() {
+                    Some((i, '\n', _)) => end = i + 1,
+                    next => self.pending = next,
+                }
+            }
+        } else {
+            let mut prev = lbo;
+            while let Some((i, c, l)) = self.read_char() {
+                let is_boundary = match kind {
+                    WordKind::Space => l != LboType::Space,
+                    _ => {
+                        !is_zero_width_attached(c)
+                            && (matches!(l, LboType::Break | LboType::Space)
+                                || (matches!(l, LboType::Before | LboType::Both)
+                                    && prev != LboType::Before)
+                                || prev == LboType::After)
+                    }
+                };
+                if is_boundary {
+                    self.pending = Some((i, c, l));
+                    break;
+                }
+                end = i + c.len_utf8();
+                prev = l;
+            }
+        }
+
+        let text = &self.text[start..end];
+        Some(Word {
+            text,
+            range: start..end,
+            width: text_width_with(text, self.ambiguous_width),
+            kind,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_of_word_iter {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn texts(iter: WordIter<'_>) -> Vec<&str> {
+        iter.map(|word| word.text).collect()
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(WordIter::new("").next().is_none());
+    }
+
+    #[test]
+    fn test_words_and_spaces() {
+        assert_eq!(
+            texts(WordIter::new("abc  def ghi ")),
+            ["abc", "  ", "def", " ", "ghi", " "]
+        );
+        let kinds: Vec<WordKind> = WordIter::new("a b").map(|w| w.kind).collect();
+        assert_eq!(kinds, [WordKind::Word, WordKind::Space, WordKind::Word]);
+    }
+
+    #[test]
+    fn test_line_breaks() {
+        assert_eq!(
+            texts(WordIter::new("ab\r\ncd\n\nef\r")),
+            ["ab", "\r\n", "cd", "\n", "\n", "ef", "\r"]
+        );
+        let word = WordIter::new("\r\n").next().unwrap();
+        assert_eq!(word.kind, WordKind::Break);
+        assert_eq!(word.width, 0);
+    }
+
+    #[test]
+    fn test_punctuations_and_brackets() {
+        assert_eq!(
+            texts(WordIter::new("a,b (cd)ef ((g)")),
+            ["a,", "b", " ", "(cd)", "ef", " ", "((g)"]
+        );
+        assert_eq!(
+            texts(WordIter::new("say \"hi there\"ok")),
+            ["say", " ", "\"hi", " ", "there\"", "ok"]
+        );
+    }
+
+    #[test]
+    fn test_fullwidth_chars() {
+        let mut iter = WordIter::new("「句読点」は、");
+        let word = iter.next().unwrap();
+        assert_eq!(word.text, "「句");
+        assert_eq!(word.width, 4);
+        assert_eq!(texts(iter), ["読", "点」", "は、"]);
+    }
+
+    #[test]
+    fn test_zero_width_attached() {
+        assert_eq!(texts(WordIter::new("か\u{3099}き")), ["か\u{3099}", "き"]);
+    }
+
+    #[test]
+    fn test_ambiguous_width() {
+        let mut iter = WordIter::new("\u{00B1}1");
+        iter.set_ambiguous_width(AmbiguousWidth::Narrow);
+        assert_eq!(iter.next().unwrap().width, 2);
+    }
+
+    #[test]
+    fn test_uax14() {
+        let mut iter = WordIter::new("abc-def ghi");
+        iter.set_break_mode(BreakMode::Uax14);
+        assert_eq!(texts(iter), ["abc-", "def", " ", "ghi"]);
+    }
+
+    #[test]
+    fn test_code() {
+        let mut iter = WordIter::new("f(a, \"b c\")");
+        iter.set_break_mode(BreakMode::Code);
+        assert_eq!(texts(iter), ["f(", "a,", " ", "\"b c\")"]);
+    }
+}
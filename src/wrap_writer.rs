@@ -0,0 +1,365 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use std::io;
+use std::str;
+
+use crate::chunk::{ChunkLines, Chunker};
+use crate::options::{Options, OptionsError};
+
+/// `WrapWriter` is the struct that implements `io::Write` and writes the
+/// incoming text to the inner writer with breaking it into lines.
+///
+/// The incoming bytes are buffered until a line break, or until an empty line
+/// in the paragraph mode, and each completed line or paragraph is wrapped and
+/// written to the inner writer.
+/// The line numbers, the maximum number of lines and the other states of the
+/// line breaking continue over the writes, so the output does not depend on
+/// how the text is divided into writes.
+/// The last incomplete line is written by `finish` or when this instance is
+/// dropped.
+///
+/// If the inner writer fails, the wrapped bytes which are not written yet are
+/// kept, and the error is returned by the next `write`, `flush` or `finish`,
+/// which writes them again before accepting more bytes.
+///
+/// ```rust
+///     use std::io::Write;
+///     use linebreak::{Options, WrapWriter};
+///
+///     let opts = Options::new(12).indent("  ");
+///     let mut writer = WrapWriter::with_options(Vec::new(), opts).unwrap();
+///     write!(writer, "The Rust programming ").unwrap();
+///     writeln!(writer, "language helps you").unwrap();
+///     write!(writer, "write faster.").unwrap();
+///
+///     let out = writer.finish().unwrap();
+///     assert_eq!(
+///         String::from_utf8(out).unwrap(),
+///         "  The Rust\n  programmin\n  g language\n  helps you\n  write\n  faster."
+///     );
+/// ```
+pub struct WrapWriter<W: io::Write> {
+    inner: Option<W>,
+    opts: Options,
+    input: Vec<u8>,
+    chunker: Chunker,
+    state: ChunkLines,
+    output: Vec<u8>,
+}
+
+impl<W: io::Write> WrapWriter<W> {
+    /// Creates a `WrapWriter` instance which writes lines within the specified
+    /// width to the specified writer.
+    pub fn new(inner: W, width: usize) -> WrapWriter<W> {
+        let opts = Options::new(width);
+        WrapWriter {
+            inner: Some(inner),
+            chunker: Chunker::new(&opts),
+            opts,
+            input: Vec::new(),
+            state: ChunkLines::new(),
+            output: Vec::new(),
+        }
+    }
+
+    /// Creates a `WrapWriter` instance which writes lines to the specified
+    /// writer with the settings in the specified `Options`.
+    /// If the settings are invalid, this function returns an `OptionsError`.
+    pub fn with_options(inner: W, opts: Options) -> Result<WrapWriter<W>, OptionsError> {
+        opts.validate()?;
+        let mut writer = WrapWriter::new(inner, opts.width);
+        writer.chunker = Chunker::new(&opts);
+        writer.opts = opts;
+        Ok(writer)
+    }
+
+    /// Returns a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        self.inner.as_ref().unwrap()
+    }
+
+    /// Writes the buffered incomplete line, flushes the inner writer, and
+    /// returns it.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_rest()?;
+        let mut inner = self.inner.take().unwrap();
+        inner.flush()?;
+        Ok(inner)
+    }
+
+    fn write_rest(&mut self) -> io::Result<()> {
+        if !self.input.is_empty() {
+            // the incomplete character at the end is invalid.
+            let e = str::from_utf8(&self.input).unwrap_err();
+            return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+        }
+        self.chunker.finish();
+        self.wrap_chunks()?;
+        self.write_output()
+    }
+
+    // Wraps the completed chunks and appends the lines to the output.
+    // A line ending is put after a line only if another line follows it, so
+    // the output is the same as the lines joined with the line ending.
+    // The empty line after the last line break is wrapped too, so that its
+    // line number, indent and suffix are output as `LineIter` does.
+    fn wrap_chunks(&mut self) -> io::Result<()> {
+        let ending = self.opts.line_ending.as_str().as_bytes();
+        while let Some((chunk, follows)) = self.chunker.pop() {
+            if self.state.is_done() {
+                continue;
+            }
+            let lines = match self.state.wrap(&chunk, follows, &self.opts) {
+                Ok(lines) => lines,
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidInput, e)),
+            };
+            let n = lines.len();
+            for (i, line) in lines.iter().enumerate() {
+                self.output.extend_from_slice(line.as_bytes());
+                if i + 1 < n || !self.state.is_done() {
+                    self.output.extend_from_slice(ending);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Writes the wrapped bytes to the inner writer, and keeps the bytes which
+    // are not written if the inner writer fails.
+    fn write_output(&mut self) -> io::Result<()> {
+        let inner = self.inner.as_mut().unwrap();
+        let mut written = 0;
+        let result = loop {
+            if written >= self.output.len() {
+                break Ok(());
+            }
+            match inner.write(&self.output[written..]) {
+                Ok(0) => break Err(io::Error::from(io::ErrorKind::WriteZero)),
+                Ok(n) => written += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => break Err(e),
+            }
+        };
+        self.output.drain(..written);
+        result
+    }
+}
+
+impl<W: io::Write> io::Write for WrapWriter<W> {
+    /// Buffers the specified bytes, and writes the completed lines.
+    ///
+    /// If the bytes which are kept by the failure of the previous write
+    /// cannot be written, or the specified bytes are not valid UTF-8, no byte
+    /// is accepted and an error is returned.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_output()?;
+
+        let len = self.input.len();
+        self.input.extend_from_slice(buf);
+        let valid_len = match str::from_utf8(&self.input) {
+            Ok(s) => s.len(),
+            // an incomplete character at the end may be completed by the
+            // following bytes.
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => {
+                self.input.truncate(len);
+                return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+            }
+        };
+        let text = str::from_utf8(&self.input[..valid_len]).unwrap();
+        text.chars().for_each(|ch| self.chunker.push(ch));
+        self.input.drain(..valid_len);
+        self.wrap_chunks()?;
+
+        // the bytes are accepted even if the inner writer fails, because the
+        // wrapped bytes are kept and written by the next call.
+        let _r = self.write_output();
+        Ok(buf.len())
+    }
+
+    /// Writes the wrapped bytes which are kept, and flushes the inner writer.
+    /// The buffered incomplete line is not written, because the following
+    /// bytes may continue it.
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_output()?;
+        self.inner.as_mut().unwrap().flush()
+    }
+}
+
+impl<W: io::Write> Drop for WrapWriter<W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            // Errors are ignored as the same as std::io::BufWriter.
+            let _r = self.write_rest();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_of_wrap_writer {
+    use super::*;
+    use crate::options::LineEnding;
+    use std::io::Write;
+
+    #[test]
+    fn test_write_lines() {
+        let mut writer = WrapWriter::new(Vec::new(), 10);
+        writer.write_all(b"abc def ghi jkl\n\nmno").unwrap();
+        assert_eq!(writer.get_ref(), b"abc def\nghi jkl\n\n");
+
+        writer.write_all(" pqr\n".as_bytes()).unwrap();
+        assert_eq!(writer.get_ref(), b"abc def\nghi jkl\n\nmno pqr\n");
+
+        writer.write_all("stu".as_bytes()).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.get_ref(), b"abc def\nghi jkl\n\nmno pqr\n");
+
+        let out = writer.finish().unwrap();
+        assert_eq!(out, b"abc def\nghi jkl\n\nmno pqr\nstu");
+    }
+
+    #[test]
+    fn test_write_multibyte_chars_split_into_pieces() {
+        let bytes = "あいうえお\n".as_bytes();
+        let mut writer = WrapWriter::new(Vec::new(), 6);
+        for b in bytes {
+            writer.write_all(&[*b]).unwrap();
+        }
+        let out = writer.finish().unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "あいう\nえお\n");
+    }
+
+    #[test]
+    fn test_write_invalid_utf8() {
+        let mut writer = WrapWriter::new(Vec::new(), 6);
+        match writer.write_all(b"\xff\n") {
+            Ok(_) => panic!(),
+            Err(e) => assert_eq!(e.kind(), io::ErrorKind::InvalidData),
+        }
+    }
+
+    #[test]
+    fn test_with_options() {
+        let opts = Options::new(8).first_indent("- ").indent("  ");
+        let mut writer = WrapWriter::with_options(Vec::new(), opts).unwrap();
+        writeln!(writer, "abc def ghi").unwrap();
+        writeln!(writer, "jkl").unwrap();
+        let out = writer.finish().unwrap();
+        assert_eq!(out, b"- abc\n  def\n  ghi\n- jkl\n");
+
+        let opts = Options::new(0);
+        assert!(WrapWriter::with_options(Vec::new(), opts).is_err());
+    }
+
+    #[test]
+    fn test_with_line_ending() {
+        let opts = Options::new(8).line_ending(LineEnding::CrLf);
+        let mut writer = WrapWriter::with_options(Vec::new(), opts).unwrap();
+        writeln!(writer, "abc def ghi").unwrap();
+        write!(writer, "jkl").unwrap();
+        let out = writer.finish().unwrap();
+        assert_eq!(out, b"abc def\r\nghi\r\njkl");
+    }
+
+    #[test]
+    fn test_state_continues_over_writes() {
+        let opts = Options::new(8).line_numbers(2);
+        let mut writer = WrapWriter::with_options(Vec::new(), opts).unwrap();
+        writeln!(writer, "abc").unwrap();
+        writeln!(writer, "def").unwrap();
+        assert_eq!(writer.finish().unwrap(), b" 1 abc\n 2 def\n 3 ");
+
+        let opts = Options::new(8).max_lines(2);
+        let mut writer = WrapWriter::with_options(Vec::new(), opts).unwrap();
+        writeln!(writer, "abc").unwrap();
+        writeln!(writer, "def").unwrap();
+        writeln!(writer, "ghi").unwrap();
+        assert_eq!(writer.finish().unwrap(), "abc\ndef…".as_bytes());
+    }
+
+    #[test]
+    fn test_same_as_line_iter() {
+        let texts = ["ー\"\r\n", "abc\n", "abc\n\n", ""];
+        let opts_list = [
+            Options::new(8),
+            Options::new(8).line_numbers(1),
+            Options::new(8).indent("> ").suffix(" <"),
+            Options::new(8).paragraph_mode(true).line_numbers(1),
+        ];
+        for text in texts {
+            for opts in &opts_list {
+                let lines: Vec<_> = crate::LineIter::with_options(text, opts).unwrap().collect();
+                let mut writer = WrapWriter::with_options(Vec::new(), opts.clone()).unwrap();
+                writer.write_all(text.as_bytes()).unwrap();
+                let out = writer.finish().unwrap();
+                assert_eq!(String::from_utf8(out).unwrap(), lines.join("\n"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_paragraph_mode_over_writes() {
+        let opts = Options::new(10).paragraph_mode(true);
+        let mut writer = WrapWriter::with_options(Vec::new(), opts.clone()).unwrap();
+        writeln!(writer, "abc").unwrap();
+        writeln!(writer, "def").unwrap();
+        assert_eq!(writer.get_ref(), b"");
+        writeln!(writer).unwrap();
+        assert_eq!(writer.get_ref(), b"abc def\n\n");
+        write!(writer, "ghi").unwrap();
+        let out = writer.finish().unwrap();
+
+        let mut writer = WrapWriter::with_options(Vec::new(), opts).unwrap();
+        writer.write_all(b"abc\ndef\n\nghi").unwrap();
+        assert_eq!(writer.finish().unwrap(), out);
+        assert_eq!(out, b"abc def\n\nghi");
+    }
+
+    struct FailingWriter {
+        out: Vec<u8>,
+        fails: bool,
+    }
+
+    impl io::Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.fails {
+                return Err(io::Error::new(io::ErrorKind::Other, "failed"));
+            }
+            self.out.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_inner_writer_fails() {
+        let inner = FailingWriter {
+            out: Vec::new(),
+            fails: true,
+        };
+        let mut writer = WrapWriter::new(inner, 5);
+        assert_eq!(writer.write(b"abc def\n").unwrap(), 8);
+        assert!(writer.write(b"ghi\n").is_err());
+        assert!(writer.flush().is_err());
+
+        writer.inner.as_mut().unwrap().fails = false;
+        assert_eq!(writer.write(b"ghi\n").unwrap(), 4);
+        let inner = writer.finish().unwrap();
+        assert_eq!(inner.out, b"abc\ndef\nghi\n");
+    }
+
+    #[test]
+    fn test_drop_writes_rest() {
+        let mut out = Vec::new();
+        {
+            let mut writer = WrapWriter::new(&mut out, 5);
+            write!(writer, "abc def").unwrap();
+        }
+        assert_eq!(out, b"abc\ndef");
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright (C) 2024 Takayuki Sato. All Rights Reserved.
+// This program is free software under MIT License.
+// See the file LICENSE in this distribution for more details.
+
+use core::fmt;
+
+use crate::line_iter::LineIter;
+use crate::options::Options;
+
+/// `Wrapped` is the struct that displays the given string with breaking it
+/// into lines.
+///
+/// This struct implements `fmt::Display`, so that the wrapped text can be
+/// written into formatting macros directly.
+/// The settings are not validated: if they leave no room for text, each line
+/// holds at least one column of text, as `LineIter::new` does for a width
+/// which is too narrow.
+///
+/// ```rust
+///     use linebreak::Wrapped;
+///
+///     let text = "The Rust programming language helps you write faster, \
+///         more reliable software.";
+///     assert_eq!(
+///         format!("{}", Wrapped::new(text, 32).indent("  ")),
+///         "  The Rust programming language\n  \
+///            helps you write faster, more\n  \
+///            reliable software."
+///     );
+/// ```
+#[derive(Clone, Debug)]
+pub struct Wrapped<'a> {
+    text: &'a str,
+    opts: Options,
+}
+
+impl<'a> Wrapped<'a> {
+    /// Creates a `Wrapped` instance which displays the given string with
+    /// breaking it into lines within the specified width.
+    pub fn new(text: &'a str, width: usize) -> Wrapped<'a> {
+        Wrapped {
+            text,
+            opts: Options::new(width),
+        }
+    }
+
+    /// Creates a `Wrapped` instance which displays the given string with the
+    /// settings in the specified `Options`.
+    pub fn with_options(text: &'a str, opts: Options) -> Wrapped<'a> {
+        Wrapped { text, opts }
+    }
+
+    /// Sets an indentation for the lines.
+    pub fn indent(mut self, indent: &str) -> Self {
+        self.opts = self.opts.indent(indent);
+        self
+    }
+
+    /// Sets an indentation for the first line of each paragraph.
+    pub fn first_indent(mut self, indent: &str) -> Self {
+        self.opts = self.opts.first_indent(indent);
+        self
+    }
+}
+
+impl fmt::Display for Wrapped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let iter = LineIter::from_options(self.text, &self.opts);
+        for (i, line) in iter.enumerate() {
+            if i > 0 {
+                f.write_str(self.opts.line_ending.as_str())?;
+            }
+            f.write_str(&line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_of_wrapped {
+    use super::*;
+    use crate::options::LineEnding;
+    use core::fmt::Write;
+
+    #[test]
+    fn test_display() {
+        let wrapped = Wrapped::new("abc def ghi", 7);
+        assert_eq!(wrapped.to_string(), "abc def\nghi");
+        assert_eq!(format!("[{}]", wrapped), "[abc def\nghi]");
+    }
+
+    #[test]
+    fn test_display_with_indents() {
+        let wrapped = Wrapped::new("abc def ghi", 7)
+            .first_indent("* ")
+            .indent("  ");
+        assert_eq!(wrapped.to_string(), "* abc\n  def\n  ghi");
+    }
+
+    #[test]
+    fn test_display_with_options() {
+        let opts = Options::new(9).prefix("# ");
+        let wrapped = Wrapped::with_options("abc def ghi", opts);
+        assert_eq!(wrapped.to_string(), "# abc def\n# ghi");
+    }
+
+    #[test]
+    fn test_display_with_line_ending() {
+        let opts = Options::new(7).line_ending(LineEnding::CrLf);
+        let wrapped = Wrapped::with_options("abc def ghi", opts);
+        assert_eq!(wrapped.to_string(), "abc def\r\nghi");
+    }
+
+    #[test]
+    fn test_display_with_invalid_options() {
+        let wrapped = Wrapped::new("abc", 0);
+        assert_eq!(wrapped.to_string(), "a\nb\nc");
+
+        let wrapped = Wrapped::with_options("abc", Options::new(2).prefix("> "));
+        let mut s = String::new();
+        write!(s, "{}", wrapped).unwrap();
+        assert_eq!(s, "> a\n> b\n> c");
+    }
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::needless_borrow, clippy::while_let_on_iterator)]
+
 use linebreak::LineIter;
 
 #[test]
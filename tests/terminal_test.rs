@@ -1,4 +1,8 @@
-use linebreak::{term_cols, term_size};
+#![cfg(feature = "terminal")]
+
+use linebreak::{
+    term_cols, term_cols_or, term_size, term_size_or, try_term_cols, try_term_size, Size,
+};
 use std::env;
 
 #[test]
@@ -22,3 +26,27 @@ fn it_should_get_terminal_size() {
         assert_eq!(size.row, 24);
     }
 }
+
+#[test]
+fn it_should_get_terminal_column_number_or_default() {
+    let cols = term_cols_or(100);
+    match try_term_cols() {
+        Ok(c) => assert_eq!(cols, c),
+        Err(_) => assert_eq!(cols, 100),
+    }
+}
+
+#[test]
+fn it_should_get_terminal_size_or_default() {
+    let size = term_size_or(Size { col: 100, row: 50 });
+    match try_term_size() {
+        Ok(sz) => {
+            assert_eq!(size.col, sz.col);
+            assert_eq!(size.row, sz.row);
+        }
+        Err(_) => {
+            assert_eq!(size.col, 100);
+            assert_eq!(size.row, 50);
+        }
+    }
+}
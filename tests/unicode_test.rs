@@ -1,3 +1,5 @@
+#![allow(clippy::bool_assert_comparison)]
+
 use linebreak::{char_width, is_print, text_width};
 
 #[test]
@@ -23,6 +25,7 @@ fn it_should_get_char_width() {
 fn it_should_get_text_width() {
     assert_eq!(text_width("Hello, world!"), 13);
     assert_eq!(text_width("こんにちわ、世界！"), 18);
+    assert_eq!(text_width("\u{26A0}\u{FE0F} 🔥"), 5);
 }
 
 #[test]